@@ -0,0 +1,69 @@
+//! Compares `RamMachine` with `CompiledMachine` on a long running loop.
+//!
+//! Run with `cargo bench`, the number of iterations can be passed as `cargo bench -- 10000000`.
+
+use std::hint::black_box;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use ram_machine::compiled::{CompiledCode, CompiledMachine};
+use ram_machine::interpreter::RamMachine;
+use ram_machine::operand::CellValue;
+use ram_machine::parser::RamCode;
+
+/// Sums `i * i mod 7` for `i` from `n` down to 1, 15 instructions per iteration.
+const PROGRAM: &str = "
+      read  1
+      load  =0
+      store 2
+LOOP: load  1
+      jzero END
+      mult  1
+      div   =7
+      mult  =7
+      store 3
+      load  1
+      mult  1
+      sub   3
+      add   2
+      store 2
+      load  1
+      sub   =1
+      store 1
+      jump  LOOP
+END:  write 2
+";
+
+const DEFAULT_ITERATIONS: u32 = 1_000_000;
+
+fn measure(name: &str, run: impl FnOnce() -> Vec<CellValue>) -> Duration {
+    let started = Instant::now();
+    let output = black_box(run());
+    let elapsed = started.elapsed();
+    println!("{name:<12} {elapsed:>12.2?}  output {output:?}");
+    elapsed
+}
+
+fn main() {
+    let iterations = std::env::args()
+        .skip(1)
+        .find_map(|arg| arg.parse().ok())
+        .unwrap_or(DEFAULT_ITERATIONS);
+    let code = RamCode::from_str(PROGRAM).unwrap();
+    let input = vec![CellValue::from(iterations)];
+    println!("{iterations} iterations");
+
+    let interpreted = measure("interpreter", || {
+        RamMachine::new(code.clone(), input.clone()).run().unwrap()
+    });
+    let compiled = measure("compiled", || {
+        let compiled = CompiledCode::new(&code);
+        CompiledMachine::new(&compiled, input.clone())
+            .run()
+            .unwrap()
+    });
+    println!(
+        "speedup      {:>11.1}x",
+        interpreted.as_secs_f64() / compiled.as_secs_f64()
+    );
+}
@@ -0,0 +1,300 @@
+use thiserror::Error;
+
+use crate::{
+    diagnostic::Diagnostic,
+    instruction::{polish_keyword, Dialect, EXTENDED_KEYWORDS, KEYWORDS, POLISH_KEYWORDS},
+    locale::{instruction_description, Lang, Localize, Message},
+    parser::{
+        cst::{Cst, Token, TokenKind},
+        source_map::Span,
+        ParseOptions, RamCode, COMMENT_START, LABEL_END,
+    },
+};
+
+/// Semantic information about a source file for editors, available even when it has errors.
+///
+/// Positions are a one-based line and a byte offset within that line.
+#[derive(Debug, Clone)]
+pub struct Analysis<'a> {
+    cst: Cst<'a>,
+    code: RamCode,
+    diagnostics: Vec<Diagnostic>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionKind {
+    Keyword,
+    Label,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    pub label: String,
+    pub kind: CompletionKind,
+    pub detail: String,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{}", self.localize(Lang::En))]
+pub enum RenameError {
+    NoLabel,
+    InvalidName(String),
+    AlreadyDefined(String),
+}
+
+/// Syntax of every instruction, their descriptions are `instruction_description`.
+const INSTRUCTION_SYNTAX: [(&str, &str); 18] = [
+    ("load", "LOAD operand"),
+    ("store", "STORE cell"),
+    ("add", "ADD operand"),
+    ("sub", "SUB operand"),
+    ("mult", "MULT operand"),
+    ("div", "DIV operand"),
+    ("read", "READ cell"),
+    ("write", "WRITE operand"),
+    ("jump", "JUMP label"),
+    ("jgtz", "JGTZ label"),
+    ("jzero", "JZERO label"),
+    ("halt", "HALT"),
+    ("mod", "MOD operand"),
+    ("jneg", "JNEG label"),
+    ("jgez", "JGEZ label"),
+    ("jnzero", "JNZERO label"),
+    ("inc", "INC"),
+    ("dec", "DEC"),
+];
+
+const JUMP_KEYWORDS: [&str; 6] = ["jump", "jgtz", "jzero", "jneg", "jgez", "jnzero"];
+
+impl<'a> Analysis<'a> {
+    pub fn new(source: &'a str, options: ParseOptions, lang: Lang) -> Self {
+        let (code, errors) = RamCode::parse_with_errors(source, options);
+        Analysis {
+            cst: Cst::parse(source),
+            code,
+            diagnostics: errors.diagnostics_in(source, lang),
+        }
+    }
+
+    pub fn code(&self) -> &RamCode {
+        &self.code
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Definition of the label at the position.
+    pub fn definition(&self, line: usize, column: usize) -> Option<Span> {
+        let label = self.label_at(line, column)?;
+        self.code.source_map.label(&label).cloned()
+    }
+
+    /// Every use of the label at the position, and its definitions if `include_definitions`.
+    pub fn references(&self, line: usize, column: usize, include_definitions: bool) -> Vec<Span> {
+        let Some(label) = self.label_at(line, column) else {
+            return Vec::new();
+        };
+        let definitions = self.code.source_map.labels.iter().map(|l| &l.span);
+        self.occurrences(&label)
+            .into_iter()
+            .filter(|span| include_definitions || !definitions.clone().any(|d| d == span))
+            .collect()
+    }
+
+    /// Markdown description of the keyword, operand or label at the position.
+    pub fn hover(&self, line: usize, column: usize, lang: Lang) -> Option<String> {
+        let token = self.token_at(line, column)?;
+        match token.kind {
+            TokenKind::Keyword => {
+                let (keyword, syntax) = instruction_syntax(token.text)?;
+                let description = instruction_description(keyword, lang)?;
+                Some(format!("`{syntax}`\n\n{description}"))
+            }
+            TokenKind::Argument if is_jump(self.keyword(line)?) => {
+                self.label_hover(token.text, lang)
+            }
+            TokenKind::Argument | TokenKind::String => operand_hover(token.text, lang),
+            TokenKind::Label => self.label_hover(token.text.trim_end_matches(LABEL_END), lang),
+            _ => None,
+        }
+    }
+
+    /// Keywords at the start of an instruction, labels after a jump.
+    pub fn completions(&self, line: usize, column: usize, lang: Lang) -> Vec<Completion> {
+        let Some(cst_line) = line.checked_sub(1).and_then(|i| self.cst.lines.get(i)) else {
+            return self.keyword_completions(lang);
+        };
+        if cst_line
+            .comment()
+            .is_some_and(|t| cst_line.columns(t).start < column)
+        {
+            return Vec::new();
+        }
+        let before: Vec<&Token> = cst_line
+            .code()
+            .filter(|t| cst_line.columns(t).end < column)
+            .collect();
+        match before.last() {
+            None => self.keyword_completions(lang),
+            Some(t) if t.kind == TokenKind::Label => self.keyword_completions(lang),
+            Some(t) if t.kind == TokenKind::Keyword && is_jump(t.text) => {
+                let mut labels: Vec<_> = self.code.jump_table.iter().collect();
+                labels.sort();
+                labels
+                    .into_iter()
+                    .map(|(label, target)| Completion {
+                        label: label.clone(),
+                        kind: CompletionKind::Label,
+                        detail: Message::InstructionNumber(*target).localize(lang),
+                    })
+                    .collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Spans to replace with `new_name` to rename the label at the position.
+    pub fn rename(
+        &self,
+        line: usize,
+        column: usize,
+        new_name: &str,
+    ) -> Result<Vec<Span>, RenameError> {
+        let label = self.label_at(line, column).ok_or(RenameError::NoLabel)?;
+        let valid = !new_name.is_empty()
+            && !new_name.contains(|c: char| {
+                c.is_whitespace() || c == LABEL_END || c == COMMENT_START || c == '"'
+            });
+        if !valid {
+            return Err(RenameError::InvalidName(new_name.to_owned()));
+        }
+        if new_name != label && self.code.jump_table.contains_key(new_name) {
+            return Err(RenameError::AlreadyDefined(new_name.to_owned()));
+        }
+        Ok(self.occurrences(&label))
+    }
+
+    /// Name of the label defined or jumped to at the position.
+    fn label_at(&self, line: usize, column: usize) -> Option<String> {
+        let token = self.token_at(line, column)?;
+        match token.kind {
+            TokenKind::Label => Some(token.text.trim_end_matches(LABEL_END).to_owned()),
+            TokenKind::Argument if is_jump(self.keyword(line)?) => Some(token.text.to_owned()),
+            _ => None,
+        }
+    }
+
+    fn occurrences(&self, label: &str) -> Vec<Span> {
+        let definitions = self
+            .code
+            .source_map
+            .labels
+            .iter()
+            .filter(|l| l.label == label)
+            .map(|l| l.span.clone());
+        let uses = self
+            .code
+            .instructions
+            .iter()
+            .zip(&self.code.source_map.instructions)
+            .filter(|(instruction, _)| instruction.label() == Some(label))
+            .map(|(_, span)| Span {
+                line: span.line,
+                columns: span.columns.end - label.len()..span.columns.end,
+                text: label.to_owned(),
+            });
+        let mut spans: Vec<Span> = definitions.chain(uses).collect();
+        spans.sort_by_key(|span| (span.line, span.columns.start));
+        spans
+    }
+
+    /// The code token under the position, which may also be just past its end.
+    fn token_at(&self, line: usize, column: usize) -> Option<&Token<'a>> {
+        let cst_line = self.cst.lines.get(line.checked_sub(1)?)?;
+        cst_line.code().find(|t| {
+            let columns = cst_line.columns(t);
+            columns.start <= column && column <= columns.end
+        })
+    }
+
+    fn keyword(&self, line: usize) -> Option<&'a str> {
+        let cst_line = self.cst.lines.get(line.checked_sub(1)?)?;
+        cst_line.token(TokenKind::Keyword).map(|t| t.text)
+    }
+
+    fn label_hover(&self, label: &str, lang: Lang) -> Option<String> {
+        let target = self.code.jump_table.get(label)?;
+        let text = match self.code.source_map.instruction(*target) {
+            Some(span) => format!(
+                "`{label}` — {}: `{}`",
+                Message::InstructionNumber(*target).localize(lang),
+                span.text
+            ),
+            None => format!(
+                "`{label}` — {}",
+                Message::InstructionNumber(*target).localize(lang)
+            ),
+        };
+        Some(text)
+    }
+
+    fn keyword_completions(&self, lang: Lang) -> Vec<Completion> {
+        let extended = match self.code.dialect {
+            Dialect::Strict => &[][..],
+            Dialect::Extended => &EXTENDED_KEYWORDS[..],
+        };
+        let polish = match self.code.keywords {
+            Lang::En => &[][..],
+            Lang::Pl => &POLISH_KEYWORDS[..],
+        };
+        let keywords = KEYWORDS.iter().chain(extended).copied().map(|k| (k, k));
+        let aliases = polish
+            .iter()
+            .filter(|(_, english)| {
+                KEYWORDS.contains(english) || self.code.dialect == Dialect::Extended
+            })
+            .copied();
+        keywords
+            .chain(aliases)
+            .filter_map(|(keyword, english)| {
+                let (english, syntax) = instruction_syntax(english)?;
+                let description = instruction_description(english, lang)?;
+                Some(Completion {
+                    label: keyword.to_owned(),
+                    kind: CompletionKind::Keyword,
+                    detail: format!("{syntax} — {description}"),
+                })
+            })
+            .collect()
+    }
+}
+
+/// English keyword of any spelling of an instruction keyword.
+fn canonical_keyword(keyword: &str) -> String {
+    let lowercase = keyword.to_lowercase();
+    match polish_keyword(&lowercase).unwrap_or(&lowercase) {
+        "jltz" => "jneg".to_owned(),
+        english => english.to_owned(),
+    }
+}
+
+/// English keyword and syntax of any spelling of an instruction keyword.
+fn instruction_syntax(keyword: &str) -> Option<(&'static str, &'static str)> {
+    let keyword = canonical_keyword(keyword);
+    INSTRUCTION_SYNTAX.into_iter().find(|(k, _)| *k == keyword)
+}
+
+fn is_jump(keyword: &str) -> bool {
+    JUMP_KEYWORDS.contains(&canonical_keyword(keyword).as_str())
+}
+
+fn operand_hover(operand: &str, lang: Lang) -> Option<String> {
+    let description = match operand.chars().next()? {
+        '=' => Message::NumberOperand,
+        '^' => Message::IndirectOperand,
+        '"' => Message::TextOperand,
+        _ => Message::CellOperand,
+    };
+    Some(format!("`{operand}` — {}", description.localize(lang)))
+}
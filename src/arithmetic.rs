@@ -0,0 +1,188 @@
+use std::{fmt, str::FromStr};
+
+use num_traits::{One, Signed, Zero};
+use thiserror::Error;
+
+use crate::locale::{Lang, Localize};
+use crate::operand::CellValue;
+
+/// What happens when a result doesn't fit in a cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Overflow {
+    /// Stop the machine with an error
+    #[default]
+    Checked,
+    /// Wrap around at the boundary of the type
+    Wrapping,
+    /// Clamp to the minimum or maximum value
+    Saturating,
+}
+
+/// How the quotient of `DIV` is rounded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Division {
+    /// Round toward zero, `-7 / 2 = -3`
+    #[default]
+    Truncating,
+    /// Round toward negative infinity, `-7 / 2 = -4`
+    Floor,
+    /// Keep the remainder non-negative, `-7 / -2 = 4`
+    Euclidean,
+}
+
+/// Semantics of `ADD`, `SUB`, `MULT`, `DIV` and `MOD`, written as e.g. `wrapping,floor`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ArithmeticMode {
+    pub overflow: Overflow,
+    pub division: Division,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{}", self.localize(Lang::En))]
+pub struct ArithmeticModeError(pub(crate) String);
+
+#[cfg(not(feature = "bigint"))]
+macro_rules! overflowing {
+    ($overflow:expr, $a:expr, $b:expr, $checked:ident, $wrapping:ident, $saturating:ident) => {
+        match $overflow {
+            Overflow::Checked => $a.$checked(*$b),
+            Overflow::Wrapping => Some($a.$wrapping(*$b)),
+            Overflow::Saturating => Some($a.$saturating(*$b)),
+        }
+    };
+}
+
+// Big integers never overflow, so every overflow mode behaves the same
+#[cfg(feature = "bigint")]
+macro_rules! overflowing {
+    ($overflow:expr, $a:expr, $b:expr, $checked:ident, $wrapping:ident, $saturating:ident) => {
+        $a.$checked($b)
+    };
+}
+
+impl ArithmeticMode {
+    pub fn add(&self, a: &CellValue, b: &CellValue) -> Option<CellValue> {
+        overflowing!(
+            self.overflow,
+            a,
+            b,
+            checked_add,
+            wrapping_add,
+            saturating_add
+        )
+    }
+
+    pub fn sub(&self, a: &CellValue, b: &CellValue) -> Option<CellValue> {
+        overflowing!(
+            self.overflow,
+            a,
+            b,
+            checked_sub,
+            wrapping_sub,
+            saturating_sub
+        )
+    }
+
+    pub fn mul(&self, a: &CellValue, b: &CellValue) -> Option<CellValue> {
+        overflowing!(
+            self.overflow,
+            a,
+            b,
+            checked_mul,
+            wrapping_mul,
+            saturating_mul
+        )
+    }
+
+    /// Divides `a` by `b`, division by zero fails in every mode.
+    pub fn div(&self, a: &CellValue, b: &CellValue) -> Option<CellValue> {
+        if b.is_zero() {
+            return None;
+        }
+        let quotient = overflowing!(
+            self.overflow,
+            a,
+            b,
+            checked_div,
+            wrapping_div,
+            saturating_div
+        )?;
+        // `MIN % -1` overflows, but the remainder is 0 anyway
+        #[cfg(not(feature = "bigint"))]
+        let remainder = a.wrapping_rem(*b);
+        #[cfg(feature = "bigint")]
+        let remainder = a % b;
+        if remainder.is_zero() {
+            return Some(quotient);
+        }
+        Some(match self.division {
+            Division::Truncating => quotient,
+            Division::Floor if remainder.is_negative() != b.is_negative() => {
+                quotient - CellValue::one()
+            }
+            Division::Floor => quotient,
+            Division::Euclidean if remainder.is_negative() && b.is_positive() => {
+                quotient - CellValue::one()
+            }
+            Division::Euclidean if remainder.is_negative() => quotient + CellValue::one(),
+            Division::Euclidean => quotient,
+        })
+    }
+
+    /// Remainder matching `div`, so that `a = b * div(a, b) + rem(a, b)`.
+    pub fn rem(&self, a: &CellValue, b: &CellValue) -> Option<CellValue> {
+        if b.is_zero() {
+            return None;
+        }
+        #[cfg(not(feature = "bigint"))]
+        let remainder = a.wrapping_rem(*b);
+        #[cfg(feature = "bigint")]
+        let remainder = a % b;
+        if remainder.is_zero() {
+            return Some(remainder);
+        }
+        Some(match self.division {
+            Division::Truncating => remainder,
+            Division::Floor if remainder.is_negative() != b.is_negative() => remainder + b,
+            Division::Floor => remainder,
+            Division::Euclidean if remainder.is_negative() && b.is_negative() => remainder - b,
+            Division::Euclidean if remainder.is_negative() => remainder + b,
+            Division::Euclidean => remainder,
+        })
+    }
+}
+
+impl FromStr for ArithmeticMode {
+    type Err = ArithmeticModeError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut mode = ArithmeticMode::default();
+        for part in s.split(',').map(str::trim) {
+            match part {
+                "checked" => mode.overflow = Overflow::Checked,
+                "wrapping" => mode.overflow = Overflow::Wrapping,
+                "saturating" => mode.overflow = Overflow::Saturating,
+                "truncating" => mode.division = Division::Truncating,
+                "floor" => mode.division = Division::Floor,
+                "euclidean" => mode.division = Division::Euclidean,
+                _ => return Err(ArithmeticModeError(part.to_owned())),
+            }
+        }
+        Ok(mode)
+    }
+}
+
+impl fmt::Display for ArithmeticMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let overflow = match self.overflow {
+            Overflow::Checked => "checked",
+            Overflow::Wrapping => "wrapping",
+            Overflow::Saturating => "saturating",
+        };
+        let division = match self.division {
+            Division::Truncating => "truncating",
+            Division::Floor => "floor",
+            Division::Euclidean => "euclidean",
+        };
+        write!(f, "{overflow},{division}")
+    }
+}
@@ -0,0 +1,242 @@
+use std::ops::Range;
+
+use crate::{instruction::Instruction, parser::RamCode};
+
+/// Instructions which are always executed one after another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BasicBlock {
+    /// Indices of the instructions in the block
+    pub instructions: Range<usize>,
+    /// Labels pointing at the first instruction
+    pub labels: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeKind {
+    /// A `JUMP` or falling through to the next block
+    Unconditional,
+    /// A conditional jump which is taken
+    True,
+    /// A conditional jump which is not taken
+    False,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edge {
+    pub from: usize,
+    pub to: usize,
+    pub kind: EdgeKind,
+}
+
+/// Basic blocks of a program and the jumps between them.
+///
+/// Blocks without outgoing edges end the program, by `HALT`, by running past the last
+/// instruction or by jumping to a label placed after it.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ControlFlowGraph {
+    pub blocks: Vec<BasicBlock>,
+    pub edges: Vec<Edge>,
+}
+
+impl ControlFlowGraph {
+    pub fn new(code: &RamCode) -> Self {
+        let len = code.instructions.len();
+        let mut leaders = vec![false; len];
+        if let Some(first) = leaders.first_mut() {
+            *first = true;
+        }
+        for target in code.jump_table.values() {
+            if let Some(leader) = leaders.get_mut(*target) {
+                *leader = true;
+            }
+        }
+        for (index, instruction) in code.instructions.iter().enumerate() {
+            if ends_block(instruction) {
+                if let Some(leader) = leaders.get_mut(index + 1) {
+                    *leader = true;
+                }
+            }
+        }
+
+        let starts: Vec<usize> = (0..len).filter(|index| leaders[*index]).collect();
+        let blocks: Vec<BasicBlock> = starts
+            .iter()
+            .zip(starts.iter().skip(1).chain([&len]))
+            .map(|(start, end)| {
+                let mut labels: Vec<String> = code
+                    .jump_table
+                    .iter()
+                    .filter(|(_, target)| *target == start)
+                    .map(|(label, _)| label.clone())
+                    .collect();
+                labels.sort();
+                BasicBlock {
+                    instructions: *start..*end,
+                    labels,
+                }
+            })
+            .collect();
+
+        let mut graph = ControlFlowGraph {
+            blocks,
+            edges: Vec::new(),
+        };
+        for (index, block) in graph.blocks.iter().enumerate() {
+            let last = &code.instructions[block.instructions.end - 1];
+            let target = last
+                .label()
+                .and_then(|label| code.jump_table.get(label))
+                .and_then(|target| graph.block_of(*target));
+            let next = graph.block_of(block.instructions.end);
+            let successors = match last {
+                Instruction::Halt => vec![],
+                Instruction::Jump(_) => vec![(target, EdgeKind::Unconditional)],
+                _ if last.label().is_some() => {
+                    vec![(target, EdgeKind::True), (next, EdgeKind::False)]
+                }
+                _ => vec![(next, EdgeKind::Unconditional)],
+            };
+            let edges: Vec<Edge> = successors
+                .into_iter()
+                .filter_map(|(to, kind)| {
+                    to.map(|to| Edge {
+                        from: index,
+                        to,
+                        kind,
+                    })
+                })
+                .collect();
+            graph.edges.extend(edges);
+        }
+        graph
+    }
+
+    /// Index of the block containing the instruction.
+    pub fn block_of(&self, instruction: usize) -> Option<usize> {
+        self.blocks
+            .iter()
+            .position(|block| block.instructions.contains(&instruction))
+    }
+
+    pub fn successors(&self, block: usize) -> impl Iterator<Item = &Edge> {
+        self.edges.iter().filter(move |edge| edge.from == block)
+    }
+
+    pub fn predecessors(&self, block: usize) -> impl Iterator<Item = &Edge> {
+        self.edges.iter().filter(move |edge| edge.to == block)
+    }
+
+    /// Graphviz description of the graph, block contents are taken from the source of `code`.
+    pub fn to_dot(&self, code: &RamCode) -> String {
+        let mut dot = String::from("digraph cfg {\n");
+        dot.push_str("    node [shape=box, fontname=\"monospace\"];\n");
+        dot.push_str("    entry [shape=oval, label=\"start\"];\n");
+        dot.push_str("    exit [shape=oval, label=\"end\"];\n");
+        for (index, _) in self.blocks.iter().enumerate() {
+            let text: String = self
+                .block_lines(code, index)
+                .iter()
+                .map(|line| escape_dot(line) + "\\l")
+                .collect();
+            dot.push_str(&format!("    b{index} [label=\"{text}\"];\n"));
+        }
+        for (from, to, kind) in self.exported_edges() {
+            match kind.and_then(edge_label) {
+                Some(label) => dot.push_str(&format!("    {from} -> {to} [label=\"{label}\"];\n")),
+                None => dot.push_str(&format!("    {from} -> {to};\n")),
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Mermaid flowchart of the graph, block contents are taken from the source of `code`.
+    pub fn to_mermaid(&self, code: &RamCode) -> String {
+        let mut mermaid = String::from("flowchart TD\n");
+        mermaid.push_str("    entry([start])\n");
+        mermaid.push_str("    exit([end])\n");
+        for (index, _) in self.blocks.iter().enumerate() {
+            let text: Vec<String> = self
+                .block_lines(code, index)
+                .iter()
+                .map(|line| escape_mermaid(line))
+                .collect();
+            mermaid.push_str(&format!("    b{index}[\"{}\"]\n", text.join("<br/>")));
+        }
+        for (from, to, kind) in self.exported_edges() {
+            match kind.and_then(edge_label) {
+                Some(label) => mermaid.push_str(&format!("    {from} -->|{label}| {to}\n")),
+                None => mermaid.push_str(&format!("    {from} --> {to}\n")),
+            }
+        }
+        mermaid
+    }
+
+    /// Labels and instructions of a block as they are written in the source.
+    fn block_lines(&self, code: &RamCode, block: usize) -> Vec<String> {
+        let block = &self.blocks[block];
+        let labels = block.labels.iter().map(|label| format!("{label}:"));
+        let instructions =
+            block
+                .instructions
+                .clone()
+                .map(|index| match code.source_map.instruction(index) {
+                    Some(span) => span.text.clone(),
+                    None => code.instructions[index].to_string(),
+                });
+        labels.chain(instructions).collect()
+    }
+
+    /// Edges between node names, including the ones from `entry` and to `exit`.
+    fn exported_edges(&self) -> Vec<(String, String, Option<EdgeKind>)> {
+        let mut edges = Vec::new();
+        if self.blocks.is_empty() {
+            edges.push(("entry".to_owned(), "exit".to_owned(), None));
+        } else {
+            edges.push(("entry".to_owned(), "b0".to_owned(), None));
+        }
+        for (index, _) in self.blocks.iter().enumerate() {
+            let successors: Vec<&Edge> = self.successors(index).collect();
+            for edge in &successors {
+                edges.push((
+                    format!("b{}", edge.from),
+                    format!("b{}", edge.to),
+                    Some(edge.kind),
+                ));
+            }
+            // A conditional jump past the end, or the end of the program
+            match successors.as_slice() {
+                [] => edges.push((format!("b{index}"), "exit".to_owned(), None)),
+                [edge] if edge.kind != EdgeKind::Unconditional => {
+                    let kind = match edge.kind {
+                        EdgeKind::True => EdgeKind::False,
+                        _ => EdgeKind::True,
+                    };
+                    edges.push((format!("b{index}"), "exit".to_owned(), Some(kind)));
+                }
+                _ => {}
+            }
+        }
+        edges
+    }
+}
+
+fn ends_block(instruction: &Instruction) -> bool {
+    instruction.label().is_some() || *instruction == Instruction::Halt
+}
+
+fn edge_label(kind: EdgeKind) -> Option<&'static str> {
+    match kind {
+        EdgeKind::Unconditional => None,
+        EdgeKind::True => Some("true"),
+        EdgeKind::False => Some("false"),
+    }
+}
+
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn escape_mermaid(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
@@ -1,15 +1,39 @@
-use ram_machine::error::ParserErrorChain;
-use ram_machine::parser::{CodeParseError, RamCode};
-use std::ops::Add;
-use std::path::PathBuf;
+use ram_machine::arithmetic::ArithmeticMode;
+use ram_machine::cfg::ControlFlowGraph;
+use ram_machine::compiled::{CompiledCode, CompiledMachine};
+use ram_machine::dataflow::DefiniteAssignment;
+use ram_machine::diagnostic::{Diagnostic, Severity};
+use ram_machine::format::{format, Case, FormatOptions};
+use ram_machine::instruction::{Dialect, Instruction};
+use ram_machine::lint::{lint, Level, LintConfig, Rule};
+use ram_machine::locale::{Lang, Localize, Localized, Message};
+use ram_machine::message;
+use ram_machine::optimize::{optimize, Pass};
+use ram_machine::parser::{ParseOptions, RamCode};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use std::{fs, io};
 use thiserror::Error;
 
-use ram_machine::interpreter::{RamMachine, RamMachineError, RunState};
+use ram_machine::interpreter::{ExecutionError, RamMachine, RunState};
+use ram_machine::limits::Limits;
+use ram_machine::operand::CellValue;
+use ram_machine::stream::{InputSource, OutputSink, ReaderSource, WriterSink};
+use ram_machine::trace::CSV_HEADER;
 
-use clap::{Command, CommandFactory, Parser as ClapParser, Subcommand, ValueHint};
+use clap::{
+    ArgMatches, Args, Command, CommandFactory, FromArgMatches, Parser as ClapParser, Subcommand,
+    ValueEnum, ValueHint,
+};
 use clap_complete::{generate, Generator, Shell};
 
+use crate::dap::dap;
+use crate::debug::debug;
+use crate::lsp::lsp;
+use crate::repl::repl;
+use crate::tui::tui;
+
 #[derive(ClapParser, Debug)]
 #[command(name = "ram", author, version, about, long_about = None)]
 struct Cli {
@@ -17,6 +41,15 @@ struct Cli {
     #[arg(short, long)]
     quiet: bool,
 
+    /// Language of messages (`en` or `pl`), taken from `LANG` by default
+    #[arg(long, global = true, value_name = "LANG")]
+    lang: Option<Lang>,
+
+    /// Keywords to accept, `pl` adds Polish aliases such as CZYTAJ (a `#!keywords` line in the
+    /// file overrides it)
+    #[arg(long, global = true, value_name = "LANG", default_value_t)]
+    keywords: Lang,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -29,99 +62,618 @@ pub enum Commands {
         file: PathBuf,
 
         /// Additional code input
-        input: Vec<i64>,
+        input: Vec<CellValue>,
 
-        /// Specifies the path to the input file from which data will be read
-        /// (input passed from the command line takes precedence)
+        /// Specifies the path to the input file from which data will be read as needed,
+        /// `-` reads from STDIN (input passed from the command line takes precedence)
         #[arg(short, long, value_name = "FILE", value_hint = ValueHint::FilePath)]
         input_file: Option<PathBuf>,
 
         /// Specifies the path to the output file where the results will be written
         #[arg(short, long, value_name = "FILE", value_hint = ValueHint::FilePath)]
         output_file: Option<PathBuf>,
+
+        /// Print every output value on its own line as soon as it is written
+        #[arg(long)]
+        stream: bool,
+
+        #[command(flatten)]
+        limits: LimitArgs,
+
+        /// Overflow (checked, wrapping, saturating) and division (truncating, floor, euclidean)
+        /// semantics of arithmetic instructions, e.g. `wrapping,floor`
+        #[arg(long, value_name = "MODE", default_value_t)]
+        arith: ArithmeticMode,
+
+        /// Print time and space cost under the uniform and logarithmic criterion
+        #[arg(short, long)]
+        stats: bool,
+
+        /// Resolve labels and decode operands before running, which is much faster for long
+        /// runs but doesn't collect statistics
+        #[arg(long, conflicts_with = "stats")]
+        compiled: bool,
+
+        #[command(flatten)]
+        parse: ParseArgs,
     },
 
     /// Validates ram code syntax of a given file
-    Check { file: PathBuf },
+    Check {
+        file: PathBuf,
+
+        /// Format of the reported diagnostics
+        #[arg(short, long, value_enum, default_value_t)]
+        format: DiagnosticFormat,
+
+        #[command(flatten)]
+        parse: ParseArgs,
+
+        /// Also reject cells which may be read before anything is stored in them
+        #[arg(long)]
+        strict: bool,
+    },
 
     /// Generate a shell completion file
     Init { shell: Shell },
-    // Repl,
-    /// Run ram machine code and see the tape, input, output for each instruction
+
+    /// Execute ram machine instructions interactively
+    Repl {
+        /// Maximum number of instructions executed for a single entry
+        #[arg(long, value_name = "STEPS", default_value_t = 1_000_000)]
+        max_steps: u64,
+    },
+
+    /// Step through ram machine code with breakpoints and watchpoints
     Debug {
         /// Path to file containing code to execute
         file: PathBuf,
 
         /// Additional code input
-        input: Vec<i64>,
+        input: Vec<CellValue>,
+
+        /// Specifies the path to the input file from which data will be read
+        /// (input passed from the command line takes precedence)
+        #[arg(short, long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+        input_file: Option<PathBuf>,
+
+        #[command(flatten)]
+        limits: StepLimitArgs,
+
+        #[command(flatten)]
+        parse: ParseArgs,
+
+        /// Number of executed instructions remembered for stepping backwards
+        #[arg(long, value_name = "STEPS", default_value_t = 100_000)]
+        history: usize,
+    },
+
+    /// Record every executed instruction as JSON Lines or CSV
+    Trace {
+        /// Path to file containing code to execute
+        file: PathBuf,
+
+        /// Additional code input
+        input: Vec<CellValue>,
 
         /// Specifies the path to the input file from which data will be read
         /// (input passed from the command line takes precedence)
         #[arg(short, long, value_name = "FILE", value_hint = ValueHint::FilePath)]
         input_file: Option<PathBuf>,
+
+        /// Specifies the path to the file where the trace will be written instead of STDOUT
+        #[arg(short, long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+        output_file: Option<PathBuf>,
+
+        /// Format of the trace records
+        #[arg(short, long, value_enum, default_value_t)]
+        format: TraceFormat,
+
+        /// Overflow (checked, wrapping, saturating) and division (truncating, floor, euclidean)
+        /// semantics of arithmetic instructions, e.g. `wrapping,floor`
+        #[arg(long, value_name = "MODE", default_value_t)]
+        arith: ArithmeticMode,
+
+        #[command(flatten)]
+        limits: LimitArgs,
+
+        #[command(flatten)]
+        parse: ParseArgs,
+    },
+
+    /// Rewrite files into the canonical form
+    Fmt {
+        /// Files to format in place
+        #[arg(required = true, value_hint = ValueHint::FilePath)]
+        files: Vec<PathBuf>,
+
+        /// Don't write the files, fail if any of them is not formatted
+        #[arg(long)]
+        check: bool,
+
+        /// Case of the instruction keywords
+        #[arg(long, value_name = "CASE", default_value_t)]
+        case: Case,
+
+        #[command(flatten)]
+        dialect: DialectArgs,
     },
+
+    /// Report common mistakes in ram machine code
+    Lint {
+        /// Files to check
+        #[arg(required = true, value_hint = ValueHint::FilePath)]
+        files: Vec<PathBuf>,
+
+        /// Format of the reported diagnostics
+        #[arg(short, long, value_enum, default_value_t)]
+        format: DiagnosticFormat,
+
+        /// Don't report the rule, the last of `-A`, `-W` and `-D` given for a rule wins and a
+        /// `#!lint` pragma in the file overrides them
+        #[arg(short = 'A', long, value_name = "RULE")]
+        allow: Vec<Rule>,
+
+        /// Report the rule as a warning
+        #[arg(short = 'W', long, value_name = "RULE")]
+        warn: Vec<Rule>,
+
+        /// Report the rule as an error
+        #[arg(short = 'D', long, value_name = "RULE")]
+        deny: Vec<Rule>,
+
+        #[command(flatten)]
+        dialect: DialectArgs,
+    },
+
+    /// Export the control-flow graph of ram machine code as Graphviz or Mermaid
+    Cfg {
+        /// Path to file containing code
+        file: PathBuf,
+
+        /// Format of the exported graph
+        #[arg(short, long, value_enum, default_value_t)]
+        format: GraphFormat,
+
+        /// Specifies the path to the file where the graph will be written instead of STDOUT
+        #[arg(short, long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+        output_file: Option<PathBuf>,
+
+        #[command(flatten)]
+        parse: ParseArgs,
+    },
+
+    /// Optimise ram machine code without changing its output
+    Opt {
+        /// Path to file containing code
+        file: PathBuf,
+
+        /// Specifies the path to the file where the optimised code will be written instead of
+        /// STDOUT
+        #[arg(short, long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+        output_file: Option<PathBuf>,
+
+        /// Don't run the pass (constant-folding, redundant-loads, jump-threading,
+        /// unreachable-code or labels)
+        #[arg(long, value_name = "PASS")]
+        skip: Vec<Pass>,
+
+        #[command(flatten)]
+        parse: ParseArgs,
+    },
+
+    /// Serve the language server protocol over STDIN and STDOUT for editors
+    Lsp {
+        #[command(flatten)]
+        dialect: DialectArgs,
+    },
+
+    /// Serve the debug adapter protocol over STDIN and STDOUT for editors
+    Dap,
+
+    /// Step through ram machine code in a full-screen terminal interface
+    Tui {
+        /// Path to file containing code to execute
+        file: PathBuf,
+
+        /// Additional code input
+        input: Vec<CellValue>,
+
+        /// Specifies the path to the input file from which data will be read
+        /// (input passed from the command line takes precedence)
+        #[arg(short, long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+        input_file: Option<PathBuf>,
+
+        #[command(flatten)]
+        limits: StepLimitArgs,
+
+        #[command(flatten)]
+        parse: ParseArgs,
+
+        /// Number of executed instructions remembered for stepping backwards
+        #[arg(long, value_name = "STEPS", default_value_t = 100_000)]
+        history: usize,
+    },
+}
+
+/// How the code of the commands which read a source file is parsed
+#[derive(Args, Debug)]
+pub struct ParseArgs {
+    /// Don't reject duplicate labels and undefined jump targets
+    #[arg(long)]
+    lenient: bool,
+
+    #[command(flatten)]
+    dialect: DialectArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct DialectArgs {
+    /// Instruction set to accept, `extended` adds MOD, JNEG, JGEZ, JNZERO, INC, DEC and
+    /// WRITE "text" (a `#!dialect` line in the file overrides it)
+    #[arg(long, value_name = "DIALECT", default_value_t)]
+    dialect: Dialect,
+}
+
+/// Limits which abort a run
+#[derive(Args, Debug)]
+pub struct LimitArgs {
+    #[command(flatten)]
+    steps: StepLimitArgs,
+
+    /// Maximum wall-clock time in milliseconds to run before aborting
+    #[arg(long, value_name = "MILLISECONDS")]
+    time_limit: Option<u64>,
+}
+
+/// Limit of the interactive commands, a time limit would count the time spent at the prompt
+#[derive(Args, Debug)]
+pub struct StepLimitArgs {
+    /// Maximum number of instructions to execute before aborting
+    #[arg(long, value_name = "STEPS")]
+    max_steps: Option<u64>,
+}
+
+impl ParseArgs {
+    fn options(&self, keywords: Lang) -> ParseOptions {
+        ParseOptions {
+            lenient: self.lenient,
+            dialect: self.dialect.dialect,
+            keywords,
+        }
+    }
+}
+
+impl LimitArgs {
+    fn apply<M: LimitedMachine>(&self, machine: M) -> M {
+        let time_limit = self.time_limit.map(Duration::from_millis);
+        machine.with_limits(Limits::new(self.steps.max_steps, time_limit))
+    }
+}
+
+impl StepLimitArgs {
+    fn apply(&self, machine: RamMachine) -> RamMachine {
+        machine.with_limits(Limits::new(self.max_steps, None))
+    }
+}
+
+/// The interpreter or the compiled machine, both run with the limits of `LimitArgs`
+trait LimitedMachine {
+    fn with_limits(self, limits: Limits) -> Self;
+}
+
+impl LimitedMachine for RamMachine {
+    fn with_limits(self, limits: Limits) -> Self {
+        RamMachine::with_limits(self, limits)
+    }
+}
+
+impl LimitedMachine for CompiledMachine<'_> {
+    fn with_limits(self, limits: Limits) -> Self {
+        CompiledMachine::with_limits(self, limits)
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DiagnosticFormat {
+    /// Human readable diagnostics with source snippets
+    #[default]
+    Human,
+    /// A JSON array of diagnostics for editor integration
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TraceFormat {
+    /// One JSON object per executed instruction
+    #[default]
+    Jsonl,
+    /// Comma separated values with a header row
+    Csv,
 }
 
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// Graphviz DOT
+    #[default]
+    Dot,
+    /// Mermaid flowchart
+    Mermaid,
+}
+
+#[allow(clippy::enum_variant_names)]
 #[derive(Error, Debug)]
+#[error("{}", self.localize(Lang::En))]
 pub enum RuntimeError {
-    #[error("Failed to convert `{0}` to an integer")]
     ConvertInputError(String),
 
-    #[error(transparent)]
-    CodeParseError(#[from] CodeParseError),
-
-    #[error(transparent)]
-    RamMachineError(#[from] RamMachineError),
+    ExecutionError(PathBuf, Box<ExecutionError>),
 
-    #[error("Could not read input from file: '{0}'")]
     ReadInputError(io::Error),
 
-    #[error("Could not read ram machine code from file: '{0}'")]
     ReadCodeError(io::Error),
 
-    #[error("Could not write output to file: '{0}'")]
     WriteOutputFileError(io::Error),
 
-    #[error("{0}")]
-    CheckFileError(ParserErrorChain),
+    CheckFileError(String),
+
+    TerminalError(io::Error),
+
+    NotFormattedError(usize),
+
+    LintError(usize),
+
+    LanguageServerError(String),
+}
+
+impl Localize for RuntimeError {
+    fn localize(&self, lang: Lang) -> String {
+        match self {
+            RuntimeError::ConvertInputError(input) => message!(
+                lang,
+                "Failed to convert `{input}` to an integer",
+                "Nie można zamienić `{input}` na liczbę całkowitą"
+            ),
+            RuntimeError::ExecutionError(path, e) => {
+                format!("{}:{}", path.display(), e.localize(lang))
+            }
+            RuntimeError::ReadInputError(e) => message!(
+                lang,
+                "Could not read input from file: '{e}'",
+                "Nie można odczytać wejścia z pliku: '{e}'"
+            ),
+            RuntimeError::ReadCodeError(e) => message!(
+                lang,
+                "Could not read ram machine code from file: '{e}'",
+                "Nie można odczytać kodu maszyny RAM z pliku: '{e}'"
+            ),
+            RuntimeError::WriteOutputFileError(e) => message!(
+                lang,
+                "Could not write output to file: '{e}'",
+                "Nie można zapisać wyjścia do pliku: '{e}'"
+            ),
+            RuntimeError::CheckFileError(diagnostics) => diagnostics.clone(),
+            RuntimeError::TerminalError(e) => message!(
+                lang,
+                "Could not access the terminal: '{e}'",
+                "Brak dostępu do terminala: '{e}'"
+            ),
+            RuntimeError::NotFormattedError(count) => message!(
+                lang,
+                "{count} file(s) not formatted",
+                "Liczba niesformatowanych plików: {count}"
+            ),
+            RuntimeError::LintError(count) => message!(
+                lang,
+                "{count} lint error(s) found",
+                "Liczba błędów znalezionych przez linter: {count}"
+            ),
+            RuntimeError::LanguageServerError(e) => message!(
+                lang,
+                "Language server failed: '{e}'",
+                "Błąd serwera języka: '{e}'"
+            ),
+        }
+    }
 }
 
 fn print_completions<G: Generator>(gen: G, cmd: &mut Command) {
     generate(gen, cmd, cmd.get_name().to_string(), &mut io::stdout());
 }
 
-pub fn app() -> Result<(), RuntimeError> {
-    let cli = Cli::parse();
+#[allow(clippy::redundant_closure)]
+pub fn read_input(
+    mut input: Vec<CellValue>,
+    input_file: Option<PathBuf>,
+) -> Result<Vec<CellValue>, RuntimeError> {
+    if let Some(input_file) = input_file {
+        let file = fs::read_to_string(input_file).map_err(|e| RuntimeError::ReadInputError(e))?;
+        for s in file.split_whitespace() {
+            input.push(
+                s.parse::<CellValue>()
+                    .map_err(|_| RuntimeError::ConvertInputError(s.to_string()))?,
+            );
+        }
+    }
+    Ok(input)
+}
+
+fn write_trace(
+    mut machine: RamMachine,
+    format: TraceFormat,
+    out: &mut impl Write,
+) -> Result<Result<(), ExecutionError>, io::Error> {
+    if format == TraceFormat::Csv {
+        writeln!(out, "{CSV_HEADER}")?;
+    }
+    loop {
+        let state = machine.run_line();
+        for record in machine.take_trace() {
+            match format {
+                TraceFormat::Jsonl => writeln!(out, "{}", record.to_json())?,
+                TraceFormat::Csv => writeln!(out, "{}", record.to_csv())?,
+            }
+        }
+        match state {
+            Ok(RunState::Running) => {}
+            Ok(RunState::Halted) => return Ok(Ok(())),
+            Err(e) => return Ok(Err(e)),
+        }
+    }
+}
+
+fn render_diagnostics(
+    file: &Path,
+    diagnostics: &[Diagnostic],
+    format: DiagnosticFormat,
+    lang: Lang,
+) -> String {
+    let path = file.display().to_string();
+    match format {
+        DiagnosticFormat::Human => {
+            let mut output: String = diagnostics
+                .iter()
+                .map(|d| d.render_in(&path, lang) + "\n")
+                .collect();
+            output.push_str(&Message::CouldNotParse(&path, diagnostics.len()).localize(lang));
+            output
+        }
+        DiagnosticFormat::Json => {
+            serde_json::Value::Array(diagnostics.iter().map(|d| d.to_json(&path)).collect())
+                .to_string()
+        }
+    }
+}
+
+fn parse_code(
+    file: &Path,
+    source: &str,
+    format: DiagnosticFormat,
+    options: ParseOptions,
+    lang: Lang,
+) -> Result<RamCode, RuntimeError> {
+    RamCode::parse_all(source, options).map_err(|errors| {
+        RuntimeError::CheckFileError(render_diagnostics(
+            file,
+            &errors.diagnostics_in(source, lang),
+            format,
+            lang,
+        ))
+    })
+}
 
+pub fn app() -> Result<(), Localized<RuntimeError>> {
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let lang = cli.lang.unwrap_or_else(Lang::from_env);
+    run(cli, &matches, lang).map_err(|e| Localized(e, lang))
+}
+
+/// Levels given with `-A`, `-W` and `-D` in command line order, so that the last one wins.
+fn lint_levels(matches: &ArgMatches) -> Vec<(Rule, Level)> {
+    let Some(("lint", matches)) = matches.subcommand() else {
+        return Vec::new();
+    };
+    let mut levels = Vec::new();
+    for (id, level) in [
+        ("allow", Level::Allow),
+        ("warn", Level::Warn),
+        ("deny", Level::Deny),
+    ] {
+        let (Some(indices), Some(rules)) = (matches.indices_of(id), matches.get_many::<Rule>(id))
+        else {
+            continue;
+        };
+        levels.extend(indices.zip(rules.map(|rule| (*rule, level))));
+    }
+    levels.sort_by_key(|(index, _)| *index);
+    levels.into_iter().map(|(_, level)| level).collect()
+}
+
+#[allow(clippy::redundant_closure)]
+fn run(cli: Cli, matches: &ArgMatches, lang: Lang) -> Result<(), RuntimeError> {
+    let keywords = cli.keywords;
     match cli.command {
         Commands::Run {
             file,
             input,
             input_file,
             output_file,
+            limits,
+            stream,
+            arith,
+            stats,
+            compiled,
+            parse,
         } => {
-            let mut input = input;
-
-            if let Some(input_file) = input_file {
-                let file =
-                    fs::read_to_string(input_file).map_err(|e| RuntimeError::ReadInputError(e))?;
-                for s in file.split_whitespace() {
-                    input.push(
-                        s.parse::<i64>()
-                            .map_err(|_| RuntimeError::ConvertInputError(s.to_string()))?,
-                    );
-                }
-            }
-
             let unparsed_file =
-                fs::read_to_string(file).map_err(|e| RuntimeError::ReadCodeError(e))?;
-            let interpreter = RamMachine::from_str(&unparsed_file, input)?;
-            let output = interpreter.run()?;
+                fs::read_to_string(&file).map_err(|e| RuntimeError::ReadCodeError(e))?;
+            let code = parse_code(
+                &file,
+                &unparsed_file,
+                DiagnosticFormat::Human,
+                parse.options(keywords),
+                lang,
+            )?;
+            let source: Option<Box<dyn InputSource + Send>> = match input_file {
+                Some(path) if path.as_os_str() == "-" => Some(Box::new(ReaderSource::stdin())),
+                Some(path) => Some(Box::new(
+                    ReaderSource::file(path).map_err(RuntimeError::ReadInputError)?,
+                )),
+                None => None,
+            };
+            // Values are printed in order with the text of `WRITE "text"` when there is any
+            let stream = stream
+                || code
+                    .instructions
+                    .iter()
+                    .any(|instruction| matches!(instruction, Instruction::WriteString(_)));
+            let sink: Option<Box<dyn OutputSink + Send>> = match (stream, cli.quiet) {
+                (true, false) => Some(Box::new(WriterSink::stdout())),
+                _ => None,
+            };
+            let (output, run_stats) = if compiled {
+                let compiled_code = CompiledCode::new(&code);
+                let mut machine =
+                    CompiledMachine::new(&compiled_code, input).with_arithmetic_mode(arith);
+                if let Some(source) = source {
+                    machine = machine.with_input_source(source);
+                }
+                if let Some(sink) = sink {
+                    machine = machine.with_output_sink(sink);
+                }
+                let output = limits
+                    .apply(machine)
+                    .run()
+                    .map_err(|e| RuntimeError::ExecutionError(file, Box::new(e)))?;
+                (output, None)
+            } else {
+                let mut interpreter = RamMachine::new(code, input).with_arithmetic_mode(arith);
+                if let Some(source) = source {
+                    interpreter = interpreter.with_input_source(source);
+                }
+                if let Some(sink) = sink {
+                    interpreter = interpreter.with_output_sink(sink);
+                }
+                let interpreter = limits.apply(interpreter);
+                // Counting the logarithmic costs slows every step down
+                let result = match stats {
+                    true => interpreter
+                        .run_with_stats()
+                        .map(|(output, run_stats)| (output, Some(run_stats))),
+                    false => interpreter.run().map(|output| (output, None)),
+                };
+                result.map_err(|e| RuntimeError::ExecutionError(file, Box::new(e)))?
+            };
 
-            if !cli.quiet {
+            if !cli.quiet && !stream {
                 println!("{:?}", output);
             }
 
+            if let Some(run_stats) = run_stats {
+                println!("{}", run_stats.localize(lang));
+            }
+
             if let Some(output_file) = output_file {
                 fs::write(
                     output_file,
@@ -134,30 +686,29 @@ pub fn app() -> Result<(), RuntimeError> {
                 .map_err(|e| RuntimeError::WriteOutputFileError(e))?;
             }
         }
-        Commands::Check { file } => {
+        Commands::Check {
+            file,
+            format,
+            parse,
+            strict,
+        } => {
             let unparsed_file =
-                fs::read_to_string(file).map_err(|e| RuntimeError::ReadCodeError(e))?;
-            let mut errors = ParserErrorChain::new();
-
-            let mut code = RamCode::new();
-            let lines = unparsed_file.lines().into_iter().enumerate();
-            for (index, line) in lines {
-                if line.is_empty() {
-                    continue;
-                };
-                code.push_line(line).unwrap_or_else(|err| {
-                    errors.add((
-                        index
-                            .add(1)
-                            .try_into()
-                            .expect("Could not convert the line number to u32"),
-                        err,
-                    ))
-                });
+                fs::read_to_string(&file).map_err(|e| RuntimeError::ReadCodeError(e))?;
+            let code = parse_code(&file, &unparsed_file, format, parse.options(keywords), lang)?;
+            if strict {
+                let diagnostics =
+                    DefiniteAssignment::new(&code).diagnostics(&code, &unparsed_file, lang);
+                if !diagnostics.is_empty() {
+                    return Err(RuntimeError::CheckFileError(render_diagnostics(
+                        &file,
+                        &diagnostics,
+                        format,
+                        lang,
+                    )));
+                }
             }
-
-            if !errors.is_empty() {
-                return Err(RuntimeError::CheckFileError(errors));
+            if format == DiagnosticFormat::Json {
+                println!("{}", render_diagnostics(&file, &[], format, lang));
             }
         }
         Commands::Init { shell } => {
@@ -165,34 +716,215 @@ pub fn app() -> Result<(), RuntimeError> {
             eprintln!("Generating completion file for {shell:?}...");
             print_completions(shell, &mut cmd)
         }
+        Commands::Repl { max_steps } => repl(Some(max_steps), lang, keywords)?,
         Commands::Debug {
             file,
             input,
             input_file,
+            limits,
+            parse,
+            history,
         } => {
-            let mut input = input;
-
-            if let Some(input_file) = input_file {
-                let file =
-                    fs::read_to_string(input_file).map_err(|e| RuntimeError::ReadInputError(e))?;
-                for s in file.split_whitespace() {
-                    input.push(
-                        s.parse::<i64>()
-                            .map_err(|_| RuntimeError::ConvertInputError(s.to_string()))?,
-                    );
+            let input = read_input(input, input_file)?;
+            let unparsed_file =
+                fs::read_to_string(&file).map_err(|e| RuntimeError::ReadCodeError(e))?;
+            let code = parse_code(
+                &file,
+                &unparsed_file,
+                DiagnosticFormat::Human,
+                parse.options(keywords),
+                lang,
+            )?;
+            let interpreter = limits.apply(RamMachine::new(code, input).with_history(history));
+            debug(interpreter, lang)?;
+        }
+        Commands::Trace {
+            file,
+            input,
+            input_file,
+            output_file,
+            format,
+            arith,
+            limits,
+            parse,
+        } => {
+            let input = read_input(input, input_file)?;
+            let unparsed_file = fs::read_to_string(&file).map_err(RuntimeError::ReadCodeError)?;
+            let code = parse_code(
+                &file,
+                &unparsed_file,
+                DiagnosticFormat::Human,
+                parse.options(keywords),
+                lang,
+            )?;
+            let interpreter = limits.apply(
+                RamMachine::new(code, input)
+                    .with_arithmetic_mode(arith)
+                    .with_trace(),
+            );
+            let mut out: BufWriter<Box<dyn Write>> = BufWriter::new(match output_file {
+                Some(path) => {
+                    Box::new(fs::File::create(path).map_err(RuntimeError::WriteOutputFileError)?)
+                }
+                None => Box::new(io::stdout()),
+            });
+            let result = write_trace(interpreter, format, &mut out)
+                .and_then(|result| out.flush().map(|_| result))
+                .map_err(RuntimeError::WriteOutputFileError)?;
+            result.map_err(|e| RuntimeError::ExecutionError(file, Box::new(e)))?;
+        }
+        Commands::Fmt {
+            files,
+            check,
+            case,
+            dialect,
+        } => {
+            let mut unformatted = 0;
+            for file in files {
+                let source = fs::read_to_string(&file).map_err(RuntimeError::ReadCodeError)?;
+                let options = ParseOptions {
+                    lenient: true,
+                    dialect: dialect.dialect,
+                    keywords,
+                };
+                parse_code(&file, &source, DiagnosticFormat::Human, options, lang)?;
+                let formatted = format(&source, FormatOptions { case });
+                if formatted == source {
+                    continue;
+                }
+                if check {
+                    unformatted += 1;
+                    if !cli.quiet {
+                        println!("{}", file.display());
+                    }
+                } else {
+                    fs::write(&file, formatted).map_err(RuntimeError::WriteOutputFileError)?;
                 }
             }
-
-            let unparsed_file =
-                fs::read_to_string(file).map_err(|e| RuntimeError::ReadCodeError(e))?;
-            let mut interpreter = RamMachine::from_str(&unparsed_file, input)?;
-            loop {
-                interpreter.print_state();
-                if interpreter.run_line()? == RunState::Halted {
-                    break;
+            if unformatted > 0 {
+                return Err(RuntimeError::NotFormattedError(unformatted));
+            }
+        }
+        Commands::Lint {
+            files,
+            format,
+            dialect,
+            ..
+        } => {
+            let mut config = LintConfig::default();
+            for (rule, level) in lint_levels(matches) {
+                config.set(rule, level);
+            }
+            let mut errors = 0;
+            let mut json = Vec::new();
+            for file in files {
+                let source = fs::read_to_string(&file).map_err(RuntimeError::ReadCodeError)?;
+                let options = ParseOptions {
+                    lenient: false,
+                    dialect: dialect.dialect,
+                    keywords,
+                };
+                let code = parse_code(&file, &source, format, options, lang)?;
+                let diagnostics = lint(&source, &code, &config, lang);
+                errors += diagnostics
+                    .iter()
+                    .filter(|d| d.severity == Severity::Error)
+                    .count();
+                let path = file.display().to_string();
+                match format {
+                    DiagnosticFormat::Human => {
+                        for diagnostic in &diagnostics {
+                            println!("{}", diagnostic.render_in(&path, lang));
+                        }
+                    }
+                    DiagnosticFormat::Json => {
+                        json.extend(diagnostics.iter().map(|d| d.to_json(&path)))
+                    }
+                }
+            }
+            if format == DiagnosticFormat::Json {
+                println!("{}", serde_json::Value::Array(json));
+            }
+            if errors > 0 {
+                return Err(RuntimeError::LintError(errors));
+            }
+        }
+        Commands::Cfg {
+            file,
+            format,
+            output_file,
+            parse,
+        } => {
+            let unparsed_file = fs::read_to_string(&file).map_err(RuntimeError::ReadCodeError)?;
+            let code = parse_code(
+                &file,
+                &unparsed_file,
+                DiagnosticFormat::Human,
+                parse.options(keywords),
+                lang,
+            )?;
+            let graph = ControlFlowGraph::new(&code);
+            let exported = match format {
+                GraphFormat::Dot => graph.to_dot(&code),
+                GraphFormat::Mermaid => graph.to_mermaid(&code),
+            };
+            match output_file {
+                Some(path) => {
+                    fs::write(path, exported).map_err(RuntimeError::WriteOutputFileError)?
+                }
+                None => print!("{exported}"),
+            }
+        }
+        Commands::Opt {
+            file,
+            output_file,
+            skip,
+            parse,
+        } => {
+            let unparsed_file = fs::read_to_string(&file).map_err(RuntimeError::ReadCodeError)?;
+            let code = parse_code(
+                &file,
+                &unparsed_file,
+                DiagnosticFormat::Human,
+                parse.options(keywords),
+                lang,
+            )?;
+            let passes: Vec<Pass> = Pass::ALL
+                .into_iter()
+                .filter(|pass| !skip.contains(pass))
+                .collect();
+            let optimized = format(
+                &optimize(&code, &passes).to_string(),
+                FormatOptions::default(),
+            );
+            match output_file {
+                Some(path) => {
+                    fs::write(path, optimized).map_err(RuntimeError::WriteOutputFileError)?
                 }
+                None => print!("{optimized}"),
             }
-            interpreter.print_state();
+        }
+        Commands::Lsp { dialect } => lsp(dialect.dialect, lang, keywords)?,
+        Commands::Dap => dap(lang, keywords)?,
+        Commands::Tui {
+            file,
+            input,
+            input_file,
+            limits,
+            parse,
+            history,
+        } => {
+            let input = read_input(input, input_file)?;
+            let unparsed_file = fs::read_to_string(&file).map_err(RuntimeError::ReadCodeError)?;
+            let code = parse_code(
+                &file,
+                &unparsed_file,
+                DiagnosticFormat::Human,
+                parse.options(keywords),
+                lang,
+            )?;
+            let interpreter = limits.apply(RamMachine::new(code, input).with_history(history));
+            tui(interpreter, &unparsed_file, lang)?;
         }
     };
     Ok(())
@@ -0,0 +1,349 @@
+use std::time::Duration;
+
+use num_traits::{One, Signed, Zero};
+
+use crate::{
+    arithmetic::ArithmeticMode,
+    instruction::Instruction,
+    interpreter::{BufferError, ExecutionError, JumpError, RamMachineError},
+    limits::Limits,
+    operand::{
+        address_in_cell, write_cell, CellAddress, CellOperand, CellValue, ExpandError, Operand,
+    },
+    parser::RamCode,
+    stream::{InputSource, OutputSink, Streams},
+};
+
+/// Operand of an instruction which reads a value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Value {
+    Number(CellValue),
+    Cell(CellAddress),
+    /// The cell whose address is stored in the cell
+    Indirect(CellAddress),
+}
+
+/// Operand of an instruction which stores a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Address {
+    Cell(CellAddress),
+    Indirect(CellAddress),
+}
+
+/// Index of the instruction a jump goes to, `None` if its label doesn't exist.
+type Target = Option<usize>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Op {
+    Load(Value),
+    Store(Address),
+    Add(Value),
+    Sub(Value),
+    Mult(Value),
+    Div(Value),
+    Mod(Value),
+    Inc,
+    Dec,
+    Read(Address),
+    Write(Value),
+    WriteString(String),
+    Jump(Target),
+    Jgtz(Target),
+    Jzero(Target),
+    Jneg(Target),
+    Jgez(Target),
+    Jnzero(Target),
+    Halt,
+}
+
+/// Program with labels resolved to instruction indices and operands decoded, ready to be run by
+/// `CompiledMachine` any number of times.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompiledCode {
+    ops: Vec<Op>,
+    /// Used to report the failing instruction
+    code: RamCode,
+}
+
+/// Runs `CompiledCode` with the same results and errors as `RamMachine`, but without history,
+/// tracing, observers or cost accounting.
+pub struct CompiledMachine<'a> {
+    code: &'a CompiledCode,
+    tape: Vec<Option<CellValue>>,
+    pointer: usize,
+    input: Vec<CellValue>,
+    input_pointer: usize,
+    output: Vec<CellValue>,
+    steps: u64,
+    limits: Limits,
+    streams: Streams,
+    arithmetic_mode: ArithmeticMode,
+}
+
+impl CompiledCode {
+    pub fn new(code: &RamCode) -> Self {
+        let target = |label: &String| code.jump_table.get(label).copied();
+        let ops = code
+            .instructions
+            .iter()
+            .map(|instruction| {
+                use Instruction::*;
+                match instruction {
+                    Load(o) => Op::Load(value(o)),
+                    Store(o) => Op::Store(address(o)),
+                    Add(o) => Op::Add(value(o)),
+                    Sub(o) => Op::Sub(value(o)),
+                    Mult(o) => Op::Mult(value(o)),
+                    Div(o) => Op::Div(value(o)),
+                    Mod(o) => Op::Mod(value(o)),
+                    Inc => Op::Inc,
+                    Dec => Op::Dec,
+                    Read(o) => Op::Read(address(o)),
+                    Write(o) => Op::Write(value(o)),
+                    WriteString(text) => Op::WriteString(text.clone()),
+                    Jump(label) => Op::Jump(target(label)),
+                    Jgtz(label) => Op::Jgtz(target(label)),
+                    Jzero(label) => Op::Jzero(target(label)),
+                    Jneg(label) => Op::Jneg(target(label)),
+                    Jgez(label) => Op::Jgez(target(label)),
+                    Jnzero(label) => Op::Jnzero(target(label)),
+                    Halt => Op::Halt,
+                }
+            })
+            .collect();
+        CompiledCode {
+            ops,
+            code: code.clone(),
+        }
+    }
+
+    pub fn code(&self) -> &RamCode {
+        &self.code
+    }
+}
+
+impl From<&RamCode> for CompiledCode {
+    fn from(code: &RamCode) -> Self {
+        CompiledCode::new(code)
+    }
+}
+
+fn value(operand: &Operand) -> Value {
+    match operand {
+        Operand::Number(v) => Value::Number(v.clone()),
+        Operand::ValueInCell(cell) => Value::Cell(*cell),
+        Operand::ValueOfValueInCell(cell) => Value::Indirect(*cell),
+    }
+}
+
+fn address(cell_operand: &CellOperand) -> Address {
+    match cell_operand {
+        CellOperand::AddressOfCell(cell) => Address::Cell(*cell),
+        CellOperand::AddressOfCellInCell(cell) => Address::Indirect(*cell),
+    }
+}
+
+impl<'a> CompiledMachine<'a> {
+    pub fn new(code: &'a CompiledCode, input: Vec<CellValue>) -> Self {
+        CompiledMachine {
+            code,
+            tape: vec![None],
+            pointer: 0,
+            input,
+            input_pointer: 0,
+            output: Vec::new(),
+            steps: 0,
+            limits: Limits::default(),
+            streams: Streams::default(),
+            arithmetic_mode: ArithmeticMode::default(),
+        }
+    }
+
+    /// Reads further values from `source` once the input passed to `new` is used up.
+    pub fn with_input_source(mut self, source: impl InputSource + Send + 'static) -> Self {
+        self.streams.source = Some(Box::new(source));
+        self
+    }
+
+    /// Passes every written value to `sink` as well as collecting it in the output.
+    pub fn with_output_sink(mut self, sink: impl OutputSink + Send + 'static) -> Self {
+        self.streams.sink = Some(Box::new(sink));
+        self
+    }
+
+    pub fn with_arithmetic_mode(mut self, mode: ArithmeticMode) -> Self {
+        self.arithmetic_mode = mode;
+        self
+    }
+
+    /// Replaces both the step and the time limit.
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn with_max_steps(mut self, max_steps: u64) -> Self {
+        self.limits.max_steps = Some(max_steps);
+        self
+    }
+
+    pub fn with_time_limit(mut self, time_limit: Duration) -> Self {
+        self.limits.time_limit = Some(time_limit);
+        self
+    }
+
+    pub fn run(mut self) -> Result<Vec<CellValue>, ExecutionError> {
+        let ops = &self.code.ops;
+        let limited = self.limits.is_set();
+        while let Some(op) = ops.get(self.pointer) {
+            let result = match limited {
+                true => self.limits.check(self.steps).and_then(|_| self.execute(op)),
+                false => self.execute(op),
+            };
+            match result {
+                Ok(running) => {
+                    self.steps += 1;
+                    if !running {
+                        break;
+                    }
+                }
+                Err(error) => return Err(self.error(error)),
+            }
+        }
+        Ok(self.output)
+    }
+
+    fn error(&self, error: RamMachineError) -> ExecutionError {
+        let code = &self.code.code;
+        ExecutionError {
+            pointer: self.pointer,
+            instruction: code.instructions[self.pointer].clone(),
+            span: code.source_map.instruction(self.pointer).cloned(),
+            error,
+        }
+    }
+
+    /// Executes a single instruction, returns `false` once the machine halted.
+    #[inline]
+    fn execute(&mut self, op: &Op) -> Result<bool, RamMachineError> {
+        match op {
+            Op::Load(v) => {
+                let value = self.get(v)?;
+                self.tape[0] = Some(value);
+            }
+            Op::Store(a) => {
+                let value = self.buffer()?.clone();
+                let address = self.address(*a)?;
+                write_cell(&mut self.tape, address, value);
+            }
+            Op::Add(v) => {
+                self.arithmetic(v, ArithmeticMode::add, RamMachineError::AdditionFailed)?
+            }
+            Op::Sub(v) => {
+                self.arithmetic(v, ArithmeticMode::sub, RamMachineError::SubtractionFailed)?
+            }
+            Op::Mult(v) => self.arithmetic(
+                v,
+                ArithmeticMode::mul,
+                RamMachineError::MultiplicationFailed,
+            )?,
+            Op::Div(v) => {
+                self.arithmetic(v, ArithmeticMode::div, RamMachineError::DivisionFailed)?
+            }
+            Op::Mod(v) => self.arithmetic(v, ArithmeticMode::rem, RamMachineError::ModuloFailed)?,
+            Op::Inc => self.arithmetic(
+                &Value::Number(CellValue::one()),
+                ArithmeticMode::add,
+                RamMachineError::AdditionFailed,
+            )?,
+            Op::Dec => self.arithmetic(
+                &Value::Number(CellValue::one()),
+                ArithmeticMode::sub,
+                RamMachineError::SubtractionFailed,
+            )?,
+            Op::Read(a) => {
+                let input = self
+                    .streams
+                    .read_input(&mut self.input, &mut self.input_pointer)?;
+                let address = self.address(*a)?;
+                write_cell(&mut self.tape, address, input);
+            }
+            Op::Write(v) => {
+                let value = self.get(v)?;
+                if let Some(sink) = &mut self.streams.sink {
+                    sink.write(&value)?;
+                }
+                self.output.push(value);
+            }
+            Op::WriteString(text) => {
+                if let Some(sink) = &mut self.streams.sink {
+                    sink.write_str(text)?;
+                }
+            }
+            Op::Jump(target) => return self.jump_to(*target),
+            Op::Jgtz(target) if self.buffer()?.is_positive() => return self.jump_to(*target),
+            Op::Jzero(target) if self.buffer()?.is_zero() => return self.jump_to(*target),
+            Op::Jneg(target) if self.buffer()?.is_negative() => return self.jump_to(*target),
+            Op::Jgez(target) if !self.buffer()?.is_negative() => return self.jump_to(*target),
+            Op::Jnzero(target) if !self.buffer()?.is_zero() => return self.jump_to(*target),
+            Op::Jgtz(_) | Op::Jzero(_) | Op::Jneg(_) | Op::Jgez(_) | Op::Jnzero(_) => {}
+            Op::Halt => return Ok(false),
+        }
+        self.pointer += 1;
+        Ok(self.pointer < self.code.ops.len())
+    }
+
+    fn jump_to(&mut self, target: Target) -> Result<bool, RamMachineError> {
+        let Some(target) = target else {
+            let label = self.code.code.instructions[self.pointer]
+                .label()
+                .unwrap_or_default();
+            return Err(JumpError::LabelNotFound(label.to_owned()).into());
+        };
+        self.pointer = target;
+        Ok(self.pointer < self.code.ops.len())
+    }
+
+    /// Replaces the buffer with `op(buffer, value)`, failing with `error(value, buffer)`.
+    #[inline]
+    fn arithmetic(
+        &mut self,
+        value: &Value,
+        op: fn(&ArithmeticMode, &CellValue, &CellValue) -> Option<CellValue>,
+        error: fn(CellValue, CellValue) -> RamMachineError,
+    ) -> Result<(), RamMachineError> {
+        let value = self.get(value)?;
+        let buffer = self.buffer()?;
+        let result = op(&self.arithmetic_mode, buffer, &value)
+            .ok_or_else(|| error(value, buffer.clone()))?;
+        self.tape[0] = Some(result);
+        Ok(())
+    }
+
+    #[inline]
+    fn get(&self, value: &Value) -> Result<CellValue, ExpandError> {
+        let address = match value {
+            Value::Number(v) => return Ok(v.clone()),
+            Value::Cell(cell) => *cell,
+            Value::Indirect(cell) => address_in_cell(&self.tape, *cell)?,
+        };
+        self.tape
+            .get(address)
+            .and_then(|val| val.as_ref())
+            .cloned()
+            .ok_or(ExpandError::ValueNotSet(address))
+    }
+
+    #[inline]
+    fn address(&self, address: Address) -> Result<CellAddress, ExpandError> {
+        match address {
+            Address::Cell(cell) => Ok(cell),
+            Address::Indirect(cell) => address_in_cell(&self.tape, cell),
+        }
+    }
+
+    #[inline]
+    fn buffer(&self) -> Result<&CellValue, BufferError> {
+        self.tape[0].as_ref().ok_or(BufferError)
+    }
+}
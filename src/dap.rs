@@ -0,0 +1,422 @@
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Sender, TryRecvError};
+use std::thread;
+
+use serde_json::{json, Value};
+
+use ram_machine::debugger::{Breakpoint, Debugger, StopReason};
+use ram_machine::instruction::Dialect;
+use ram_machine::interpreter::RamMachine;
+use ram_machine::locale::{Lang, Localize, Message};
+use ram_machine::operand::CellValue;
+use ram_machine::parser::{ParseOptions, RamCode};
+
+use crate::cli::{read_input, RuntimeError};
+
+/// The only thread of the debugged program.
+const THREAD_ID: u64 = 1;
+const TAPE_SCOPE: u64 = 1;
+const IO_SCOPE: u64 = 2;
+/// Instructions executed between checking for requests while the program runs.
+const RUN_CHUNK: usize = 10_000;
+
+struct Session {
+    debugger: Option<Debugger>,
+    /// Whether the program runs until a request pauses it or it stops by itself
+    running: bool,
+    program: PathBuf,
+    stop_on_entry: bool,
+    /// Source lines of the breakpoints set before the program was launched
+    pending_breakpoints: Vec<u64>,
+    lang: Lang,
+    /// Keywords accepted unless the launch request asks for others
+    keywords: Lang,
+}
+
+/// Serves the debug adapter protocol over STDIN and STDOUT until the client disconnects.
+pub fn dap(lang: Lang, keywords: Lang) -> Result<(), RuntimeError> {
+    let mut session = Session {
+        debugger: None,
+        running: false,
+        program: PathBuf::new(),
+        stop_on_entry: false,
+        pending_breakpoints: Vec::new(),
+        lang,
+        keywords,
+    };
+    let (sender, requests) = mpsc::channel();
+    thread::spawn(move || forward_requests(sender));
+    let mut stdout = io::stdout().lock();
+    let mut seq = 0;
+    loop {
+        // A running program only executes the next chunk when no request is waiting
+        let request = match session.running {
+            true => match requests.try_recv() {
+                Ok(request) => Some(request),
+                Err(TryRecvError::Empty) => None,
+                Err(TryRecvError::Disconnected) => break,
+            },
+            false => match requests.recv() {
+                Ok(request) => Some(request),
+                Err(_) => break,
+            },
+        };
+        let Some(request) = request else {
+            let events = session.continue_run();
+            write_messages(&mut stdout, &mut seq, events).map_err(RuntimeError::TerminalError)?;
+            continue;
+        };
+        let request = request.map_err(RuntimeError::TerminalError)?;
+        let messages = session.handle(&request);
+        write_messages(&mut stdout, &mut seq, messages).map_err(RuntimeError::TerminalError)?;
+        if request["command"] == "disconnect" {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Reads requests from STDIN on its own thread, so that a running program can be paused.
+fn forward_requests(sender: Sender<io::Result<Value>>) {
+    let mut stdin = io::stdin().lock();
+    while let Some(request) = read_message(&mut stdin).transpose() {
+        let failed = request.is_err();
+        if sender.send(request).is_err() || failed {
+            break;
+        }
+    }
+}
+
+/// Reads a message framed with a `Content-Length` header, `None` at the end of the input.
+fn read_message(reader: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                length = value.trim().parse().ok();
+            }
+        }
+    }
+    let Some(length) = length else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "missing Content-Length header",
+        ));
+    };
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn write_message(writer: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(writer, "Content-Length: {}\r\n\r\n{body}", body.len())?;
+    writer.flush()
+}
+
+/// Writes the messages, numbering them after the ones written before.
+fn write_messages(writer: &mut impl Write, seq: &mut u64, messages: Vec<Value>) -> io::Result<()> {
+    for mut message in messages {
+        *seq += 1;
+        message["seq"] = json!(*seq);
+        write_message(writer, &message)?;
+    }
+    Ok(())
+}
+
+impl Session {
+    /// The response to `request`, followed by the events it caused.
+    fn handle(&mut self, request: &Value) -> Vec<Value> {
+        let command = request["command"].as_str().unwrap_or_default();
+        let arguments = &request["arguments"];
+        let mut events = Vec::new();
+        let result = match command {
+            "initialize" => {
+                events.push(self.event("initialized", Value::Null));
+                Ok(json!({ "supportsConfigurationDoneRequest": true }))
+            }
+            "launch" => self.launch(arguments),
+            "setBreakpoints" => Ok(self.set_breakpoints(arguments)),
+            "configurationDone" => {
+                match self.stop_on_entry || self.debugger.is_none() {
+                    true => events.push(self.stopped_event("entry", None)),
+                    false => self.running = true,
+                }
+                Ok(Value::Null)
+            }
+            "threads" => Ok(json!({ "threads": [{ "id": THREAD_ID, "name": "main" }] })),
+            "stackTrace" => Ok(self.stack_trace()),
+            "scopes" => Ok(json!({
+                "scopes": [
+                    { "name": self.localize(Message::TapeTitle), "variablesReference": TAPE_SCOPE, "expensive": false },
+                    { "name": "I/O", "variablesReference": IO_SCOPE, "expensive": false },
+                ]
+            })),
+            "variables" => Ok(self.variables(arguments["variablesReference"].as_u64())),
+            "next" | "stepIn" | "stepOut" => {
+                self.running = false;
+                let reason = self.debugger.as_mut().map(|debugger| debugger.step(1));
+                events.extend(
+                    reason
+                        .map(|reason| self.stopped(reason))
+                        .unwrap_or_default(),
+                );
+                Ok(Value::Null)
+            }
+            "continue" => {
+                self.running = self.debugger.is_some();
+                Ok(json!({ "allThreadsContinued": true }))
+            }
+            "pause" => {
+                if self.running {
+                    self.running = false;
+                    events.push(self.stopped_event("pause", None));
+                }
+                Ok(Value::Null)
+            }
+            "disconnect" => Ok(Value::Null),
+            _ => Err(self.localize(Message::UnsupportedRequest(command))),
+        };
+        let response = self.response(request, result);
+        [response].into_iter().chain(events).collect()
+    }
+
+    /// Executes the next chunk of a run in progress, the events if the program stopped.
+    fn continue_run(&mut self) -> Vec<Value> {
+        let reason = self
+            .debugger
+            .as_mut()
+            .and_then(|debugger| debugger.resume_for(RUN_CHUNK));
+        match reason {
+            Some(reason) => {
+                self.running = false;
+                self.stopped(reason)
+            }
+            None => Vec::new(),
+        }
+    }
+
+    fn launch(&mut self, arguments: &Value) -> Result<Value, String> {
+        let Some(program) = arguments["program"].as_str() else {
+            return Err(self.localize(Message::ProgramRequired));
+        };
+        self.program = PathBuf::from(program);
+        self.stop_on_entry = arguments["stopOnEntry"].as_bool().unwrap_or_default();
+        let dialect: Dialect = match arguments["dialect"].as_str() {
+            Some(dialect) => dialect.parse().map_err(|e| self.localize(e))?,
+            None => Dialect::default(),
+        };
+        let keywords: Lang = match arguments["keywords"].as_str() {
+            Some(keywords) => keywords.parse().map_err(|e| self.localize(e))?,
+            None => self.keywords,
+        };
+        let input = arguments["input"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|value| {
+                let value = value.as_str().map_or(value.to_string(), str::to_owned);
+                value
+                    .parse::<CellValue>()
+                    .map_err(|_| RuntimeError::ConvertInputError(value))
+            })
+            .collect::<Result<Vec<CellValue>, RuntimeError>>()
+            .map_err(|e| self.localize(e))?;
+        let input_file = arguments["inputFile"].as_str().map(PathBuf::from);
+        let input = read_input(input, input_file).map_err(|e| self.localize(e))?;
+
+        let source = fs::read_to_string(&self.program)
+            .map_err(|e| self.localize(RuntimeError::ReadCodeError(e)))?;
+        let options = ParseOptions {
+            lenient: arguments["lenient"].as_bool().unwrap_or_default(),
+            dialect,
+            keywords,
+        };
+        let code = RamCode::parse_all(&source, options).map_err(|e| self.localize(e))?;
+        let mut machine = RamMachine::new(code, input);
+        if let Some(steps) = arguments["maxSteps"].as_u64() {
+            machine = machine.with_max_steps(steps);
+        }
+        let mut debugger = Debugger::new(machine);
+        for line in self.pending_breakpoints.drain(..) {
+            if let Ok(location) = debugger.resolve_line(line as usize) {
+                debugger.add_breakpoint(Breakpoint {
+                    location,
+                    condition: None,
+                });
+            }
+        }
+        self.debugger = Some(debugger);
+        Ok(Value::Null)
+    }
+
+    fn set_breakpoints(&mut self, arguments: &Value) -> Value {
+        let lines: Vec<u64> = arguments["breakpoints"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|breakpoint| breakpoint["line"].as_u64())
+            .collect();
+        let Some(debugger) = self.debugger.as_mut() else {
+            // Breakpoints usually arrive between `launch` and `configurationDone`
+            self.pending_breakpoints = lines.clone();
+            let breakpoints: Vec<Value> = lines
+                .iter()
+                .map(|line| json!({ "verified": false, "line": line }))
+                .collect();
+            return json!({ "breakpoints": breakpoints });
+        };
+        debugger.clear_breakpoints();
+        let breakpoints: Vec<Value> = lines
+            .iter()
+            .map(|line| match debugger.resolve_line(*line as usize) {
+                Ok(location) => {
+                    let spans = &debugger.machine().code().source_map.instructions;
+                    let Some(line) = spans.get(location).map(|span| span.line) else {
+                        return json!({ "verified": false, "line": line });
+                    };
+                    debugger.add_breakpoint(Breakpoint {
+                        location,
+                        condition: None,
+                    });
+                    json!({ "verified": true, "line": line })
+                }
+                Err(e) => json!({
+                    "verified": false,
+                    "line": line,
+                    "message": e.localize(self.lang),
+                }),
+            })
+            .collect();
+        json!({ "breakpoints": breakpoints })
+    }
+
+    fn stack_trace(&self) -> Value {
+        let Some(debugger) = &self.debugger else {
+            return json!({ "stackFrames": [], "totalFrames": 0 });
+        };
+        let machine = debugger.machine();
+        let spans = &machine.code().source_map.instructions;
+        let span = spans.get(machine.pointer()).or(spans.last());
+        let frame = json!({
+            "id": 0,
+            "name": machine.get_current_instruction().to_string(),
+            "source": { "path": self.program.display().to_string() },
+            "line": span.map_or(0, |span| span.line),
+            "column": span.map_or(0, |span| span.columns.start + 1),
+        });
+        json!({ "stackFrames": [frame], "totalFrames": 1 })
+    }
+
+    fn variables(&self, reference: Option<u64>) -> Value {
+        let Some(debugger) = &self.debugger else {
+            return json!({ "variables": [] });
+        };
+        let machine = debugger.machine();
+        let variable = |name: String, value: String| json!({ "name": name, "value": value, "variablesReference": 0 });
+        let variables: Vec<Value> = match reference {
+            Some(TAPE_SCOPE) => machine
+                .tape()
+                .iter()
+                .enumerate()
+                .filter_map(|(cell, value)| {
+                    value
+                        .as_ref()
+                        .map(|value| variable(format!("[{cell}]"), value.to_string()))
+                })
+                .collect(),
+            Some(IO_SCOPE) => vec![
+                variable("input".to_owned(), format!("{:?}", machine.input())),
+                variable("next input".to_owned(), machine.input_pointer().to_string()),
+                variable("output".to_owned(), format!("{:?}", machine.output())),
+            ],
+            _ => Vec::new(),
+        };
+        json!({ "variables": variables })
+    }
+
+    /// Events telling the client why the program stopped.
+    fn stopped(&self, reason: StopReason) -> Vec<Value> {
+        match reason {
+            StopReason::Step | StopReason::StartOfHistory => {
+                vec![self.stopped_event("step", None)]
+            }
+            StopReason::Breakpoint(_) => vec![self.stopped_event("breakpoint", None)],
+            StopReason::Watchpoint(..) => vec![self.stopped_event("data breakpoint", None)],
+            StopReason::Error(e) => {
+                let message = e.localize(self.lang);
+                vec![
+                    self.event(
+                        "output",
+                        json!({ "category": "stderr", "output": format!("{message}\n") }),
+                    ),
+                    self.stopped_event("exception", Some(message)),
+                ]
+            }
+            StopReason::Halted => {
+                let output = self
+                    .debugger
+                    .as_ref()
+                    .map(|debugger| format!("{:?}\n", debugger.machine().output()))
+                    .unwrap_or_default();
+                vec![
+                    self.event("output", json!({ "category": "stdout", "output": output })),
+                    self.event("exited", json!({ "exitCode": 0 })),
+                    self.event("terminated", Value::Null),
+                ]
+            }
+        }
+    }
+
+    fn stopped_event(&self, reason: &str, text: Option<String>) -> Value {
+        let mut body = json!({
+            "reason": reason,
+            "threadId": THREAD_ID,
+            "allThreadsStopped": true,
+        });
+        if let Some(text) = text {
+            body["description"] = json!(text);
+            body["text"] = json!(text);
+        }
+        self.event("stopped", body)
+    }
+
+    fn event(&self, event: &str, body: Value) -> Value {
+        let mut message = json!({ "type": "event", "event": event });
+        if !body.is_null() {
+            message["body"] = body;
+        }
+        message
+    }
+
+    fn response(&self, request: &Value, result: Result<Value, String>) -> Value {
+        let mut message = json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": result.is_ok(),
+        });
+        match result {
+            Ok(Value::Null) => {}
+            Ok(body) => message["body"] = body,
+            Err(error) => message["message"] = json!(error),
+        }
+        message
+    }
+
+    fn localize(&self, message: impl Localize) -> String {
+        message.localize(self.lang)
+    }
+}
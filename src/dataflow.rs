@@ -0,0 +1,300 @@
+use std::collections::{BTreeSet, VecDeque};
+
+use crate::{
+    cfg::ControlFlowGraph,
+    diagnostic::{Diagnostic, Severity},
+    instruction::Instruction,
+    locale::{Lang, Localize, Message},
+    operand::{CellAddress, CellOperand, Operand},
+    parser::RamCode,
+};
+
+/// Whether a cell is set when an instruction reads it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Initialization {
+    /// Set on every path to the instruction
+    Always,
+    /// Set on some paths only
+    Sometimes,
+    /// Not set on any path
+    Never,
+}
+
+/// A read of a cell by a reachable instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CellUse {
+    pub instruction: usize,
+    pub cell: CellAddress,
+    /// The buffer is used by the instruction itself, e.g. by `ADD` or `JZERO`
+    pub implicit: bool,
+    pub initialization: Initialization,
+}
+
+/// Definite assignment analysis, which cells are set whenever an instruction reads them.
+#[derive(Debug, Clone)]
+pub struct DefiniteAssignment {
+    pub uses: Vec<CellUse>,
+    graph: ControlFlowGraph,
+    stores: Vec<Stores>,
+}
+
+/// Cells set before an instruction.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+struct Facts {
+    /// Set on at least one path
+    may: BTreeSet<CellAddress>,
+    /// A cell with a computed address was stored, so any cell may be set
+    may_any: bool,
+    /// Set on every path
+    must: BTreeSet<CellAddress>,
+}
+
+/// Cells an instruction sets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Stores {
+    Nothing,
+    Cell(CellAddress),
+    /// A cell whose address is stored in a cell
+    Computed,
+}
+
+impl Facts {
+    fn initialization(&self, cell: CellAddress) -> Initialization {
+        if self.must.contains(&cell) {
+            Initialization::Always
+        } else if self.may_any || self.may.contains(&cell) {
+            Initialization::Sometimes
+        } else {
+            Initialization::Never
+        }
+    }
+
+    fn apply(&mut self, stores: &Stores) {
+        match stores {
+            Stores::Nothing => {}
+            Stores::Cell(cell) => {
+                self.may.insert(*cell);
+                self.must.insert(*cell);
+            }
+            Stores::Computed => self.may_any = true,
+        }
+    }
+
+    /// Merges the facts of another path, returns whether anything changed.
+    fn join(&mut self, other: &Facts) -> bool {
+        let before = self.clone();
+        self.may.extend(&other.may);
+        self.may_any |= other.may_any;
+        self.must.retain(|cell| other.must.contains(cell));
+        *self != before
+    }
+}
+
+impl DefiniteAssignment {
+    pub fn new(code: &RamCode) -> Self {
+        let graph = ControlFlowGraph::new(code);
+        let stores: Vec<Stores> = code.instructions.iter().map(stores).collect();
+
+        let mut entry: Vec<Option<Facts>> = vec![None; graph.blocks.len()];
+        let mut worklist = VecDeque::new();
+        if !graph.blocks.is_empty() {
+            entry[0] = Some(Facts::default());
+            worklist.push_back(0);
+        }
+        while let Some(block) = worklist.pop_front() {
+            let Some(mut facts) = entry[block].clone() else {
+                continue;
+            };
+            for index in graph.blocks[block].instructions.clone() {
+                facts.apply(&stores[index]);
+            }
+            for edge in graph.successors(block) {
+                let changed = match &mut entry[edge.to] {
+                    Some(target) => target.join(&facts),
+                    target => {
+                        *target = Some(facts.clone());
+                        true
+                    }
+                };
+                if changed && !worklist.contains(&edge.to) {
+                    worklist.push_back(edge.to);
+                }
+            }
+        }
+
+        let mut uses = Vec::new();
+        for (block, facts) in graph.blocks.iter().zip(entry) {
+            let Some(mut facts) = facts else {
+                continue;
+            };
+            for index in block.instructions.clone() {
+                let instruction = &code.instructions[index];
+                let reads = uses_buffer(instruction)
+                    .then_some((0, true))
+                    .into_iter()
+                    .chain(read_cell(instruction).map(|cell| (cell, false)));
+                for (cell, implicit) in reads {
+                    uses.push(CellUse {
+                        instruction: index,
+                        cell,
+                        implicit,
+                        initialization: facts.initialization(cell),
+                    });
+                }
+                facts.apply(&stores[index]);
+            }
+        }
+        DefiniteAssignment {
+            uses,
+            graph,
+            stores,
+        }
+    }
+
+    /// Uses of cells which are not set on every path.
+    pub fn unsafe_uses(&self) -> impl Iterator<Item = &CellUse> {
+        self.uses
+            .iter()
+            .filter(|cell_use| cell_use.initialization != Initialization::Always)
+    }
+
+    /// Shortest sequence of instructions from the start of the program up to and including the
+    /// use, on which the cell is not stored.
+    pub fn unset_path(&self, cell_use: &CellUse) -> Option<Vec<usize>> {
+        let len = self.stores.len();
+        let mut previous: Vec<Option<usize>> = vec![None; len];
+        let mut visited = vec![false; len];
+        let mut queue = VecDeque::from([0]);
+        *visited.first_mut()? = true;
+        while let Some(index) = queue.pop_front() {
+            if index == cell_use.instruction {
+                let mut path = vec![index];
+                while let Some(before) = previous[path[path.len() - 1]] {
+                    path.push(before);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            if self.stores[index] == Stores::Cell(cell_use.cell) {
+                continue;
+            }
+            for next in self.next_instructions(index) {
+                if !visited[next] {
+                    visited[next] = true;
+                    previous[next] = Some(index);
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+
+    fn next_instructions(&self, index: usize) -> Vec<usize> {
+        let Some(block) = self.graph.block_of(index) else {
+            return Vec::new();
+        };
+        if index + 1 < self.graph.blocks[block].instructions.end {
+            return vec![index + 1];
+        }
+        self.graph
+            .successors(block)
+            .map(|edge| self.graph.blocks[edge.to].instructions.start)
+            .collect()
+    }
+
+    /// Errors for every use of a cell which is not set on every path, as reported by
+    /// `ram check --strict`.
+    pub fn diagnostics(&self, code: &RamCode, source: &str, lang: Lang) -> Vec<Diagnostic> {
+        let lines: Vec<&str> = source.lines().collect();
+        self.unsafe_uses()
+            .filter_map(|cell_use| {
+                let span = code.source_map.instruction(cell_use.instruction)?.clone();
+                let (error_code, message) = match (cell_use.initialization, cell_use.implicit) {
+                    (Initialization::Never, true) => ("E0013", Message::BufferNeverSet),
+                    (Initialization::Never, false) => {
+                        ("E0013", Message::CellNeverStored(cell_use.cell))
+                    }
+                    (_, true) => ("E0014", Message::BufferMaybeUnset),
+                    (_, false) => ("E0014", Message::CellMaybeUnset(cell_use.cell)),
+                };
+                let help = self.unset_path(cell_use).map(|path| {
+                    let mut path_lines: Vec<String> = path
+                        .iter()
+                        .filter_map(|index| code_line(code, *index))
+                        .collect();
+                    path_lines.dedup();
+                    let path = path_lines.join(" → ");
+                    Message::UnsetPath(&path).localize(lang)
+                });
+                Some(Diagnostic {
+                    severity: Severity::Error,
+                    code: error_code,
+                    message: message.localize(lang),
+                    source_line: lines
+                        .get(span.line.saturating_sub(1))
+                        .copied()
+                        .unwrap_or_default()
+                        .to_owned(),
+                    span,
+                    help,
+                })
+            })
+            .collect()
+    }
+}
+
+fn code_line(code: &RamCode, instruction: usize) -> Option<String> {
+    code.source_map
+        .instruction(instruction)
+        .map(|span| span.line.to_string())
+}
+
+fn stores(instruction: &Instruction) -> Stores {
+    use Instruction::*;
+    match instruction {
+        Load(_) | Add(_) | Sub(_) | Mult(_) | Div(_) | Mod(_) | Inc | Dec => Stores::Cell(0),
+        Store(CellOperand::AddressOfCell(cell)) | Read(CellOperand::AddressOfCell(cell)) => {
+            Stores::Cell(*cell)
+        }
+        Store(CellOperand::AddressOfCellInCell(_)) | Read(CellOperand::AddressOfCellInCell(_)) => {
+            Stores::Computed
+        }
+        _ => Stores::Nothing,
+    }
+}
+
+/// Whether the instruction reads the buffer without naming cell 0.
+fn uses_buffer(instruction: &Instruction) -> bool {
+    use Instruction::*;
+    matches!(
+        instruction,
+        Add(_)
+            | Sub(_)
+            | Mult(_)
+            | Div(_)
+            | Mod(_)
+            | Store(_)
+            | Inc
+            | Dec
+            | Jgtz(_)
+            | Jzero(_)
+            | Jneg(_)
+            | Jgez(_)
+            | Jnzero(_)
+    )
+}
+
+/// The cell named by the operand of the instruction which is read.
+fn read_cell(instruction: &Instruction) -> Option<CellAddress> {
+    use Instruction::*;
+    match instruction {
+        Load(operand) | Add(operand) | Sub(operand) | Mult(operand) | Div(operand)
+        | Mod(operand) | Write(operand) => match operand {
+            Operand::Number(_) => None,
+            Operand::ValueInCell(cell) | Operand::ValueOfValueInCell(cell) => Some(*cell),
+        },
+        Store(CellOperand::AddressOfCellInCell(cell))
+        | Read(CellOperand::AddressOfCellInCell(cell)) => Some(*cell),
+        _ => None,
+    }
+}
@@ -0,0 +1,186 @@
+use std::io::{self, BufRead, Write};
+
+use ram_machine::debugger::{Breakpoint, Debugger, StopReason};
+use ram_machine::interpreter::RamMachine;
+use ram_machine::locale::{Lang, Localize, Message};
+use ram_machine::operand::{CellAddress, CellValue};
+
+use crate::cli::RuntimeError;
+
+const PROMPT: &str = "(ram) ";
+fn describe_next(debugger: &Debugger) -> String {
+    let machine = debugger.machine();
+    match machine.code().source_map.instruction(machine.pointer()) {
+        Some(span) if !debugger.is_halted() => format!("{}: {}", span.line, span.text),
+        _ => machine.get_current_instruction().to_string(),
+    }
+}
+
+fn report(debugger: &Debugger, reason: StopReason, lang: Lang) {
+    match reason {
+        StopReason::Step => {}
+        StopReason::Breakpoint(index) => say(Message::BreakpointHit(index), lang),
+        StopReason::Watchpoint(cell, old, new) => say(
+            Message::WatchpointHit(cell, &format_cell(old), &format_cell(new)),
+            lang,
+        ),
+        StopReason::Halted => {
+            let machine = debugger.machine();
+            say(Message::HaltedAfter(machine.steps()), lang);
+            say(Message::Output(&format!("{:?}", machine.output())), lang);
+            return;
+        }
+        StopReason::StartOfHistory => say(Message::StartOfHistory, lang),
+        StopReason::Error(e) => println!("{}", e.localize(lang)),
+    }
+    let next = describe_next(debugger);
+    say(Message::StepNext(debugger.machine().steps(), &next), lang);
+}
+
+fn say(message: Message, lang: Lang) {
+    println!("{}", message.localize(lang))
+}
+
+fn format_cell(value: Option<CellValue>) -> String {
+    value.map_or("?".to_owned(), |v| v.to_string())
+}
+
+fn parse_count(s: &str) -> Option<usize> {
+    match s {
+        "" => Some(1),
+        n => n.parse().ok(),
+    }
+}
+
+fn parse_cell(s: &str) -> Option<CellAddress> {
+    s.trim_start_matches('[').trim_end_matches(']').parse().ok()
+}
+
+fn eval(debugger: &mut Debugger, line: &str, lang: Lang) -> bool {
+    let line = line.trim();
+    let (command, argument) = line.split_once(' ').unwrap_or((line, ""));
+    let argument = argument.trim();
+    match command {
+        "step" | "s" => match parse_count(argument) {
+            Some(n) => {
+                let reason = debugger.step(n);
+                report(debugger, reason, lang)
+            }
+            None => say(Message::InvalidStepCount(argument), lang),
+        },
+        "step-back" | "sb" => match parse_count(argument) {
+            Some(n) => {
+                let reason = debugger.step_back(n);
+                report(debugger, reason, lang)
+            }
+            None => say(Message::InvalidStepCount(argument), lang),
+        },
+        "reverse-continue" | "rc" => {
+            let reason = debugger.reverse_resume();
+            report(debugger, reason, lang)
+        }
+        "goto" | "g" => match argument.parse() {
+            Ok(step) => {
+                let reason = debugger.go_to(step);
+                report(debugger, reason, lang)
+            }
+            Err(_) => say(Message::InvalidStepNumber(argument), lang),
+        },
+        "continue" | "c" => {
+            let reason = debugger.resume();
+            report(debugger, reason, lang)
+        }
+        "finish" | "f" => {
+            let reason = debugger.finish();
+            report(debugger, reason, lang)
+        }
+        "break" | "b" => {
+            let (location, condition) = match argument.split_once(" if ") {
+                Some((location, condition)) => (location.trim(), Some(condition)),
+                None => (argument, None),
+            };
+            let breakpoint = debugger.resolve_location(location).and_then(|location| {
+                Ok(Breakpoint {
+                    location,
+                    condition: condition.map(str::parse).transpose()?,
+                })
+            });
+            match breakpoint {
+                Ok(breakpoint) => {
+                    let index = debugger.breakpoints().len();
+                    say(
+                        Message::BreakpointAdded(index, breakpoint.location, None),
+                        lang,
+                    );
+                    debugger.add_breakpoint(breakpoint);
+                }
+                Err(e) => println!("{}", e.localize(lang)),
+            }
+        }
+        "delete" | "d" => match argument
+            .parse()
+            .ok()
+            .and_then(|n| debugger.remove_breakpoint(n))
+        {
+            Some(_) => say(Message::BreakpointDeleted(argument), lang),
+            None => say(Message::NoBreakpoint(argument), lang),
+        },
+        "watch" | "w" => match parse_cell(argument) {
+            Some(cell) => {
+                debugger.add_watchpoint(cell);
+                say(Message::Watching(cell), lang);
+            }
+            None => say(Message::InvalidCell(argument), lang),
+        },
+        "unwatch" => match parse_cell(argument) {
+            Some(cell) if debugger.remove_watchpoint(cell) => say(Message::Unwatched(cell), lang),
+            _ => say(Message::NoWatchpoint(argument), lang),
+        },
+        "info" | "i" => {
+            for (index, breakpoint) in debugger.breakpoints().iter().enumerate() {
+                let condition = breakpoint.condition.as_ref().map(ToString::to_string);
+                let message =
+                    Message::BreakpointAdded(index, breakpoint.location, condition.as_deref());
+                say(message, lang);
+            }
+            for cell in debugger.watchpoints() {
+                say(Message::WatchpointOn(*cell), lang);
+            }
+        }
+        "print" | "p" => match argument {
+            "" => println!("{}", debugger.machine().state_table_in(lang)),
+            cell => match parse_cell(cell) {
+                Some(cell) => println!("[{cell}] = {}", format_cell(debugger.machine().cell(cell))),
+                None => say(Message::InvalidCell(cell), lang),
+            },
+        },
+        "help" | "h" => say(Message::DebuggerHelp, lang),
+        "quit" | "q" => return false,
+        c => say(Message::UnknownCommand(c, "help"), lang),
+    }
+    true
+}
+
+pub fn debug(machine: RamMachine, lang: Lang) -> Result<(), RuntimeError> {
+    let mut debugger = Debugger::new(machine);
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut previous = String::new();
+
+    say(Message::NextInstruction(&describe_next(&debugger)), lang);
+    loop {
+        print!("{PROMPT}");
+        io::stdout().flush().map_err(RuntimeError::TerminalError)?;
+        let Some(line) = lines.next() else {
+            println!();
+            return Ok(());
+        };
+        let line = line.map_err(RuntimeError::TerminalError)?;
+        if !line.trim().is_empty() {
+            previous = line;
+        }
+        if !previous.is_empty() && !eval(&mut debugger, &previous, lang) {
+            return Ok(());
+        }
+    }
+}
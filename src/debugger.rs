@@ -0,0 +1,388 @@
+use std::{fmt, str::FromStr};
+
+use thiserror::Error;
+
+use crate::{
+    interpreter::{ExecutionError, RamMachine, RunState},
+    locale::{Lang, Localize},
+    operand::{CellAddress, CellValue},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConditionOperand {
+    Cell(CellAddress), // [x]
+    Number(CellValue), // x
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Condition {
+    pub left: ConditionOperand,
+    pub comparison: Comparison,
+    pub right: ConditionOperand,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Breakpoint {
+    pub location: usize,
+    pub condition: Option<Condition>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StopReason {
+    Step,
+    Breakpoint(usize),
+    Watchpoint(CellAddress, Option<CellValue>, Option<CellValue>),
+    Halted,
+    StartOfHistory,
+    Error(ExecutionError),
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+#[error("{}", self.localize(Lang::En))]
+pub enum DebuggerError {
+    LocationNotFound(String),
+    InvalidCondition(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct Debugger {
+    machine: RamMachine,
+    breakpoints: Vec<Breakpoint>,
+    watchpoints: Vec<CellAddress>,
+    halted: bool,
+}
+
+impl Debugger {
+    pub fn new(machine: RamMachine) -> Self {
+        Debugger {
+            machine,
+            breakpoints: Vec::new(),
+            watchpoints: Vec::new(),
+            halted: false,
+        }
+    }
+
+    pub fn machine(&self) -> &RamMachine {
+        &self.machine
+    }
+
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    pub fn watchpoints(&self) -> &[CellAddress] {
+        &self.watchpoints
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.halted
+    }
+
+    /// Resolves a label or a source line to an instruction index.
+    pub fn resolve_location(&self, location: &str) -> Result<usize, DebuggerError> {
+        let code = self.machine.code();
+        if let Some(index) = code.jump_table.get(location) {
+            return Ok(*index);
+        }
+        location
+            .parse::<usize>()
+            .ok()
+            .and_then(|line| self.line_instruction(line))
+            .ok_or_else(|| DebuggerError::LocationNotFound(location.to_owned()))
+    }
+
+    /// Resolves a source line to the index of the first instruction at or after it, ignoring
+    /// labels which look like line numbers.
+    pub fn resolve_line(&self, line: usize) -> Result<usize, DebuggerError> {
+        self.line_instruction(line)
+            .ok_or_else(|| DebuggerError::LocationNotFound(line.to_string()))
+    }
+
+    fn line_instruction(&self, line: usize) -> Option<usize> {
+        self.machine
+            .code()
+            .source_map
+            .instructions
+            .iter()
+            .position(|span| span.line >= line)
+    }
+
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        self.breakpoints.push(breakpoint)
+    }
+
+    pub fn remove_breakpoint(&mut self, index: usize) -> Option<Breakpoint> {
+        (index < self.breakpoints.len()).then(|| self.breakpoints.remove(index))
+    }
+
+    pub fn clear_breakpoints(&mut self) {
+        self.breakpoints.clear()
+    }
+
+    pub fn add_watchpoint(&mut self, cell: CellAddress) {
+        self.watchpoints.push(cell)
+    }
+
+    pub fn remove_watchpoint(&mut self, cell: CellAddress) -> bool {
+        let len = self.watchpoints.len();
+        self.watchpoints.retain(|c| *c != cell);
+        len != self.watchpoints.len()
+    }
+
+    pub fn step(&mut self, count: usize) -> StopReason {
+        for _ in 0..count {
+            if let Some(reason) = self.single_step() {
+                return reason;
+            }
+        }
+        StopReason::Step
+    }
+
+    /// Runs until a breakpoint or watchpoint is hit or the machine halts.
+    pub fn resume(&mut self) -> StopReason {
+        loop {
+            if let Some(reason) = self.resume_for(usize::MAX) {
+                return reason;
+            }
+        }
+    }
+
+    /// Like `resume`, but returns `None` after `steps` instructions, so that the caller can stay
+    /// responsive while a long run is in progress.
+    pub fn resume_for(&mut self, steps: usize) -> Option<StopReason> {
+        for _ in 0..steps {
+            if let Some(reason) = self.single_step() {
+                return Some(reason);
+            }
+            if let Some(index) = self.breakpoint_hit() {
+                return Some(StopReason::Breakpoint(index));
+            }
+        }
+        None
+    }
+
+    /// Runs until the machine halts, ignoring breakpoints and watchpoints.
+    pub fn finish(&mut self) -> StopReason {
+        loop {
+            if let Some(reason) = self.finish_for(usize::MAX) {
+                return reason;
+            }
+        }
+    }
+
+    /// Like `finish`, but returns `None` after `steps` instructions.
+    pub fn finish_for(&mut self, steps: usize) -> Option<StopReason> {
+        if self.halted {
+            return Some(StopReason::Halted);
+        }
+        for _ in 0..steps {
+            match self.machine.run_line() {
+                Ok(RunState::Running) => {}
+                Ok(RunState::Halted) => {
+                    self.halted = true;
+                    return Some(StopReason::Halted);
+                }
+                Err(e) => return Some(StopReason::Error(e)),
+            }
+        }
+        None
+    }
+
+    pub fn step_back(&mut self, count: usize) -> StopReason {
+        for _ in 0..count {
+            if !self.single_step_back() {
+                return StopReason::StartOfHistory;
+            }
+        }
+        StopReason::Step
+    }
+
+    /// Runs backwards until a breakpoint or watchpoint is hit or the recorded history runs out.
+    pub fn reverse_resume(&mut self) -> StopReason {
+        loop {
+            let watched = self.watched_cells();
+            if !self.single_step_back() {
+                return StopReason::StartOfHistory;
+            }
+            if let Some(reason) = self.watchpoint_hit(watched) {
+                return reason;
+            }
+            if let Some(index) = self.breakpoint_hit() {
+                return StopReason::Breakpoint(index);
+            }
+        }
+    }
+
+    /// Moves forwards or backwards to the state before the given step was executed.
+    pub fn go_to(&mut self, step: u64) -> StopReason {
+        while self.machine.steps() > step {
+            if !self.single_step_back() {
+                return StopReason::StartOfHistory;
+            }
+        }
+        while self.machine.steps() < step {
+            if self.halted {
+                return StopReason::Halted;
+            }
+            match self.machine.run_line() {
+                Ok(RunState::Running) => {}
+                Ok(RunState::Halted) => self.halted = true,
+                Err(e) => return StopReason::Error(e),
+            }
+        }
+        StopReason::Step
+    }
+
+    fn single_step_back(&mut self) -> bool {
+        let stepped = self.machine.step_back();
+        if stepped {
+            self.halted = false;
+        }
+        stepped
+    }
+
+    fn watched_cells(&self) -> Vec<Option<CellValue>> {
+        self.watchpoints
+            .iter()
+            .map(|cell| self.machine.cell(*cell))
+            .collect()
+    }
+
+    fn watchpoint_hit(&self, watched: Vec<Option<CellValue>>) -> Option<StopReason> {
+        self.watchpoints
+            .iter()
+            .zip(watched)
+            .find(|(cell, old)| self.machine.cell(**cell) != *old)
+            .map(|(cell, old)| StopReason::Watchpoint(*cell, old, self.machine.cell(*cell)))
+    }
+
+    fn single_step(&mut self) -> Option<StopReason> {
+        if self.halted {
+            return Some(StopReason::Halted);
+        }
+        let watched = self.watched_cells();
+        match self.machine.run_line() {
+            Ok(RunState::Running) => {}
+            Ok(RunState::Halted) => {
+                self.halted = true;
+                return Some(StopReason::Halted);
+            }
+            Err(e) => return Some(StopReason::Error(e)),
+        }
+        self.watchpoint_hit(watched)
+    }
+
+    fn breakpoint_hit(&self) -> Option<usize> {
+        self.breakpoints.iter().position(|breakpoint| {
+            breakpoint.location == self.machine.pointer()
+                && breakpoint
+                    .condition
+                    .as_ref()
+                    .is_none_or(|condition| condition.evaluate(&self.machine))
+        })
+    }
+}
+
+impl ConditionOperand {
+    fn value(&self, machine: &RamMachine) -> Option<CellValue> {
+        match self {
+            ConditionOperand::Cell(cell) => machine.cell(*cell),
+            ConditionOperand::Number(value) => Some(value.clone()),
+        }
+    }
+}
+
+impl Condition {
+    /// Conditions involving cells which were never set are false.
+    pub fn evaluate(&self, machine: &RamMachine) -> bool {
+        let (Some(left), Some(right)) = (self.left.value(machine), self.right.value(machine))
+        else {
+            return false;
+        };
+        match self.comparison {
+            Comparison::Equal => left == right,
+            Comparison::NotEqual => left != right,
+            Comparison::Less => left < right,
+            Comparison::LessOrEqual => left <= right,
+            Comparison::Greater => left > right,
+            Comparison::GreaterOrEqual => left >= right,
+        }
+    }
+}
+
+impl FromStr for ConditionOperand {
+    type Err = DebuggerError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || DebuggerError::InvalidCondition(s.to_owned());
+        match s.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            Some(cell) => Ok(Self::Cell(cell.trim().parse().map_err(|_| invalid())?)),
+            None => Ok(Self::Number(s.parse().map_err(|_| invalid())?)),
+        }
+    }
+}
+
+impl FromStr for Condition {
+    type Err = DebuggerError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const COMPARISONS: [(&str, Comparison); 6] = [
+            ("==", Comparison::Equal),
+            ("!=", Comparison::NotEqual),
+            ("<=", Comparison::LessOrEqual),
+            (">=", Comparison::GreaterOrEqual),
+            ("<", Comparison::Less),
+            (">", Comparison::Greater),
+        ];
+        let (left, comparison, right) = COMPARISONS
+            .iter()
+            .find_map(|(op, comparison)| {
+                s.split_once(op)
+                    .map(|(left, right)| (left, *comparison, right))
+            })
+            .ok_or_else(|| DebuggerError::InvalidCondition(s.to_owned()))?;
+        let invalid = |_| DebuggerError::InvalidCondition(s.to_owned());
+        Ok(Condition {
+            left: left.trim().parse().map_err(invalid)?,
+            comparison,
+            right: right.trim().parse().map_err(invalid)?,
+        })
+    }
+}
+
+impl fmt::Display for ConditionOperand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConditionOperand::Cell(cell) => write!(f, "[{cell}]"),
+            ConditionOperand::Number(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = match self {
+            Comparison::Equal => "==",
+            Comparison::NotEqual => "!=",
+            Comparison::Less => "<",
+            Comparison::LessOrEqual => "<=",
+            Comparison::Greater => ">",
+            Comparison::GreaterOrEqual => ">=",
+        };
+        write!(f, "{op}")
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.left, self.comparison, self.right)
+    }
+}
@@ -0,0 +1,231 @@
+use std::fmt;
+
+use serde_json::{json, Value};
+
+use crate::{
+    instruction::{
+        polish_keyword, InstructionParseError, EXTENDED_KEYWORDS, KEYWORDS, POLISH_KEYWORDS,
+    },
+    locale::{Lang, Localize, Message},
+    operand::OperandParseError,
+    parser::{
+        cst::{CstLine, TokenKind},
+        source_map::Span,
+        validate::ValidationError,
+        CodeParseError, LABEL_END,
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: &'static str,
+    pub message: String,
+    pub span: Span,
+    pub source_line: String,
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn from_parse_error(line: usize, source_line: &str, error: &CodeParseError) -> Self {
+        Self::from_parse_error_in(line, source_line, error, Lang::En, Lang::En)
+    }
+
+    /// Like `from_parse_error`, with the message and help in `lang` and suggestions among the
+    /// keywords accepted with `keywords`.
+    pub fn from_parse_error_in(
+        line: usize,
+        source_line: &str,
+        error: &CodeParseError,
+        lang: Lang,
+        keywords: Lang,
+    ) -> Self {
+        let cst = CstLine::parse(line, 0, source_line);
+        let tokens: Vec<_> = cst.code().collect();
+        let keyword_index = match tokens.first() {
+            Some(token) if token.kind == TokenKind::Label => 1,
+            _ => 0,
+        };
+        let token = |offset: usize| {
+            tokens
+                .get(keyword_index + offset)
+                .or(tokens.last())
+                .map_or(0..source_line.len(), |t| cst.columns(t))
+        };
+
+        // Everything after the pragma name
+        let pragma_value = || {
+            let end = source_line.trim_end().len();
+            let name_end = source_line
+                .trim_start()
+                .find(char::is_whitespace)
+                .map_or(end, |i| {
+                    i + source_line.len() - source_line.trim_start().len()
+                });
+            let start = end - source_line[name_end..end].trim_start().len();
+            start.min(end)..end
+        };
+
+        let label_token = || {
+            tokens.first().map_or(0..source_line.len(), |t| {
+                let columns = cst.columns(t);
+                columns.start..columns.start + t.text.trim_end_matches(LABEL_END).len()
+            })
+        };
+
+        use CodeParseError as C;
+        use InstructionParseError as I;
+        use OperandParseError as O;
+        use ValidationError as V;
+        let help = |message: Message| Some(message.localize(lang));
+        let (code, columns, help) = match error {
+            C::InstructionParseError(I::InvalidKeyword(keyword)) => {
+                ("E0001", token(0), suggest_keyword(keyword, keywords, lang))
+            }
+            C::InstructionParseError(I::LabelNotFound(keyword)) => (
+                "E0002",
+                token(0),
+                help(Message::AddJumpLabel(&keyword.to_uppercase())),
+            ),
+            C::InstructionParseError(I::OperandParseError(O::InvalidOperand(_, keyword))) => {
+                ("E0003", token(1), help(operand_help(keyword)))
+            }
+            C::InstructionParseError(I::OperandParseError(O::OperandNotFound(keyword))) => {
+                ("E0004", token(0), help(operand_help(keyword)))
+            }
+            C::InstructionParseError(I::UnexpectedArgument(_)) => (
+                "E0005",
+                token(1),
+                help(Message::NoArgument(&source_line[token(0)].to_uppercase())),
+            ),
+            C::UnexpectedArgument(_) => ("E0006", token(2), help(Message::OneInstructionPerLine)),
+            // The diagnostic is about the first error, `RamCode::parse_all` reports one per line
+            C::ValidationErrors(errors) => match errors.first() {
+                Some(V::DuplicateLabel(_)) => ("E0007", label_token(), help(Message::UniqueLabels)),
+                Some(V::UndefinedLabel(label, _)) => {
+                    ("E0008", token(1), help(Message::DefineLabel(label)))
+                }
+                None => ("E0009", label_token(), None),
+            },
+            C::DialectParseError(_) => ("E0010", pragma_value(), help(Message::DialectPragma)),
+            C::InstructionParseError(I::UnterminatedString(_)) => (
+                "E0011",
+                token(1).start..source_line.trim_end().len(),
+                help(Message::CloseString),
+            ),
+            C::LangParseError(_) => ("E0012", pragma_value(), help(Message::KeywordsPragma)),
+        };
+
+        Diagnostic {
+            severity: Severity::Error,
+            code,
+            message: error.localize(lang),
+            span: Span::new(line, source_line, columns),
+            source_line: source_line.to_owned(),
+            help,
+        }
+    }
+
+    /// Renders the diagnostic in the style of rustc, with the offending token underlined.
+    pub fn render(&self, path: &str) -> String {
+        self.render_in(path, Lang::En)
+    }
+
+    /// Like `render`, with the labels in `lang`.
+    pub fn render_in(&self, path: &str, lang: Lang) -> String {
+        let line_number = self.span.line.to_string();
+        let gutter = " ".repeat(line_number.len());
+        let padding: String = self.source_line[..self.span.columns.start]
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let underline = "^".repeat(self.span.text.chars().count().max(1));
+
+        let mut output = format!(
+            "{}[{}]: {}\n{gutter}--> {path}:{}:{}\n{gutter} |\n{line_number} | {}\n{gutter} | {padding}{underline}\n",
+            self.severity.localize(lang),
+            self.code,
+            self.message,
+            self.span.line,
+            self.column(),
+            self.source_line,
+        );
+        if let Some(help) = &self.help {
+            let label = Message::Help.localize(lang);
+            output.push_str(&format!("{gutter} |\n{gutter} = {label}: {help}\n"));
+        }
+        output
+    }
+
+    pub fn to_json(&self, path: &str) -> Value {
+        json!({
+            "file": path,
+            "severity": self.severity.to_string(),
+            "code": self.code,
+            "message": self.message,
+            "line": self.span.line,
+            "column": self.column(),
+            "end_column": self.column() + self.span.text.chars().count(),
+            "text": self.span.text,
+            "help": self.help,
+        })
+    }
+
+    /// One-based column of the first underlined character.
+    pub fn column(&self) -> usize {
+        self.source_line[..self.span.columns.start].chars().count() + 1
+    }
+}
+
+fn operand_help(keyword: &str) -> Message<'static> {
+    let lowercase = keyword.to_lowercase();
+    match polish_keyword(&lowercase).unwrap_or(&lowercase) {
+        "store" | "read" => Message::CellOperandSyntax,
+        _ => Message::OperandSyntax,
+    }
+}
+
+/// The keyword most similar to `keyword` among the ones accepted with `keywords`.
+fn suggest_keyword(keyword: &str, keywords: Lang, lang: Lang) -> Option<String> {
+    let lowercase = keyword.to_lowercase();
+    if EXTENDED_KEYWORDS.contains(&polish_keyword(&lowercase).unwrap_or(&lowercase)) {
+        return Some(Message::ExtendedKeyword(&lowercase).localize(lang));
+    }
+    let polish = POLISH_KEYWORDS.iter().map(|(polish, _)| polish);
+    let candidates: Vec<&&str> = match keywords {
+        Lang::En => KEYWORDS.iter().collect(),
+        Lang::Pl => KEYWORDS.iter().chain(polish).collect(),
+    };
+    candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(&lowercase, candidate), candidate))
+        .filter(|(distance, candidate)| *distance <= 2.min(candidate.chars().count() / 2))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| Message::DidYouMean(candidate).localize(lang))
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.localize(Lang::En))
+    }
+}
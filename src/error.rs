@@ -1,11 +1,14 @@
+use crate::diagnostic::Diagnostic;
+use crate::locale::{Lang, Localize};
 use crate::parser::CodeParseError;
 
-#[derive(Debug)]
-pub struct ParserErrorChain(Vec<(u32, CodeParseError)>);
+/// Errors with their line numbers, and the keywords accepted by the code they were found in.
+#[derive(Debug, Default)]
+pub struct ParserErrorChain(pub(crate) Vec<(u32, CodeParseError)>, pub(crate) Lang);
 
 impl ParserErrorChain {
     pub fn new() -> Self {
-        Self(Vec::new())
+        Self(Vec::new(), Lang::default())
     }
 
     pub fn add(&mut self, (line, err): (u32, CodeParseError)) {
@@ -15,14 +18,30 @@ impl ParserErrorChain {
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
     }
+
+    pub fn diagnostics(&self, source: &str) -> Vec<Diagnostic> {
+        self.diagnostics_in(source, Lang::En)
+    }
+
+    /// Like `diagnostics`, with the messages in `lang`.
+    pub fn diagnostics_in(&self, source: &str, lang: Lang) -> Vec<Diagnostic> {
+        let lines: Vec<&str> = source.lines().collect();
+        self.0
+            .iter()
+            .map(|(line, err)| {
+                let source_line = (*line as usize)
+                    .checked_sub(1)
+                    .and_then(|index| lines.get(index))
+                    .copied()
+                    .unwrap_or_default();
+                Diagnostic::from_parse_error_in(*line as usize, source_line, err, lang, self.1)
+            })
+            .collect()
+    }
 }
 
 impl std::fmt::Display for ParserErrorChain {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let mut output: String = "".to_string();
-        for (line, err) in &self.0 {
-            output.push_str(&format!("At line {} found error: {}\n", line, err));
-        }
-        write!(f, "{}", output)
+        write!(f, "{}", self.localize(Lang::En))
     }
 }
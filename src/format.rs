@@ -0,0 +1,124 @@
+use std::{fmt, str::FromStr};
+
+use thiserror::Error;
+
+use crate::locale::{Lang, Localize};
+use crate::parser::{cst::Cst, layout::LineLayout};
+
+/// Case of the keywords in formatted code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Case {
+    #[default]
+    Lower,
+    Upper,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{}", self.localize(Lang::En))]
+pub struct CaseParseError(pub String);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FormatOptions {
+    pub case: Case,
+}
+
+/// Rewrites `source` into the canonical form.
+///
+/// Labels are put in a gutter as wide as the longest label, operands and trailing comments are
+/// aligned, runs of blank lines are collapsed and the file ends with a single newline.
+pub fn format(source: &str, options: FormatOptions) -> String {
+    let cst = Cst::parse(source);
+    let lines: Vec<LineLayout> = cst.lines.iter().map(LineLayout::from).collect();
+    let gutter = lines
+        .iter()
+        .filter_map(|line| line.label)
+        .map(|label| label.chars().count() + 2)
+        .max()
+        .unwrap_or(0);
+    let keyword_width = lines
+        .iter()
+        .filter(|line| line.argument.is_some())
+        .filter_map(|line| line.keyword)
+        .map(|keyword| keyword.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let code: Vec<String> = lines
+        .iter()
+        .map(|line| format_code(line, gutter, keyword_width, options.case))
+        .collect();
+    let comment_column = lines
+        .iter()
+        .zip(&code)
+        .filter(|(line, _)| line.has_code() && line.comment.is_some())
+        .map(|(_, code)| code.chars().count() + 1)
+        .max()
+        .unwrap_or(0);
+
+    let mut output = String::new();
+    let mut previous_blank = true;
+    for (line, code) in lines.iter().zip(code) {
+        if line.is_blank() {
+            if !previous_blank {
+                output.push('\n');
+            }
+            previous_blank = true;
+            continue;
+        }
+        previous_blank = false;
+        let formatted = match line.comment {
+            Some(comment) if line.has_code() => format!("{code:comment_column$}{comment}"),
+            Some(comment) => comment.to_owned(),
+            None => code,
+        };
+        output.push_str(&formatted);
+        output.push('\n');
+    }
+    // Blank lines at the end of the file
+    while output.ends_with("\n\n") {
+        output.pop();
+    }
+    output
+}
+
+fn format_code(line: &LineLayout, gutter: usize, keyword_width: usize, case: Case) -> String {
+    let mut code = match line.label {
+        Some(label) => format!("{:gutter$}", format!("{label}:")),
+        None => " ".repeat(gutter),
+    };
+    if let Some(keyword) = line.keyword {
+        let keyword = match case {
+            Case::Lower => keyword.to_lowercase(),
+            Case::Upper => keyword.to_uppercase(),
+        };
+        match line.argument {
+            Some(argument) => code.push_str(&format!("{keyword:keyword_width$} {argument}")),
+            None => code.push_str(&keyword),
+        }
+    }
+    if let Some(extra) = line.extra {
+        code.push(' ');
+        code.push_str(extra);
+    }
+    code.trim_end().to_owned()
+}
+
+impl FromStr for Case {
+    type Err = CaseParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "lower" => Ok(Case::Lower),
+            "upper" => Ok(Case::Upper),
+            _ => Err(CaseParseError(s.to_owned())),
+        }
+    }
+}
+
+impl fmt::Display for Case {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Case::Lower => write!(f, "lower"),
+            Case::Upper => write!(f, "upper"),
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use std::collections::VecDeque;
+
+use crate::interpreter::RamMachine;
+use crate::observer::ExecutionObserver;
+use crate::operand::{CellAddress, CellValue};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TapeWrite {
+    pub address: CellAddress,
+    pub old: Option<CellValue>,
+    pub new: CellValue,
+    pub(crate) old_cost: u64,
+    pub(crate) old_len: usize,
+}
+
+/// Everything an executed instruction changed, enough to undo it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepDelta {
+    pub step: u64,
+    pub pointer: usize,
+    pub input_pointer: usize,
+    pub tape_write: Option<TapeWrite>,
+    pub output: Option<CellValue>,
+    pub(crate) logarithmic_cost: u64,
+}
+
+/// A bounded log of the most recent steps, the oldest ones are dropped first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct History {
+    deltas: VecDeque<StepDelta>,
+    capacity: usize,
+}
+
+impl History {
+    pub fn new(capacity: usize) -> Self {
+        History {
+            deltas: VecDeque::with_capacity(capacity.min(4096)),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, delta: StepDelta) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.deltas.len() == self.capacity {
+            self.deltas.pop_front();
+        }
+        self.deltas.push_back(delta);
+    }
+
+    pub fn pop(&mut self) -> Option<StepDelta> {
+        self.deltas.pop_back()
+    }
+
+    pub fn last(&self) -> Option<&StepDelta> {
+        self.deltas.back()
+    }
+
+    /// Iterates over the recorded steps, oldest first.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &StepDelta> {
+        self.deltas.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.deltas.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.deltas.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Step number of the oldest step which can still be undone.
+    pub fn first_step(&self) -> Option<u64> {
+        self.deltas.front().map(|delta| delta.step)
+    }
+}
+
+impl ExecutionObserver for History {
+    fn on_step(&mut self, _: &RamMachine, delta: &StepDelta) {
+        self.push(delta.clone());
+    }
+}
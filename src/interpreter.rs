@@ -1,12 +1,23 @@
+use crate::arithmetic::ArithmeticMode;
+use crate::history::{History, StepDelta, TapeWrite};
+use crate::limits::Limits;
+use crate::locale::{Lang, Localize, Message};
+use crate::observer::{ExecutionObserver, Observers};
 use crate::parser::{
     instruction::Instruction,
-    operand::{CellOperand, CellValue, ExpandError, Operand},
+    operand::{write_cell, CellAddress, CellOperand, CellValue, ExpandError, Operand},
+    source_map::Span,
     CodeParseError, RamCode,
 };
+use crate::stats::{address_cost, value_cost, RunStats};
+use crate::stream::{InputSource, OutputSink, StreamError, Streams};
+use crate::trace::{TraceRecord, Tracer};
+use num_traits::{One, Signed, ToPrimitive, Zero};
+use std::time::Duration;
 use tabled::{settings::Style, Table};
 use thiserror::Error;
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RamMachine {
     code: RamCode,
     tape: Vec<Option<CellValue>>,
@@ -14,41 +25,59 @@ pub struct RamMachine {
     input: Vec<CellValue>,
     input_pointer: usize,
     output: Vec<CellValue>,
+    steps: u64,
+    limits: Limits,
+    count_costs: bool,
+    logarithmic_time: u64,
+    cell_costs: Vec<u64>,
+    history: Option<History>,
+    last_write: Option<TapeWrite>,
+    trace: Option<Tracer>,
+    observers: Observers,
+    streams: Streams,
+    arithmetic_mode: ArithmeticMode,
 }
 
-#[derive(Error, Debug, PartialEq, Eq)]
-#[error("Buffer could not be accessed, because its value was never set.")]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{}", self.localize(Lang::En))]
 pub struct BufferError;
 
-#[derive(Error, Debug, PartialEq, Eq)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{}", self.localize(Lang::En))]
 pub enum RamMachineError {
-    #[error(transparent)]
     ExpandError(#[from] ExpandError),
-    #[error(transparent)]
     BufferError(#[from] BufferError),
-    #[error(transparent)]
     InputAccessError(#[from] InputAccessError),
-    #[error(transparent)]
     JumpError(#[from] JumpError),
-    #[error("Addition of `{0}` to `{1}` failed.")]
+    StreamError(#[from] StreamError),
     AdditionFailed(CellValue, CellValue),
-    #[error("Subtraction of `{0}` from `{1}` failed.")]
     SubtractionFailed(CellValue, CellValue),
-    #[error("Multiplication by `{0}` of `{1}` failed.")]
     MultiplicationFailed(CellValue, CellValue),
-    #[error("Division by `{0}` of `{1}` failed.")]
     DivisionFailed(CellValue, CellValue),
+    ModuloFailed(CellValue, CellValue),
+    StepLimitExceeded(u64),
+    TimeLimitExceeded(Duration, u64),
 }
 
-#[derive(Error, Debug, PartialEq, Eq)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{}", self.localize(Lang::En))]
+pub struct ExecutionError {
+    pub pointer: usize,
+    pub instruction: Instruction,
+    pub span: Option<Span>,
+    #[source]
+    pub error: RamMachineError,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{}", self.localize(Lang::En))]
 pub enum InputAccessError {
-    #[error("Input at index `{0}` not found.")]
     NotExistentInput(usize),
 }
 
-#[derive(Error, Debug, PartialEq, Eq)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{}", self.localize(Lang::En))]
 pub enum JumpError {
-    #[error("Label `{0}` could not be found in Ram code.")]
     LabelNotFound(String),
 }
 
@@ -67,26 +96,227 @@ impl RamMachine {
             input_pointer: 0,
             input,
             output: Vec::new(),
+            steps: 0,
+            limits: Limits::default(),
+            count_costs: false,
+            logarithmic_time: 0,
+            cell_costs: vec![0],
+            history: None,
+            last_write: None,
+            trace: None,
+            observers: Observers::default(),
+            streams: Streams::default(),
+            arithmetic_mode: ArithmeticMode::default(),
         }
     }
 
+    /// Counts the logarithmic costs reported by `stats`, `run_with_stats` turns it on as well.
+    pub fn with_stats(mut self) -> Self {
+        self.count_costs = true;
+        self
+    }
+
+    /// Records up to `capacity` most recent steps so they can be undone with `step_back`.
+    pub fn with_history(mut self, capacity: usize) -> Self {
+        self.history = Some(History::new(capacity));
+        self
+    }
+
+    pub fn history(&self) -> Option<&History> {
+        self.history.as_ref()
+    }
+
+    /// Records a `TraceRecord` for every executed instruction, collected with `take_trace`.
+    pub fn with_trace(mut self) -> Self {
+        self.trace = Some(Tracer::default());
+        self
+    }
+
+    /// Returns the records traced since the last call.
+    pub fn take_trace(&mut self) -> Vec<TraceRecord> {
+        self.trace
+            .as_mut()
+            .map(|trace| std::mem::take(&mut trace.records))
+            .unwrap_or_default()
+    }
+
+    pub fn with_observer(mut self, observer: impl ExecutionObserver + Send + 'static) -> Self {
+        self.add_observer(observer);
+        self
+    }
+
+    pub fn add_observer(&mut self, observer: impl ExecutionObserver + Send + 'static) {
+        self.observers.0.push(Box::new(observer));
+    }
+
+    /// Reads further values from `source` once the input passed to `new` is used up.
+    pub fn with_input_source(mut self, source: impl InputSource + Send + 'static) -> Self {
+        self.streams.source = Some(Box::new(source));
+        self
+    }
+
+    /// Passes every written value to `sink` as well as collecting it in `output`.
+    pub fn with_output_sink(mut self, sink: impl OutputSink + Send + 'static) -> Self {
+        self.streams.sink = Some(Box::new(sink));
+        self
+    }
+
+    pub fn with_arithmetic_mode(mut self, mode: ArithmeticMode) -> Self {
+        self.arithmetic_mode = mode;
+        self
+    }
+
+    pub fn arithmetic_mode(&self) -> ArithmeticMode {
+        self.arithmetic_mode
+    }
+
+    /// Replaces both the step and the time limit.
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn with_max_steps(mut self, max_steps: u64) -> Self {
+        self.limits.max_steps = Some(max_steps);
+        self
+    }
+
+    pub fn with_time_limit(mut self, time_limit: Duration) -> Self {
+        self.limits.time_limit = Some(time_limit);
+        self
+    }
+
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
     pub fn from_str(str: &str, input: Vec<CellValue>) -> Result<Self, CodeParseError> {
         Ok(RamMachine::new(str.parse()?, input))
     }
 
-    pub fn run(mut self) -> Result<Vec<CellValue>, RamMachineError> {
-        loop {
-            let instruction = self.code.instructions[self.pointer].clone();
-            if self.execute(&instruction)? == RunState::Halted {
-                break;
-            }
-        }
+    pub fn run(mut self) -> Result<Vec<CellValue>, ExecutionError> {
+        while self.run_line()? == RunState::Running {}
         Ok(self.output)
     }
 
-    pub fn run_line(&mut self) -> Result<RunState, RamMachineError> {
-        let instruction = self.code.instructions[self.pointer].clone();
-        self.execute(&instruction)
+    pub fn run_with_stats(mut self) -> Result<(Vec<CellValue>, RunStats), ExecutionError> {
+        self.count_costs = true;
+        while self.run_line()? == RunState::Running {}
+        let stats = self.stats();
+        Ok((self.output, stats))
+    }
+
+    pub fn run_line(&mut self) -> Result<RunState, ExecutionError> {
+        let Some(instruction) = self.code.instructions.get(self.pointer).cloned() else {
+            return Ok(RunState::Halted);
+        };
+        let cost = self
+            .count_costs
+            .then(|| self.logarithmic_cost(&instruction));
+        let (pointer, input_pointer, output_len) =
+            (self.pointer, self.input_pointer, self.output.len());
+        let observed = self.is_observed();
+        let jumped = observed && jump_taken(&instruction, self.tape[0].as_ref());
+        self.last_write = None;
+        self.notify(|observer, machine| observer.before_instruction(machine, &instruction));
+        let state = self
+            .limits
+            .check(self.steps)
+            .and_then(|_| self.execute(&instruction))
+            .map_err(|error| ExecutionError {
+                pointer: self.pointer,
+                span: self.code.source_map.instruction(self.pointer).cloned(),
+                instruction: instruction.clone(),
+                error,
+            })?;
+        let read = (self.input_pointer > input_pointer).then(|| &self.input[input_pointer]);
+        let cost = cost.map_or(0, |cost| cost + read.map_or(0, value_cost));
+        self.logarithmic_time = self.logarithmic_time.saturating_add(cost);
+        self.steps += 1;
+        if observed {
+            let delta = StepDelta {
+                step: self.steps - 1,
+                pointer,
+                input_pointer,
+                tape_write: self.last_write.take(),
+                output: self.output.get(output_len).cloned(),
+                logarithmic_cost: cost,
+            };
+            self.notify_executed(&instruction, &state, &delta, jumped);
+        }
+        Ok(state)
+    }
+
+    /// Undoes the most recently executed instruction, returns `false` if the history is empty.
+    pub fn step_back(&mut self) -> bool {
+        let Some(delta) = self.history.as_mut().and_then(History::pop) else {
+            return false;
+        };
+        self.pointer = delta.pointer;
+        self.input_pointer = delta.input_pointer;
+        self.steps = delta.step;
+        self.logarithmic_time = self.logarithmic_time.saturating_sub(delta.logarithmic_cost);
+        if let Some(write) = delta.tape_write {
+            self.tape[write.address] = write.old;
+            self.cell_costs[write.address] = write.old_cost;
+            self.tape.truncate(write.old_len);
+            self.cell_costs.truncate(write.old_len);
+        }
+        if delta.output.is_some() {
+            self.output.pop();
+        }
+        true
+    }
+
+    /// Logarithmic costs are only counted after `with_stats` or in `run_with_stats`.
+    pub fn stats(&self) -> RunStats {
+        RunStats {
+            uniform_time: self.steps,
+            logarithmic_time: self.logarithmic_time,
+            uniform_space: self.tape.iter().filter(|cell| cell.is_some()).count() as u64,
+            logarithmic_space: self.cell_costs.iter().sum(),
+        }
+    }
+
+    pub fn push_line(&mut self, line: &str) -> Result<(), CodeParseError> {
+        self.code.push_line(line)
+    }
+
+    pub fn push_input(&mut self, input: impl IntoIterator<Item = CellValue>) {
+        self.input.extend(input)
+    }
+
+    pub fn set_max_steps(&mut self, max_steps: Option<u64>) {
+        self.limits.max_steps = max_steps;
+    }
+
+    pub fn code(&self) -> &RamCode {
+        &self.code
+    }
+
+    pub fn pointer(&self) -> usize {
+        self.pointer
+    }
+
+    pub fn input(&self) -> &[CellValue] {
+        &self.input
+    }
+
+    /// Index of the next input value to be read.
+    pub fn input_pointer(&self) -> usize {
+        self.input_pointer
+    }
+
+    pub fn output(&self) -> &[CellValue] {
+        &self.output
+    }
+
+    pub fn tape(&self) -> &[Option<CellValue>] {
+        &self.tape
+    }
+
+    pub fn cell(&self, address: CellAddress) -> Option<CellValue> {
+        self.tape.get(address).cloned().flatten()
     }
 
     pub fn get_current_instruction(&self) -> &Instruction {
@@ -96,7 +326,21 @@ impl RamMachine {
             .unwrap_or(&Instruction::Halt)
     }
 
-    pub fn print_state(&self) {
+    pub fn max_steps(&self) -> Option<u64> {
+        self.limits.max_steps
+    }
+
+    pub fn time_limit(&self) -> Option<Duration> {
+        self.limits.time_limit
+    }
+
+    /// The tape, input, output and next instruction as printed by `print_state`.
+    pub fn state_table(&self) -> String {
+        self.state_table_in(Lang::En)
+    }
+
+    /// `state_table` with the labels in `lang`.
+    pub fn state_table_in(&self, lang: Lang) -> String {
         let tab: Vec<String> = self
             .tape
             .iter()
@@ -115,20 +359,88 @@ impl RamMachine {
 
         let next_instruction = self.get_current_instruction();
 
-        println!("{table}");
-        println!(
-            "Input:{}",
+        format!(
+            "{table}\n{}:{}\n{}:{}\n{}",
+            Message::InputTitle.localize(lang),
             self.input
                 .iter()
-                .fold("".to_string(), |s, v| format!("{s} {v}"))
-        );
-        println!(
-            "Output:{}",
+                .fold("".to_string(), |s, v| format!("{s} {v}")),
+            Message::OutputTitle.localize(lang),
             self.output
                 .iter()
-                .fold("".to_string(), |s, v| format!("{s} {v}"))
-        );
-        println!("Next instruction: {next_instruction}");
+                .fold("".to_string(), |s, v| format!("{s} {v}")),
+            Message::NextInstruction(&next_instruction.to_string()).localize(lang)
+        )
+    }
+
+    pub fn print_state(&self) {
+        println!("{}", self.state_table());
+    }
+
+    /// Calls `f` on the history, the trace and then the attached observers.
+    fn notify(&mut self, mut f: impl FnMut(&mut dyn ExecutionObserver, &RamMachine)) {
+        if !self.is_observed() {
+            return;
+        }
+        let mut history = self.history.take();
+        let mut trace = self.trace.take();
+        let mut observers = std::mem::take(&mut self.observers);
+        let all = history
+            .iter_mut()
+            .map(|history| history as &mut dyn ExecutionObserver)
+            .chain(
+                trace
+                    .iter_mut()
+                    .map(|trace| trace as &mut dyn ExecutionObserver),
+            )
+            .chain(
+                observers
+                    .0
+                    .iter_mut()
+                    .map(|observer| observer.as_mut() as _),
+            );
+        for observer in all {
+            f(observer, self);
+        }
+        self.history = history;
+        self.trace = trace;
+        self.observers = observers;
+    }
+
+    /// Whether anything records or observes the effects of each step.
+    fn is_observed(&self) -> bool {
+        self.history.is_some() || self.trace.is_some() || !self.observers.0.is_empty()
+    }
+
+    /// Reports the effects of a successfully executed instruction to the observers.
+    fn notify_executed(
+        &mut self,
+        instruction: &Instruction,
+        state: &RunState,
+        delta: &StepDelta,
+        jumped: bool,
+    ) {
+        self.notify(|observer, machine| {
+            if machine.input_pointer > delta.input_pointer {
+                observer.on_read(delta.input_pointer, &machine.input[delta.input_pointer]);
+            }
+            if let Some(write) = &delta.tape_write {
+                observer.on_tape_write(write.address, write.old.as_ref(), &write.new);
+            }
+            if let Some(value) = &delta.output {
+                observer.on_write(value);
+            }
+            if let Instruction::WriteString(text) = instruction {
+                observer.on_write_text(text);
+            }
+            if jumped {
+                observer.on_jump(delta.pointer, machine.pointer);
+            }
+            if *state == RunState::Halted {
+                observer.on_halt(machine);
+            }
+            observer.on_step(machine, delta);
+        });
     }
 
     fn jump_to(&mut self, label: &str) -> Result<RunState, JumpError> {
@@ -139,8 +451,8 @@ impl RamMachine {
             .ok_or_else(|| JumpError::LabelNotFound(label.to_owned()))
         {
             Ok(v) => {
-                if *v < self.code.instructions.len() {
-                    self.pointer = *v;
+                self.pointer = *v;
+                if self.pointer < self.code.instructions.len() {
                     return Ok(RunState::Running);
                 }
                 Ok(RunState::Halted)
@@ -149,13 +461,19 @@ impl RamMachine {
         }
     }
 
-    fn get_input(&mut self) -> Result<&CellValue, InputAccessError> {
-        let input = self
-            .input
-            .get(self.input_pointer)
-            .ok_or(InputAccessError::NotExistentInput(self.input_pointer));
-        self.input_pointer += 1;
-        input
+    fn put_output(&mut self, value: CellValue) -> Result<(), StreamError> {
+        if let Some(sink) = &mut self.streams.sink {
+            sink.write(&value)?;
+        }
+        self.output.push(value);
+        Ok(())
+    }
+
+    fn put_text(&mut self, text: &str) -> Result<(), StreamError> {
+        if let Some(sink) = &mut self.streams.sink {
+            sink.write_str(text)?;
+        }
+        Ok(())
     }
 
     fn advance_pointer(&mut self) -> RunState {
@@ -167,18 +485,83 @@ impl RamMachine {
     }
 
     fn get(&self, operand: &Operand) -> Result<CellValue, ExpandError> {
-        Ok(*operand.expand(&self.tape)?)
+        Ok(operand.expand(&self.tape)?.clone())
     }
 
     fn set(&mut self, cell_operand: &CellOperand, value: CellValue) -> Result<(), ExpandError> {
         let index = cell_operand.expand(&self.tape)?;
-        if self.tape.len() < index + 1 {
-            self.tape.resize(index + 1, None);
-        }
-        *self.tape.get_mut(index).expect("Tape was just resized") = Some(value);
+        self.write_cell(index, value);
         Ok(())
     }
 
+    fn set_buffer(&mut self, value: CellValue) {
+        self.write_cell(0, value);
+    }
+
+    fn write_cell(&mut self, index: CellAddress, value: CellValue) {
+        if self.is_observed() {
+            self.last_write = Some(TapeWrite {
+                address: index,
+                old: self.cell(index),
+                new: value.clone(),
+                old_cost: self.cell_costs.get(index).copied().unwrap_or_default(),
+                old_len: self.tape.len(),
+            });
+        }
+        if self.cell_costs.len() <= index {
+            self.cell_costs.resize(index + 1, 0);
+        }
+        if self.count_costs {
+            let cost = &mut self.cell_costs[index];
+            *cost = (*cost).max(value_cost(&value));
+        }
+        write_cell(&mut self.tape, index, value);
+    }
+
+    fn cell_cost(&self, address: CellAddress) -> u64 {
+        self.tape
+            .get(address)
+            .and_then(|val| val.as_ref())
+            .map_or(1, value_cost)
+    }
+
+    fn operand_cost(&self, operand: &Operand) -> u64 {
+        match operand {
+            Operand::Number(v) => value_cost(v),
+            Operand::ValueInCell(cell) => address_cost(*cell) + self.cell_cost(*cell),
+            Operand::ValueOfValueInCell(cell) => {
+                let inner = self.tape.get(*cell).and_then(|val| val.as_ref());
+                address_cost(*cell)
+                    + inner.map_or(1, value_cost)
+                    + inner
+                        .and_then(ToPrimitive::to_usize)
+                        .map_or(1, |a| self.cell_cost(a))
+            }
+        }
+    }
+
+    fn cell_operand_cost(&self, cell_operand: &CellOperand) -> u64 {
+        match cell_operand {
+            CellOperand::AddressOfCell(cell) => address_cost(*cell),
+            CellOperand::AddressOfCellInCell(cell) => address_cost(*cell) + self.cell_cost(*cell),
+        }
+    }
+
+    fn logarithmic_cost(&self, instruction: &Instruction) -> u64 {
+        use Instruction::*;
+        let buffer = self.cell_cost(0);
+        match instruction {
+            Load(o) | Write(o) => self.operand_cost(o),
+            Add(o) | Sub(o) | Mult(o) | Div(o) | Mod(o) => buffer + self.operand_cost(o),
+            Inc | Dec => buffer + 1,
+            Store(o) => buffer + self.cell_operand_cost(o),
+            // The cost of the value read is added once it was read
+            Read(o) => self.cell_operand_cost(o),
+            Jgtz(_) | Jzero(_) | Jneg(_) | Jgez(_) | Jnzero(_) => buffer,
+            Jump(_) | Halt | WriteString(_) => 1,
+        }
+    }
+
     fn buffer(&self) -> Result<&CellValue, BufferError> {
         self.tape
             .first()
@@ -186,77 +569,69 @@ impl RamMachine {
             .ok_or(BufferError)
     }
 
-    fn buffer_mut(&mut self) -> &mut Option<CellValue> {
-        self.tape
-            .get_mut(0)
-            .expect("Tape was initialized with length 1")
+    /// Replaces the buffer with `op(buffer, operand)`, failing with `error(operand, buffer)`.
+    fn arithmetic(
+        &mut self,
+        operand: &Operand,
+        op: fn(&ArithmeticMode, &CellValue, &CellValue) -> Option<CellValue>,
+        error: fn(CellValue, CellValue) -> RamMachineError,
+    ) -> Result<RunState, RamMachineError> {
+        let value = self.get(operand)?;
+        let buffer = self.buffer()?;
+        let result = op(&self.arithmetic_mode, buffer, &value)
+            .ok_or_else(|| error(value, buffer.clone()))?;
+        self.set_buffer(result);
+        Ok(self.advance_pointer())
     }
 
     fn execute(&mut self, instruction: &Instruction) -> Result<RunState, RamMachineError> {
         use Instruction::*;
         match instruction {
             Load(o) => {
-                *self.buffer_mut() = Some(self.get(o)?);
+                self.set_buffer(self.get(o)?);
                 Ok(self.advance_pointer())
             }
             Store(o) => {
-                self.set(o, *self.buffer()?)?;
-                Ok(self.advance_pointer())
-            }
-            Add(o) => {
-                *self.buffer_mut() = Some(self.buffer()?.checked_add(self.get(o)?).ok_or(
-                    RamMachineError::AdditionFailed(
-                        self.get(o).expect("Checked before"),
-                        *self.buffer().expect("Checked before"),
-                    ),
-                )?);
-                Ok(self.advance_pointer())
-            }
-            Sub(o) => {
-                *self.buffer_mut() = Some(self.buffer()?.checked_sub(self.get(o)?).ok_or(
-                    RamMachineError::SubtractionFailed(
-                        self.get(o).expect("Checked before"),
-                        *self.buffer().expect("Checked before"),
-                    ),
-                )?);
-                Ok(self.advance_pointer())
-            }
-            Mult(o) => {
-                *self.buffer_mut() = Some(self.buffer()?.checked_mul(self.get(o)?).ok_or(
-                    RamMachineError::MultiplicationFailed(
-                        self.get(o).expect("Checked before"),
-                        *self.buffer().expect("Checked before"),
-                    ),
-                )?);
-                Ok(self.advance_pointer())
-            }
-            Div(o) => {
-                *self.buffer_mut() = Some(self.buffer()?.checked_div(self.get(o)?).ok_or(
-                    RamMachineError::DivisionFailed(
-                        self.get(o).expect("Checked before"),
-                        *self.buffer().expect("Checked before"),
-                    ),
-                )?);
+                self.set(o, self.buffer()?.clone())?;
                 Ok(self.advance_pointer())
             }
+            Add(o) => self.arithmetic(o, ArithmeticMode::add, RamMachineError::AdditionFailed),
+            Sub(o) => self.arithmetic(o, ArithmeticMode::sub, RamMachineError::SubtractionFailed),
+            Mult(o) => self.arithmetic(
+                o,
+                ArithmeticMode::mul,
+                RamMachineError::MultiplicationFailed,
+            ),
+            Div(o) => self.arithmetic(o, ArithmeticMode::div, RamMachineError::DivisionFailed),
+            Mod(o) => self.arithmetic(o, ArithmeticMode::rem, RamMachineError::ModuloFailed),
+            Inc => self.arithmetic(
+                &Operand::Number(CellValue::one()),
+                ArithmeticMode::add,
+                RamMachineError::AdditionFailed,
+            ),
+            Dec => self.arithmetic(
+                &Operand::Number(CellValue::one()),
+                ArithmeticMode::sub,
+                RamMachineError::SubtractionFailed,
+            ),
             Read(o) => {
-                let input = *self.get_input()?;
+                let input = self
+                    .streams
+                    .read_input(&mut self.input, &mut self.input_pointer)?;
                 self.set(o, input)?;
                 Ok(self.advance_pointer())
             }
             Write(o) => {
-                self.output.push(self.get(o)?);
+                self.put_output(self.get(o)?)?;
                 Ok(self.advance_pointer())
             }
-            Jump(s) => Ok(self.jump_to(s)?),
-            Jgtz(s) => {
-                if *self.buffer()? > 0 {
-                    return Ok(self.jump_to(s)?);
-                }
+            WriteString(text) => {
+                self.put_text(text)?;
                 Ok(self.advance_pointer())
             }
-            Jzero(s) => {
-                if *self.buffer()? == 0 {
+            Jump(s) => Ok(self.jump_to(s)?),
+            Jgtz(s) | Jzero(s) | Jneg(s) | Jgez(s) | Jnzero(s) => {
+                if jump_taken(instruction, Some(self.buffer()?)) {
                     Ok(self.jump_to(s)?)
                 } else {
                     Ok(self.advance_pointer())
@@ -266,3 +641,17 @@ impl RamMachine {
         }
     }
 }
+
+/// Whether `instruction` jumps when the buffer holds `buffer`.
+fn jump_taken(instruction: &Instruction, buffer: Option<&CellValue>) -> bool {
+    use Instruction::*;
+    match instruction {
+        Jump(_) => true,
+        Jgtz(_) => buffer.is_some_and(Signed::is_positive),
+        Jzero(_) => buffer.is_some_and(Zero::is_zero),
+        Jneg(_) => buffer.is_some_and(Signed::is_negative),
+        Jgez(_) => buffer.is_some_and(|v| !v.is_negative()),
+        Jnzero(_) => buffer.is_some_and(|v| !v.is_zero()),
+        _ => false,
+    }
+}
@@ -1,6 +1,28 @@
+// `CellValue` is only `Copy` without the `bigint` feature, the code has to clone it for both
+#![cfg_attr(not(feature = "bigint"), allow(clippy::clone_on_copy))]
+// Errors carrying big integers are large, but a failed run is not on a hot path
+#![cfg_attr(feature = "bigint", allow(clippy::result_large_err))]
+
+pub mod analysis;
+pub mod arithmetic;
+pub mod cfg;
+pub mod compiled;
+pub mod dataflow;
+pub mod debugger;
+pub mod diagnostic;
 pub mod error;
+pub mod format;
+pub mod history;
 pub mod interpreter;
+pub mod limits;
+pub mod lint;
+pub mod locale;
+pub mod observer;
+pub mod optimize;
 pub mod parser;
+pub mod stats;
+pub mod stream;
+pub mod trace;
 
 pub use parser::instruction;
 pub use parser::operand;
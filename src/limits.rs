@@ -0,0 +1,53 @@
+use std::time::{Duration, Instant};
+
+use crate::interpreter::RamMachineError;
+
+const TIME_CHECK_INTERVAL: u64 = 1024;
+
+/// Step and time limits of a run, checked before every instruction by both machines.
+#[derive(Debug, Clone, Default)]
+pub struct Limits {
+    pub max_steps: Option<u64>,
+    pub time_limit: Option<Duration>,
+    started: Option<Instant>,
+}
+
+/// Machines with the same limits are equal, whenever they started running.
+impl PartialEq for Limits {
+    fn eq(&self, other: &Self) -> bool {
+        self.max_steps == other.max_steps && self.time_limit == other.time_limit
+    }
+}
+
+impl Eq for Limits {}
+
+impl Limits {
+    pub fn new(max_steps: Option<u64>, time_limit: Option<Duration>) -> Self {
+        Limits {
+            max_steps,
+            time_limit,
+            started: None,
+        }
+    }
+
+    pub(crate) fn is_set(&self) -> bool {
+        self.max_steps.is_some() || self.time_limit.is_some()
+    }
+
+    /// Fails once `steps` reached the step limit or the run took longer than the time limit.
+    pub(crate) fn check(&mut self, steps: u64) -> Result<(), RamMachineError> {
+        if let Some(max_steps) = self.max_steps {
+            if steps >= max_steps {
+                return Err(RamMachineError::StepLimitExceeded(steps));
+            }
+        }
+        if let Some(time_limit) = self.time_limit {
+            let started = *self.started.get_or_insert_with(Instant::now);
+            // Reading the clock on every step is noticeably slow for long runs
+            if steps.is_multiple_of(TIME_CHECK_INTERVAL) && started.elapsed() > time_limit {
+                return Err(RamMachineError::TimeLimitExceeded(time_limit, steps));
+            }
+        }
+        Ok(())
+    }
+}
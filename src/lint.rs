@@ -0,0 +1,417 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    str::FromStr,
+};
+
+use num_traits::Zero;
+use thiserror::Error;
+
+use crate::{
+    cfg::ControlFlowGraph,
+    dataflow::{DefiniteAssignment, Initialization},
+    diagnostic::{Diagnostic, Severity},
+    instruction::Instruction,
+    locale::{Lang, Localize, Message},
+    operand::Operand,
+    parser::{cst::Cst, source_map::Span, RamCode},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Rule {
+    /// Instructions after a `JUMP` or `HALT` which no jump leads to
+    UnreachableCode,
+    /// Labels which are never jumped to
+    UnusedLabel,
+    /// Reads of cells which are not stored on any path
+    UninitializedRead,
+    /// Uses of the buffer before anything is loaded into it
+    UninitializedBuffer,
+    /// `DIV =0` and `MOD =0`
+    DivisionByZero,
+    /// Running past the last instruction instead of stopping with `HALT`
+    MissingHalt,
+    /// Labels after the last instruction, jumping to them runs past the end of the program
+    TrailingLabel,
+    /// Loops which change nothing, so they may never end once entered
+    InfiniteLoop,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Allow,
+    Warn,
+    Deny,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{}", self.localize(Lang::En))]
+pub struct RuleParseError(pub String);
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{}", self.localize(Lang::En))]
+pub struct LevelParseError(pub String);
+
+/// Level of every rule, the ones which are not set use their default level.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct LintConfig {
+    levels: HashMap<Rule, Level>,
+}
+
+const DIRECTIVE: &str = "lint:";
+
+impl Rule {
+    pub const ALL: [Rule; 8] = [
+        Rule::UnreachableCode,
+        Rule::UnusedLabel,
+        Rule::UninitializedRead,
+        Rule::UninitializedBuffer,
+        Rule::DivisionByZero,
+        Rule::MissingHalt,
+        Rule::TrailingLabel,
+        Rule::InfiniteLoop,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Rule::UnreachableCode => "unreachable-code",
+            Rule::UnusedLabel => "unused-label",
+            Rule::UninitializedRead => "uninitialized-read",
+            Rule::UninitializedBuffer => "uninitialized-buffer",
+            Rule::DivisionByZero => "division-by-zero",
+            Rule::MissingHalt => "missing-halt",
+            Rule::TrailingLabel => "trailing-label",
+            Rule::InfiniteLoop => "infinite-loop",
+        }
+    }
+
+    pub fn default_level(self) -> Level {
+        match self {
+            // A conditional jump to itself may be left, so `InfiniteLoop` only warns
+            Rule::DivisionByZero => Level::Deny,
+            _ => Level::Warn,
+        }
+    }
+}
+
+impl LintConfig {
+    pub fn level(&self, rule: Rule) -> Level {
+        self.levels
+            .get(&rule)
+            .copied()
+            .unwrap_or(rule.default_level())
+    }
+
+    pub fn set(&mut self, rule: Rule, level: Level) {
+        self.levels.insert(rule, level);
+    }
+}
+
+/// A rule broken by an instruction or label.
+struct Finding<'a> {
+    rule: Rule,
+    span: Span,
+    message: Message<'a>,
+}
+
+/// Checks `code`, parsed from `source`, against every rule which is not allowed.
+///
+/// A `# lint: allow(rule)` comment allows the rules on its own line, or on the next line if it is
+/// the only thing on its line. A `#!lint deny(rule)` pragma sets the level for the whole file,
+/// overriding the level in `config`.
+pub fn lint(source: &str, code: &RamCode, config: &LintConfig, lang: Lang) -> Vec<Diagnostic> {
+    let cst = Cst::parse(source);
+    let mut config = config.clone();
+    let mut allowed: HashSet<(usize, Rule)> = HashSet::new();
+    for line in &cst.lines {
+        if let Some(("lint", value)) = line.pragma() {
+            for (level, rules) in directives(value) {
+                for rule in rules {
+                    config.set(rule, level);
+                }
+            }
+            continue;
+        }
+        let Some(comment) = line.comment() else {
+            continue;
+        };
+        let Some((_, directive)) = comment.text.split_once(DIRECTIVE) else {
+            continue;
+        };
+        let target = match line.code().next() {
+            Some(_) => line.number,
+            None => line.number + 1,
+        };
+        for (level, rules) in directives(directive) {
+            if level == Level::Allow {
+                allowed.extend(rules.into_iter().map(|rule| (target, rule)));
+            }
+        }
+    }
+
+    let lines: Vec<&str> = source.lines().collect();
+    let mut diagnostics: Vec<Diagnostic> = Linter::new(code)
+        .findings()
+        .into_iter()
+        .filter(|finding| !allowed.contains(&(finding.span.line, finding.rule)))
+        .filter_map(|finding| {
+            let severity = match config.level(finding.rule) {
+                Level::Allow => return None,
+                Level::Warn => Severity::Warning,
+                Level::Deny => Severity::Error,
+            };
+            let name = finding.rule.name();
+            Some(Diagnostic {
+                severity,
+                code: name,
+                message: finding.message.localize(lang),
+                source_line: lines
+                    .get(finding.span.line.saturating_sub(1))
+                    .copied()
+                    .unwrap_or_default()
+                    .to_owned(),
+                span: finding.span,
+                help: Some(Message::AllowLint(name).localize(lang)),
+            })
+        })
+        .collect();
+    diagnostics.sort_by_key(|d| (d.span.line, d.span.columns.start));
+    diagnostics
+}
+
+/// Levels and rules of directives such as `allow(unused-label, missing-halt) warn(infinite-loop)`,
+/// unknown rules are skipped.
+fn directives(s: &str) -> Vec<(Level, Vec<Rule>)> {
+    let mut directives = Vec::new();
+    let mut rest = s;
+    while let Some((level, after)) = rest.split_once('(') {
+        let Some((rules, after)) = after.split_once(')') else {
+            break;
+        };
+        rest = after;
+        let Ok(level) = level.trim().parse() else {
+            continue;
+        };
+        let rules = rules
+            .split(',')
+            .filter_map(|rule| rule.trim().parse().ok())
+            .collect();
+        directives.push((level, rules));
+    }
+    directives
+}
+
+struct Linter<'a> {
+    code: &'a RamCode,
+    graph: ControlFlowGraph,
+    reachable: Vec<bool>,
+}
+
+impl<'a> Linter<'a> {
+    fn new(code: &'a RamCode) -> Self {
+        let graph = ControlFlowGraph::new(code);
+        let mut reachable = vec![false; graph.blocks.len()];
+        let mut stack = vec![0];
+        while let Some(block) = stack.pop() {
+            match reachable.get_mut(block) {
+                Some(seen) if !*seen => *seen = true,
+                _ => continue,
+            }
+            stack.extend(graph.successors(block).map(|edge| edge.to));
+        }
+        Linter {
+            code,
+            graph,
+            reachable,
+        }
+    }
+
+    fn findings(&self) -> Vec<Finding<'a>> {
+        let mut findings = Vec::new();
+        findings.extend(self.unreachable_code());
+        findings.extend(self.unused_labels());
+        findings.extend(self.uninitialized_reads());
+        findings.extend(self.division_by_zero());
+        findings.extend(self.missing_halt());
+        findings.extend(self.trailing_labels());
+        findings.extend(self.infinite_loops());
+        findings
+    }
+
+    fn span(&self, instruction: usize) -> Span {
+        self.code
+            .source_map
+            .instruction(instruction)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn unreachable_code(&self) -> Vec<Finding<'a>> {
+        self.graph
+            .blocks
+            .iter()
+            .zip(&self.reachable)
+            .filter(|(_, reachable)| !**reachable)
+            .map(|(block, _)| Finding {
+                rule: Rule::UnreachableCode,
+                span: self.span(block.instructions.start),
+                message: Message::NeverExecuted,
+            })
+            .collect()
+    }
+
+    fn unused_labels(&self) -> Vec<Finding<'a>> {
+        let used: HashSet<&str> = self
+            .code
+            .instructions
+            .iter()
+            .filter_map(Instruction::label)
+            .collect();
+        self.code
+            .source_map
+            .labels
+            .iter()
+            .filter(|label| !used.contains(label.label.as_str()))
+            .map(|label| Finding {
+                rule: Rule::UnusedLabel,
+                span: label.span.clone(),
+                message: Message::UnusedLabel(&label.label),
+            })
+            .collect()
+    }
+
+    fn trailing_labels(&self) -> Vec<Finding<'a>> {
+        self.code
+            .trailing_labels()
+            .map(|label| Finding {
+                rule: Rule::TrailingLabel,
+                span: label.span.clone(),
+                message: Message::TrailingLabel(&label.label),
+            })
+            .collect()
+    }
+
+    fn uninitialized_reads(&self) -> Vec<Finding<'a>> {
+        DefiniteAssignment::new(self.code)
+            .uses
+            .into_iter()
+            .filter(|cell_use| cell_use.initialization == Initialization::Never)
+            .map(|cell_use| match cell_use.implicit {
+                true => Finding {
+                    rule: Rule::UninitializedBuffer,
+                    span: self.span(cell_use.instruction),
+                    message: Message::BufferNeverSet,
+                },
+                false => Finding {
+                    rule: Rule::UninitializedRead,
+                    span: self.span(cell_use.instruction),
+                    message: Message::CellNeverStoredBefore(cell_use.cell),
+                },
+            })
+            .collect()
+    }
+
+    fn division_by_zero(&self) -> Vec<Finding<'a>> {
+        self.code
+            .instructions
+            .iter()
+            .enumerate()
+            .filter(|(_, instruction)| {
+                matches!(instruction, Instruction::Div(Operand::Number(n)) | Instruction::Mod(Operand::Number(n)) if n.is_zero())
+            })
+            .map(|(index, _)| Finding {
+                rule: Rule::DivisionByZero,
+                span: self.span(index),
+                message: Message::DivisionByZero,
+            })
+            .collect()
+    }
+
+    fn missing_halt(&self) -> Vec<Finding<'a>> {
+        let Some(last) = self.code.instructions.len().checked_sub(1) else {
+            return Vec::new();
+        };
+        let reachable = self
+            .graph
+            .block_of(last)
+            .is_some_and(|block| self.reachable[block]);
+        let stops = matches!(
+            self.code.instructions[last],
+            Instruction::Halt | Instruction::Jump(_)
+        );
+        if !reachable || stops {
+            return Vec::new();
+        }
+        vec![Finding {
+            rule: Rule::MissingHalt,
+            span: self.span(last),
+            message: Message::MissingHalt,
+        }]
+    }
+
+    fn infinite_loops(&self) -> Vec<Finding<'a>> {
+        self.graph
+            .edges
+            .iter()
+            .filter(|edge| edge.from == edge.to)
+            .filter(|edge| {
+                self.graph.blocks[edge.from]
+                    .instructions
+                    .clone()
+                    .all(|index| has_no_effect(&self.code.instructions[index]))
+            })
+            .map(|edge| Finding {
+                rule: Rule::InfiniteLoop,
+                span: self.span(self.graph.blocks[edge.from].instructions.end - 1),
+                message: Message::InfiniteLoop,
+            })
+            .collect()
+    }
+}
+
+/// Whether executing the instruction again and again leaves the machine in the same state.
+fn has_no_effect(instruction: &Instruction) -> bool {
+    use Instruction::*;
+    matches!(
+        instruction,
+        Load(_) | Jump(_) | Jgtz(_) | Jzero(_) | Jneg(_) | Jgez(_) | Jnzero(_)
+    )
+}
+
+impl FromStr for Rule {
+    type Err = RuleParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Rule::ALL
+            .into_iter()
+            .find(|rule| rule.name() == s)
+            .ok_or_else(|| RuleParseError(s.to_owned()))
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Level {
+    type Err = LevelParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "allow" => Ok(Level::Allow),
+            "warn" => Ok(Level::Warn),
+            "deny" => Ok(Level::Deny),
+            _ => Err(LevelParseError(s.to_owned())),
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Level::Allow => write!(f, "allow"),
+            Level::Warn => write!(f, "warn"),
+            Level::Deny => write!(f, "deny"),
+        }
+    }
+}
@@ -0,0 +1,1070 @@
+use std::{env, fmt, str::FromStr};
+
+use thiserror::Error;
+
+use crate::analysis::RenameError;
+use crate::arithmetic::ArithmeticModeError;
+use crate::debugger::DebuggerError;
+use crate::diagnostic::Severity;
+use crate::error::ParserErrorChain;
+use crate::format::CaseParseError;
+use crate::instruction::{DialectParseError, InstructionParseError};
+use crate::interpreter::{
+    BufferError, ExecutionError, InputAccessError, JumpError, RamMachineError,
+};
+use crate::lint::{LevelParseError, Rule, RuleParseError};
+use crate::operand::{CellAddress, ExpandError, InvalidOperandError, OperandParseError};
+use crate::optimize::{Pass, PassParseError};
+use crate::parser::{validate::ValidationError, CodeParseError};
+use crate::stats::RunStats;
+use crate::stream::StreamError;
+
+/// Language of messages, or of the keywords accepted by the parser.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Lang {
+    #[default]
+    En,
+    /// Polish messages, or keywords such as `CZYTAJ` and `PISZ` next to the English ones
+    Pl,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{}", self.localize(Lang::En))]
+pub struct LangParseError(pub String);
+
+impl Lang {
+    /// Language of a locale such as `pl_PL.UTF-8`, `None` if it is not supported.
+    pub fn from_locale(locale: &str) -> Option<Lang> {
+        let language = locale.split(['_', '.', '@', '-']).next()?;
+        language.parse().ok()
+    }
+
+    /// Language from `LC_ALL`, `LC_MESSAGES` or `LANG`, falling back to English.
+    pub fn from_env() -> Lang {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|locale| Lang::from_locale(&locale))
+            .unwrap_or_default()
+    }
+}
+
+impl FromStr for Lang {
+    type Err = LangParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "en" | "english" | "c" | "posix" => Ok(Lang::En),
+            "pl" | "polish" | "polski" => Ok(Lang::Pl),
+            _ => Err(LangParseError(s.to_owned())),
+        }
+    }
+}
+
+impl fmt::Display for Lang {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Lang::En => write!(f, "en"),
+            Lang::Pl => write!(f, "pl"),
+        }
+    }
+}
+
+/// Messages which can be shown in every `Lang`.
+///
+/// The English and Polish texts of every message are kept together in this file, errors display
+/// their English text.
+pub trait Localize {
+    fn localize(&self, lang: Lang) -> String;
+}
+
+/// Formats the English or the Polish text, both capture the same variables.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! message {
+    ($lang:expr, $en:literal, $pl:literal $(,)?) => {
+        match $lang {
+            $crate::locale::Lang::En => format!($en),
+            $crate::locale::Lang::Pl => format!($pl),
+        }
+    };
+}
+
+/// Displays the wrapped message in the given language.
+#[derive(Debug)]
+pub struct Localized<T>(pub T, pub Lang);
+
+impl<T: Localize> fmt::Display for Localized<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.localize(self.1))
+    }
+}
+
+/// Texts of diagnostics, hovers, completions and the interactive tools which are not errors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message<'a> {
+    /// Label in front of the help of a diagnostic
+    Help,
+    AddJumpLabel(&'a str),
+    OperandSyntax,
+    CellOperandSyntax,
+    NoArgument(&'a str),
+    OneInstructionPerLine,
+    UniqueLabels,
+    DefineLabel(&'a str),
+    DialectPragma,
+    CloseString,
+    KeywordsPragma,
+    ExtendedKeyword(&'a str),
+    DidYouMean(&'a str),
+    AllowLint(&'a str),
+    NeverExecuted,
+    UnusedLabel(&'a str),
+    TrailingLabel(&'a str),
+    BufferNeverSet,
+    CellNeverStored(CellAddress),
+    CellNeverStoredBefore(CellAddress),
+    BufferMaybeUnset,
+    CellMaybeUnset(CellAddress),
+    UnsetPath(&'a str),
+    DivisionByZero,
+    MissingHalt,
+    InfiniteLoop,
+    /// Summary of `ram check` after it found the given number of errors in the file
+    CouldNotParse(&'a str, usize),
+    InstructionNumber(usize),
+    NumberOperand,
+    IndirectOperand,
+    TextOperand,
+    CellOperand,
+    /// Commands of `ram debug`
+    DebuggerHelp,
+    /// Commands of `ram repl`
+    ReplHelp,
+    ReplGreeting,
+    /// A command the user typed which doesn't exist, with the command showing the help
+    UnknownCommand(&'a str, &'a str),
+    InvalidStepCount(&'a str),
+    InvalidStepNumber(&'a str),
+    InvalidCell(&'a str),
+    NextInstruction(&'a str),
+    /// Position of the machine after it stopped, with the next instruction
+    StepNext(u64, &'a str),
+    Step(u64),
+    BreakpointHit(usize),
+    /// Cell, its old and its new value
+    WatchpointHit(CellAddress, &'a str, &'a str),
+    StepBreakpointHit(u64, usize),
+    StepCellChanged(u64, CellAddress),
+    HaltedAfter(u64),
+    StartOfHistory,
+    Running(u64),
+    StoppedAfter(u64),
+    /// Breakpoint number, the instruction and its condition
+    BreakpointAdded(usize, usize, Option<&'a str>),
+    BreakpointDeleted(&'a str),
+    NoBreakpoint(&'a str),
+    BreakpointSet(usize),
+    BreakpointRemoved(usize),
+    Watching(CellAddress),
+    WatchpointOn(CellAddress),
+    Unwatched(CellAddress),
+    NoWatchpoint(&'a str),
+    Output(&'a str),
+    ReplHalted,
+    NothingToUndo,
+    TuiStart,
+    /// Actions in the key bar of `ram tui`
+    KeyStep,
+    KeyStepBack,
+    KeyRun,
+    KeyReverseRun,
+    KeyFinish,
+    KeyBreakpoint,
+    KeyStopRun,
+    KeySelectLine,
+    KeyQuit,
+    /// Titles of the panes showing the machine
+    SourceTitle,
+    TapeTitle,
+    InputTitle,
+    OutputTitle,
+    ProgramRequired,
+    UnsupportedRequest(&'a str),
+}
+
+impl Localize for Message<'_> {
+    fn localize(&self, lang: Lang) -> String {
+        match *self {
+            Message::Help => message!(lang, "help", "pomoc"),
+            Message::AddJumpLabel(keyword) => message!(
+                lang,
+                "add the label to jump to, e.g. `{keyword} LOOP`",
+                "dodaj etykietę, do której ma nastąpić skok, np. `{keyword} PETLA`"
+            ),
+            Message::OperandSyntax => message!(
+                lang,
+                "operands are written as `=x` (number), `x` (cell) or `^x` (indirect)",
+                "operandy zapisuje się jako `=x` (liczba), `x` (komórka) lub `^x` (adres pośredni)"
+            ),
+            Message::CellOperandSyntax => message!(
+                lang,
+                "this keyword expects a cell, written as `x` or `^x`",
+                "to słowo kluczowe oczekuje komórki, zapisanej jako `x` lub `^x`"
+            ),
+            Message::NoArgument(keyword) => message!(
+                lang,
+                "`{keyword}` does not take an argument",
+                "`{keyword}` nie przyjmuje argumentu"
+            ),
+            Message::OneInstructionPerLine => message!(
+                lang,
+                "put each instruction on its own line and start comments with `#`",
+                "umieść każdą instrukcję w osobnej linii, a komentarze zaczynaj od `#`"
+            ),
+            Message::UniqueLabels => message!(
+                lang,
+                "labels must be unique, rename one of the definitions",
+                "etykiety muszą być unikalne, zmień nazwę jednej z definicji"
+            ),
+            Message::DefineLabel(label) => message!(
+                lang,
+                "define the label with `{label}:` before an instruction",
+                "zdefiniuj etykietę, pisząc `{label}:` przed instrukcją"
+            ),
+            Message::DialectPragma => message!(
+                lang,
+                "use `#!dialect strict` or `#!dialect extended`",
+                "użyj `#!dialect strict` lub `#!dialect extended`"
+            ),
+            Message::CloseString => message!(
+                lang,
+                "close the string with `\"`, write `\\\"` for a quote inside it",
+                "zamknij napis znakiem `\"`, cudzysłów wewnątrz zapisz jako `\\\"`"
+            ),
+            Message::KeywordsPragma => message!(
+                lang,
+                "use `#!keywords en` or `#!keywords pl`",
+                "użyj `#!keywords en` lub `#!keywords pl`"
+            ),
+            Message::ExtendedKeyword(keyword) => message!(
+                lang,
+                "`{keyword}` is part of the extended dialect, enable it with `#!dialect extended`",
+                "`{keyword}` należy do rozszerzonego dialektu, włącz go przez `#!dialect extended`"
+            ),
+            Message::DidYouMean(candidate) => message!(
+                lang,
+                "did you mean `{candidate}`?",
+                "czy chodziło o `{candidate}`?"
+            ),
+            Message::AllowLint(rule) => message!(
+                lang,
+                "to allow it on this line, add `# lint: allow({rule})`",
+                "aby zezwolić na to w tej linii, dodaj `# lint: allow({rule})`"
+            ),
+            Message::NeverExecuted => message!(
+                lang,
+                "this instruction is never executed",
+                "ta instrukcja nigdy nie jest wykonywana"
+            ),
+            Message::UnusedLabel(label) => message!(
+                lang,
+                "label `{label}` is never jumped to",
+                "do etykiety `{label}` nie prowadzi żaden skok"
+            ),
+            Message::TrailingLabel(label) => message!(
+                lang,
+                "label `{label}` is placed after the last instruction, end the program with `HALT` instead",
+                "etykieta `{label}` stoi za ostatnią instrukcją, zakończ program instrukcją `HALT`"
+            ),
+            Message::BufferNeverSet => message!(
+                lang,
+                "the buffer is used before anything is loaded into it",
+                "akumulator jest używany, zanim cokolwiek zostało do niego załadowane"
+            ),
+            Message::CellNeverStored(cell) => message!(
+                lang,
+                "cell {cell} is read before it is ever stored",
+                "komórka {cell} jest odczytywana, zanim cokolwiek zostało do niej zapisane"
+            ),
+            Message::CellNeverStoredBefore(cell) => message!(
+                lang,
+                "cell {cell} is read, but it is never stored before",
+                "komórka {cell} jest odczytywana, ale wcześniej nic do niej nie zapisano"
+            ),
+            Message::BufferMaybeUnset => message!(
+                lang,
+                "the buffer may be used before anything is loaded into it",
+                "akumulator może być użyty, zanim cokolwiek zostanie do niego załadowane"
+            ),
+            Message::CellMaybeUnset(cell) => message!(
+                lang,
+                "cell {cell} may be read before it is stored",
+                "komórka {cell} może być odczytana, zanim cokolwiek zostanie do niej zapisane"
+            ),
+            Message::UnsetPath(path) => message!(
+                lang,
+                "it is unset when the program goes through lines {path}",
+                "nie jest ustawiona, gdy program przechodzi przez linie {path}"
+            ),
+            Message::DivisionByZero => message!(
+                lang,
+                "this instruction always divides by zero",
+                "ta instrukcja zawsze dzieli przez zero"
+            ),
+            Message::MissingHalt => message!(
+                lang,
+                "the program runs past its last instruction, end it with `HALT`",
+                "program wychodzi poza ostatnią instrukcję, zakończ go instrukcją `HALT`"
+            ),
+            Message::InfiniteLoop => message!(
+                lang,
+                "this loop changes nothing, once entered it may never end",
+                "ta pętla niczego nie zmienia, po wejściu do niej może się nigdy nie skończyć"
+            ),
+            Message::CouldNotParse(path, 1) => message!(
+                lang,
+                "error: could not parse `{path}` due to 1 previous error",
+                "błąd: nie udało się przetworzyć `{path}` z powodu 1 błędu"
+            ),
+            Message::CouldNotParse(path, count) => message!(
+                lang,
+                "error: could not parse `{path}` due to {count} previous errors",
+                "błąd: nie udało się przetworzyć `{path}` z powodu {count} błędów"
+            ),
+            Message::InstructionNumber(target) => {
+                message!(lang, "instruction {target}", "instrukcja {target}")
+            }
+            Message::NumberOperand => message!(
+                lang,
+                "number, the value itself",
+                "liczba, wartość podana wprost"
+            ),
+            Message::IndirectOperand => message!(
+                lang,
+                "indirect, the cell whose address is stored in this cell",
+                "adres pośredni, komórka o adresie zapisanym w tej komórce"
+            ),
+            Message::TextOperand => message!(
+                lang,
+                "text written to the output",
+                "tekst wypisywany na wyjście"
+            ),
+            Message::CellOperand => message!(
+                lang,
+                "cell, the value stored in this cell",
+                "komórka, wartość zapisana w tej komórce"
+            ),
+            Message::DebuggerHelp => message!(
+                lang,
+                "\
+Commands:
+  step [n]                     Execute the next n instructions (default 1)
+  continue                     Run until a breakpoint or watchpoint is hit
+  finish                       Run until the machine halts, ignoring breakpoints
+  step-back [n]                Undo the last n instructions (default 1)
+  reverse-continue             Run backwards until a breakpoint or watchpoint is hit
+  goto <step>                  Move forwards or backwards to the given step number
+  break <label|line> [if <c>]  Stop before an instruction, e.g. `break LOOP if [2] == 0`
+  delete <n>                   Remove the n-th breakpoint
+  watch <cell>                 Stop when the value of a tape cell changes
+  unwatch <cell>               Remove a watchpoint
+  info                         List breakpoints and watchpoints
+  print [cell]                 Print a tape cell, or the whole machine state
+  help                         Show this message
+  quit                         Leave the debugger
+An empty line repeats the previous command.",
+                "\
+Polecenia:
+  step [n]                     Wykonaj n kolejnych instrukcji (domyślnie 1)
+  continue                     Uruchom do punktu przerwania lub obserwacji
+  finish                       Uruchom do zatrzymania maszyny, pomijając punkty przerwania
+  step-back [n]                Cofnij n ostatnich instrukcji (domyślnie 1)
+  reverse-continue             Cofaj do punktu przerwania lub obserwacji
+  goto <krok>                  Przejdź do przodu lub wstecz do kroku o danym numerze
+  break <etykieta|linia> [if <w>]
+                               Zatrzymaj przed instrukcją, np. `break PETLA if [2] == 0`
+  delete <n>                   Usuń n-ty punkt przerwania
+  watch <komórka>              Zatrzymaj, gdy zmieni się wartość komórki taśmy
+  unwatch <komórka>            Usuń punkt obserwacji
+  info                         Wypisz punkty przerwania i obserwacji
+  print [komórka]              Wypisz komórkę taśmy lub cały stan maszyny
+  help                         Pokaż tę wiadomość
+  quit                         Zakończ debugger
+Pusta linia powtarza poprzednie polecenie."
+            ),
+            Message::ReplHelp => message!(
+                lang,
+                "\
+Type RAM instructions to execute them immediately, e.g. `load =5` or `loop: add =1`.
+Meta-commands:
+  :tape            Show the tape, input, output and next instruction
+  :input <values>  Append values to the input
+  :load <file>     Execute a file in the current session
+  :reset           Start over with an empty machine
+  :undo            Revert the last instruction, input or load
+  :help            Show this message
+  :quit            Leave the REPL",
+                "\
+Wpisuj instrukcje maszyny RAM, aby od razu je wykonać, np. `load =5` lub `petla: add =1`.
+Polecenia:
+  :tape            Pokaż taśmę, wejście, wyjście i następną instrukcję
+  :input <wartości>
+                   Dopisz wartości do wejścia
+  :load <plik>     Wykonaj plik w bieżącej sesji
+  :reset           Zacznij od nowa z pustą maszyną
+  :undo            Cofnij ostatnią instrukcję, wejście lub wczytanie pliku
+  :help            Pokaż tę wiadomość
+  :quit            Zakończ REPL"
+            ),
+            Message::ReplGreeting => message!(
+                lang,
+                "RAM machine REPL, type :help for help",
+                "REPL maszyny RAM, wpisz :help, aby uzyskać pomoc"
+            ),
+            Message::UnknownCommand(command, help) => message!(
+                lang,
+                "Unknown command `{command}`, see {help}",
+                "Nieznane polecenie `{command}`, zobacz {help}"
+            ),
+            Message::InvalidStepCount(argument) => message!(
+                lang,
+                "`{argument}` is not a valid number of steps",
+                "`{argument}` nie jest poprawną liczbą kroków"
+            ),
+            Message::InvalidStepNumber(argument) => message!(
+                lang,
+                "`{argument}` is not a valid step number",
+                "`{argument}` nie jest poprawnym numerem kroku"
+            ),
+            Message::InvalidCell(argument) => message!(
+                lang,
+                "`{argument}` is not a valid cell address",
+                "`{argument}` nie jest poprawnym adresem komórki"
+            ),
+            Message::NextInstruction(next) => message!(
+                lang,
+                "Next instruction: {next}",
+                "Następna instrukcja: {next}"
+            ),
+            Message::StepNext(steps, next) => message!(
+                lang,
+                "Step {steps}, next instruction: {next}",
+                "Krok {steps}, następna instrukcja: {next}"
+            ),
+            Message::Step(steps) => message!(lang, "Step {steps}", "Krok {steps}"),
+            Message::BreakpointHit(index) => message!(
+                lang,
+                "Breakpoint {index} hit",
+                "Osiągnięto punkt przerwania {index}"
+            ),
+            Message::WatchpointHit(cell, old, new) => message!(
+                lang,
+                "Watchpoint on cell {cell}: {old} -> {new}",
+                "Punkt obserwacji komórki {cell}: {old} -> {new}"
+            ),
+            Message::StepBreakpointHit(steps, index) => message!(
+                lang,
+                "Step {steps}, breakpoint {index} hit",
+                "Krok {steps}, osiągnięto punkt przerwania {index}"
+            ),
+            Message::StepCellChanged(steps, cell) => message!(
+                lang,
+                "Step {steps}, cell {cell} changed",
+                "Krok {steps}, komórka {cell} się zmieniła"
+            ),
+            Message::HaltedAfter(steps) => message!(
+                lang,
+                "Machine halted after {steps} steps",
+                "Maszyna zatrzymała się po {steps} krokach"
+            ),
+            Message::StartOfHistory => message!(
+                lang,
+                "Reached the start of the recorded history",
+                "Osiągnięto początek zapisanej historii"
+            ),
+            Message::Running(steps) => message!(
+                lang,
+                "Running, step {steps}, press Esc to stop",
+                "Trwa wykonywanie, krok {steps}, naciśnij Esc, aby zatrzymać"
+            ),
+            Message::StoppedAfter(steps) => message!(
+                lang,
+                "Stopped after {steps} steps",
+                "Zatrzymano po {steps} krokach"
+            ),
+            Message::BreakpointAdded(index, location, None) => message!(
+                lang,
+                "Breakpoint {index} at instruction {location}",
+                "Punkt przerwania {index} na instrukcji {location}"
+            ),
+            Message::BreakpointAdded(index, location, Some(condition)) => message!(
+                lang,
+                "Breakpoint {index} at instruction {location} if {condition}",
+                "Punkt przerwania {index} na instrukcji {location}, jeśli {condition}"
+            ),
+            Message::BreakpointDeleted(index) => message!(
+                lang,
+                "Deleted breakpoint {index}",
+                "Usunięto punkt przerwania {index}"
+            ),
+            Message::NoBreakpoint(index) => message!(
+                lang,
+                "No breakpoint `{index}`",
+                "Brak punktu przerwania `{index}`"
+            ),
+            Message::BreakpointSet(location) => message!(
+                lang,
+                "Breakpoint at instruction {location}",
+                "Punkt przerwania na instrukcji {location}"
+            ),
+            Message::BreakpointRemoved(location) => message!(
+                lang,
+                "Removed breakpoint at instruction {location}",
+                "Usunięto punkt przerwania na instrukcji {location}"
+            ),
+            Message::Watching(cell) => message!(
+                lang,
+                "Watching cell {cell}",
+                "Obserwowanie komórki {cell}"
+            ),
+            Message::WatchpointOn(cell) => message!(
+                lang,
+                "Watchpoint on cell {cell}",
+                "Punkt obserwacji komórki {cell}"
+            ),
+            Message::Unwatched(cell) => message!(
+                lang,
+                "Stopped watching {cell}",
+                "Zakończono obserwowanie komórki {cell}"
+            ),
+            Message::NoWatchpoint(cell) => message!(
+                lang,
+                "No watchpoint on `{cell}`",
+                "Brak punktu obserwacji komórki `{cell}`"
+            ),
+            Message::Output(output) => message!(lang, "Output: {output}", "Wyjście: {output}"),
+            Message::ReplHalted => message!(
+                lang,
+                "Machine halted, use :reset or :undo to continue",
+                "Maszyna się zatrzymała, użyj :reset lub :undo, aby kontynuować"
+            ),
+            Message::NothingToUndo => message!(lang, "Nothing to undo", "Nie ma czego cofnąć"),
+            Message::TuiStart => message!(
+                lang,
+                "Press s to step or r to run",
+                "Naciśnij s, aby wykonać krok, lub r, aby uruchomić"
+            ),
+            Message::KeyStep => message!(lang, "step", "krok"),
+            Message::KeyStepBack => message!(lang, "step back", "krok wstecz"),
+            Message::KeyRun => message!(lang, "run", "uruchom"),
+            Message::KeyReverseRun => message!(lang, "reverse run", "cofaj"),
+            Message::KeyFinish => message!(lang, "finish", "dokończ"),
+            Message::KeyBreakpoint => message!(lang, "breakpoint", "punkt przerwania"),
+            Message::KeyStopRun => message!(lang, "stop run", "zatrzymaj"),
+            Message::KeySelectLine => message!(lang, "select line", "wybierz linię"),
+            Message::KeyQuit => message!(lang, "quit", "wyjdź"),
+            Message::SourceTitle => message!(lang, "Source", "Kod"),
+            Message::TapeTitle => message!(lang, "Tape", "Taśma"),
+            Message::InputTitle => message!(lang, "Input", "Wejście"),
+            Message::OutputTitle => message!(lang, "Output", "Wyjście"),
+            Message::ProgramRequired => message!(
+                lang,
+                "`program` is required",
+                "argument `program` jest wymagany"
+            ),
+            Message::UnsupportedRequest(command) => message!(
+                lang,
+                "Unsupported request `{command}`",
+                "Nieobsługiwane żądanie `{command}`"
+            ),
+        }
+    }
+}
+
+/// Description of the instruction with the English keyword, `None` for any other word.
+pub fn instruction_description(keyword: &str, lang: Lang) -> Option<String> {
+    let description = match keyword {
+        "load" => message!(
+            lang,
+            "Copies the operand into the buffer (cell 0).",
+            "Kopiuje operand do akumulatora (komórki 0)."
+        ),
+        "store" => message!(
+            lang,
+            "Copies the buffer (cell 0) into the cell.",
+            "Kopiuje akumulator (komórkę 0) do komórki."
+        ),
+        "add" => message!(
+            lang,
+            "Adds the operand to the buffer.",
+            "Dodaje operand do akumulatora."
+        ),
+        "sub" => message!(
+            lang,
+            "Subtracts the operand from the buffer.",
+            "Odejmuje operand od akumulatora."
+        ),
+        "mult" => message!(
+            lang,
+            "Multiplies the buffer by the operand.",
+            "Mnoży akumulator przez operand."
+        ),
+        "div" => message!(
+            lang,
+            "Divides the buffer by the operand.",
+            "Dzieli akumulator przez operand."
+        ),
+        "read" => message!(
+            lang,
+            "Reads the next input value into the cell.",
+            "Wczytuje kolejną wartość wejściową do komórki."
+        ),
+        "write" => message!(
+            lang,
+            "Appends the operand to the output.",
+            "Dopisuje operand do wyjścia."
+        ),
+        "jump" => message!(lang, "Continues at the label.", "Przechodzi do etykiety."),
+        "jgtz" => message!(
+            lang,
+            "Continues at the label if the buffer is greater than zero.",
+            "Przechodzi do etykiety, jeśli akumulator jest większy od zera."
+        ),
+        "jzero" => message!(
+            lang,
+            "Continues at the label if the buffer is zero.",
+            "Przechodzi do etykiety, jeśli akumulator jest równy zero."
+        ),
+        "halt" => message!(lang, "Stops the machine.", "Zatrzymuje maszynę."),
+        "mod" => message!(
+            lang,
+            "Replaces the buffer with the remainder of dividing it by the operand.",
+            "Zastępuje akumulator resztą z dzielenia go przez operand."
+        ),
+        "jneg" => message!(
+            lang,
+            "Continues at the label if the buffer is negative.",
+            "Przechodzi do etykiety, jeśli akumulator jest ujemny."
+        ),
+        "jgez" => message!(
+            lang,
+            "Continues at the label if the buffer is not negative.",
+            "Przechodzi do etykiety, jeśli akumulator jest nieujemny."
+        ),
+        "jnzero" => message!(
+            lang,
+            "Continues at the label if the buffer is not zero.",
+            "Przechodzi do etykiety, jeśli akumulator jest różny od zera."
+        ),
+        "inc" => message!(
+            lang,
+            "Adds one to the buffer.",
+            "Dodaje jeden do akumulatora."
+        ),
+        "dec" => message!(
+            lang,
+            "Subtracts one from the buffer.",
+            "Odejmuje jeden od akumulatora."
+        ),
+        _ => return None,
+    };
+    Some(description)
+}
+
+impl Localize for LangParseError {
+    fn localize(&self, lang: Lang) -> String {
+        let lang_name = &self.0;
+        message!(
+            lang,
+            "`{lang_name}` is not a supported language, expected `en` or `pl`",
+            "`{lang_name}` nie jest obsługiwanym językiem, oczekiwano `en` lub `pl`"
+        )
+    }
+}
+
+impl Localize for DialectParseError {
+    fn localize(&self, lang: Lang) -> String {
+        let dialect = &self.0;
+        message!(
+            lang,
+            "`{dialect}` is not a dialect, expected `strict` or `extended`",
+            "`{dialect}` nie jest dialektem, oczekiwano `strict` lub `extended`"
+        )
+    }
+}
+
+impl Localize for CaseParseError {
+    fn localize(&self, lang: Lang) -> String {
+        let case = &self.0;
+        message!(
+            lang,
+            "`{case}` is not a case, expected `lower` or `upper`",
+            "`{case}` nie jest wielkością liter, oczekiwano `lower` lub `upper`"
+        )
+    }
+}
+
+impl Localize for ArithmeticModeError {
+    fn localize(&self, lang: Lang) -> String {
+        let mode = &self.0;
+        message!(
+            lang,
+            "`{mode}` is not an arithmetic mode, expected checked, wrapping, saturating, truncating, floor or euclidean",
+            "`{mode}` nie jest trybem arytmetyki, oczekiwano checked, wrapping, saturating, truncating, floor lub euclidean"
+        )
+    }
+}
+
+impl Localize for InvalidOperandError {
+    fn localize(&self, lang: Lang) -> String {
+        let operand = &self.0;
+        message!(
+            lang,
+            "Operand {operand} is not a valid operand",
+            "Operand {operand} nie jest poprawnym operandem"
+        )
+    }
+}
+
+impl Localize for OperandParseError {
+    fn localize(&self, lang: Lang) -> String {
+        match self {
+            OperandParseError::InvalidOperand(operand, keyword) => message!(
+                lang,
+                "Operand {operand} is not a valid operand for keyword `{keyword}`",
+                "Operand {operand} nie jest poprawnym operandem dla słowa kluczowego `{keyword}`"
+            ),
+            OperandParseError::OperandNotFound(keyword) => message!(
+                lang,
+                "Expected operand for keyword `{keyword}`, found nothing",
+                "Oczekiwano operandu dla słowa kluczowego `{keyword}`, nie znaleziono niczego"
+            ),
+        }
+    }
+}
+
+impl Localize for InstructionParseError {
+    fn localize(&self, lang: Lang) -> String {
+        use InstructionParseError::*;
+        match self {
+            LabelNotFound(keyword) => message!(
+                lang,
+                "Expected a label after keyword {keyword}, got nothing",
+                "Oczekiwano etykiety po słowie kluczowym {keyword}, nie znaleziono niczego"
+            ),
+            InvalidKeyword(keyword) => message!(
+                lang,
+                "Keyword `{keyword}` is not a valid keyword",
+                "Słowo kluczowe `{keyword}` nie jest poprawnym słowem kluczowym"
+            ),
+            OperandParseError(e) => e.localize(lang),
+            UnexpectedArgument(argument) => message!(
+                lang,
+                "Expected nothing, found `{argument}`",
+                "Oczekiwano niczego, znaleziono `{argument}`"
+            ),
+            UnterminatedString(s) => message!(
+                lang,
+                "String {s} is not terminated, expected a closing `\"`",
+                "Napis {s} nie jest zakończony, oczekiwano zamykającego `\"`"
+            ),
+        }
+    }
+}
+
+impl Localize for ValidationError {
+    fn localize(&self, lang: Lang) -> String {
+        match self {
+            ValidationError::DuplicateLabel(label) => message!(
+                lang,
+                "Label `{label}` is defined more than once",
+                "Etykieta `{label}` jest zdefiniowana więcej niż raz"
+            ),
+            ValidationError::UndefinedLabel(label, index) => message!(
+                lang,
+                "Label `{label}` is used by instruction `{index}`, but it is never defined",
+                "Etykieta `{label}` jest używana przez instrukcję `{index}`, ale nigdy nie została zdefiniowana"
+            ),
+        }
+    }
+}
+
+impl Localize for CodeParseError {
+    fn localize(&self, lang: Lang) -> String {
+        match self {
+            CodeParseError::UnexpectedArgument(argument) => message!(
+                lang,
+                "Expected EOL, found `{argument}`",
+                "Oczekiwano końca linii, znaleziono `{argument}`"
+            ),
+            CodeParseError::InstructionParseError(e) => e.localize(lang),
+            CodeParseError::ValidationErrors(errors) => errors
+                .iter()
+                .map(|e| e.localize(lang))
+                .collect::<Vec<_>>()
+                .join(", "),
+            CodeParseError::DialectParseError(e) => e.localize(lang),
+            CodeParseError::LangParseError(e) => e.localize(lang),
+        }
+    }
+}
+
+impl Localize for ParserErrorChain {
+    fn localize(&self, lang: Lang) -> String {
+        self.0
+            .iter()
+            .map(|(line, err)| {
+                let err = err.localize(lang);
+                message!(
+                    lang,
+                    "At line {line} found error: {err}\n",
+                    "W linii {line} znaleziono błąd: {err}\n"
+                )
+            })
+            .collect()
+    }
+}
+
+impl Localize for ExpandError {
+    fn localize(&self, lang: Lang) -> String {
+        match self {
+            ExpandError::ConvertError(value, cell) => message!(
+                lang,
+                "Value `{value}` in cell `{cell}` could not be converted to a tape index.",
+                "Wartości `{value}` z komórki `{cell}` nie można zamienić na adres na taśmie."
+            ),
+            ExpandError::ValueNotSet(cell) => message!(
+                lang,
+                "Tried reading from cell with address `{cell}`, which was never set.",
+                "Próba odczytu z komórki o adresie `{cell}`, której wartość nigdy nie została ustawiona."
+            ),
+        }
+    }
+}
+
+impl Localize for BufferError {
+    fn localize(&self, lang: Lang) -> String {
+        message!(
+            lang,
+            "Buffer could not be accessed, because its value was never set.",
+            "Nie można odczytać akumulatora, ponieważ jego wartość nigdy nie została ustawiona."
+        )
+    }
+}
+
+impl Localize for InputAccessError {
+    fn localize(&self, lang: Lang) -> String {
+        match self {
+            InputAccessError::NotExistentInput(index) => message!(
+                lang,
+                "Input at index `{index}` not found.",
+                "Brak wartości wejściowej o indeksie `{index}`."
+            ),
+        }
+    }
+}
+
+impl Localize for JumpError {
+    fn localize(&self, lang: Lang) -> String {
+        match self {
+            JumpError::LabelNotFound(label) => message!(
+                lang,
+                "Label `{label}` could not be found in Ram code.",
+                "Nie znaleziono etykiety `{label}` w kodzie programu."
+            ),
+        }
+    }
+}
+
+impl Localize for StreamError {
+    fn localize(&self, lang: Lang) -> String {
+        match self {
+            StreamError::ReadFailed(e) => message!(
+                lang,
+                "Could not read input: '{e}'",
+                "Nie można odczytać wejścia: '{e}'"
+            ),
+            StreamError::InvalidInput(input) => message!(
+                lang,
+                "Failed to convert input `{input}` to an integer",
+                "Nie można zamienić wejścia `{input}` na liczbę całkowitą"
+            ),
+            StreamError::WriteFailed(e) => message!(
+                lang,
+                "Could not write output: '{e}'",
+                "Nie można zapisać wyjścia: '{e}'"
+            ),
+        }
+    }
+}
+
+impl Localize for RamMachineError {
+    fn localize(&self, lang: Lang) -> String {
+        use RamMachineError::*;
+        match self {
+            ExpandError(e) => e.localize(lang),
+            BufferError(e) => e.localize(lang),
+            InputAccessError(e) => e.localize(lang),
+            JumpError(e) => e.localize(lang),
+            StreamError(e) => e.localize(lang),
+            AdditionFailed(a, b) => message!(
+                lang,
+                "Addition of `{a}` to `{b}` failed.",
+                "Dodawanie `{a}` do `{b}` nie powiodło się."
+            ),
+            SubtractionFailed(a, b) => message!(
+                lang,
+                "Subtraction of `{a}` from `{b}` failed.",
+                "Odejmowanie `{a}` od `{b}` nie powiodło się."
+            ),
+            MultiplicationFailed(a, b) => message!(
+                lang,
+                "Multiplication by `{a}` of `{b}` failed.",
+                "Mnożenie `{b}` przez `{a}` nie powiodło się."
+            ),
+            DivisionFailed(a, b) => message!(
+                lang,
+                "Division by `{a}` of `{b}` failed.",
+                "Dzielenie `{b}` przez `{a}` nie powiodło się."
+            ),
+            ModuloFailed(a, b) => message!(
+                lang,
+                "Remainder of division by `{a}` of `{b}` failed.",
+                "Obliczenie reszty z dzielenia `{b}` przez `{a}` nie powiodło się."
+            ),
+            StepLimitExceeded(steps) => message!(
+                lang,
+                "Step limit of `{steps}` executed instructions reached.",
+                "Osiągnięto limit `{steps}` wykonanych instrukcji."
+            ),
+            TimeLimitExceeded(limit, steps) => message!(
+                lang,
+                "Time limit of `{limit:?}` reached after `{steps}` executed instructions.",
+                "Osiągnięto limit czasu `{limit:?}` po wykonaniu `{steps}` instrukcji."
+            ),
+        }
+    }
+}
+
+impl Localize for ExecutionError {
+    fn localize(&self, lang: Lang) -> String {
+        let error = self.error.localize(lang);
+        match &self.span {
+            Some(span) => format!("{}: {} — {error}", span.line, span.text),
+            None => {
+                let (pointer, instruction) = (self.pointer, &self.instruction);
+                message!(
+                    lang,
+                    "instruction {pointer}: {instruction} — {error}",
+                    "instrukcja {pointer}: {instruction} — {error}"
+                )
+            }
+        }
+    }
+}
+
+impl Localize for DebuggerError {
+    fn localize(&self, lang: Lang) -> String {
+        match self {
+            DebuggerError::LocationNotFound(location) => message!(
+                lang,
+                "`{location}` is neither a label nor a line with an instruction",
+                "`{location}` nie jest ani etykietą, ani linią z instrukcją"
+            ),
+            DebuggerError::InvalidCondition(condition) => message!(
+                lang,
+                "Condition `{condition}` is not valid, expected e.g. `[2] == 0`",
+                "Warunek `{condition}` jest niepoprawny, oczekiwano np. `[2] == 0`"
+            ),
+        }
+    }
+}
+
+impl Localize for RenameError {
+    fn localize(&self, lang: Lang) -> String {
+        match self {
+            RenameError::NoLabel => message!(
+                lang,
+                "There is no label at this position",
+                "W tym miejscu nie ma etykiety"
+            ),
+            RenameError::InvalidName(name) => message!(
+                lang,
+                "`{name}` is not a valid label name",
+                "`{name}` nie jest poprawną nazwą etykiety"
+            ),
+            RenameError::AlreadyDefined(name) => message!(
+                lang,
+                "Label `{name}` is already defined",
+                "Etykieta `{name}` jest już zdefiniowana"
+            ),
+        }
+    }
+}
+
+impl Localize for RuleParseError {
+    fn localize(&self, lang: Lang) -> String {
+        let rule = &self.0;
+        let rules = Rule::ALL.map(|rule| rule.name()).join(", ");
+        message!(
+            lang,
+            "`{rule}` is not a lint rule, expected one of {rules}",
+            "`{rule}` nie jest regułą lintera, oczekiwano jednej z {rules}"
+        )
+    }
+}
+
+impl Localize for LevelParseError {
+    fn localize(&self, lang: Lang) -> String {
+        let level = &self.0;
+        message!(
+            lang,
+            "`{level}` is not a lint level, expected `allow`, `warn` or `deny`",
+            "`{level}` nie jest poziomem lintera, oczekiwano `allow`, `warn` lub `deny`"
+        )
+    }
+}
+
+impl Localize for PassParseError {
+    fn localize(&self, lang: Lang) -> String {
+        let pass = &self.0;
+        let passes = Pass::ALL.map(|pass| pass.name()).join(", ");
+        message!(
+            lang,
+            "`{pass}` is not an optimisation pass, expected one of {passes}",
+            "`{pass}` nie jest przebiegiem optymalizatora, oczekiwano jednego z {passes}"
+        )
+    }
+}
+
+impl Localize for RunStats {
+    fn localize(&self, lang: Lang) -> String {
+        let RunStats {
+            uniform_time,
+            logarithmic_time,
+            uniform_space,
+            logarithmic_space,
+        } = self;
+        message!(
+            lang,
+            "Uniform time cost: {uniform_time}
+Logarithmic time cost: {logarithmic_time}
+Uniform space cost: {uniform_space}
+Logarithmic space cost: {logarithmic_space}",
+            "Jednorodny koszt czasowy: {uniform_time}
+Logarytmiczny koszt czasowy: {logarithmic_time}
+Jednorodny koszt pamięciowy: {uniform_space}
+Logarytmiczny koszt pamięciowy: {logarithmic_space}"
+        )
+    }
+}
+
+impl Localize for Severity {
+    fn localize(&self, lang: Lang) -> String {
+        match self {
+            Severity::Error => message!(lang, "error", "błąd"),
+            Severity::Warning => message!(lang, "warning", "ostrzeżenie"),
+        }
+    }
+}
@@ -0,0 +1,366 @@
+use std::collections::HashMap;
+
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+        Notification as LspNotification, PublishDiagnostics,
+    },
+    request::{
+        Completion, GotoDefinition, HoverRequest, References, Rename, Request as LspRequest,
+    },
+    CompletionItem, CompletionItemKind, CompletionParams, CompletionResponse,
+    Diagnostic as LspDiagnostic, DiagnosticSeverity, DidChangeTextDocumentParams,
+    DidCloseTextDocumentParams, DidOpenTextDocumentParams, GotoDefinitionParams,
+    GotoDefinitionResponse, Hover, HoverContents, HoverParams, HoverProviderCapability, Location,
+    MarkupContent, MarkupKind, NumberOrString, OneOf, Position, PublishDiagnosticsParams, Range,
+    ReferenceParams, RenameParams, ServerCapabilities, TextDocumentSyncCapability,
+    TextDocumentSyncKind, TextEdit, Url, WorkspaceEdit,
+};
+use serde_json::Value;
+
+use ram_machine::analysis::{Analysis, CompletionKind};
+use ram_machine::diagnostic::{Diagnostic, Severity};
+use ram_machine::instruction::Dialect;
+use ram_machine::locale::{self, Lang, Localize};
+use ram_machine::parser::{source_map::Span, ParseOptions};
+
+use crate::cli::RuntimeError;
+
+struct Server {
+    connection: Connection,
+    documents: HashMap<Url, String>,
+    options: ParseOptions,
+    lang: Lang,
+}
+
+/// Serves the language server protocol over STDIN and STDOUT until the client shuts it down.
+pub fn lsp(dialect: Dialect, lang: Lang, keywords: Lang) -> Result<(), RuntimeError> {
+    let (connection, io_threads) = Connection::stdio();
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        definition_provider: Some(OneOf::Left(true)),
+        references_provider: Some(OneOf::Left(true)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        completion_provider: Some(Default::default()),
+        rename_provider: Some(OneOf::Left(true)),
+        ..Default::default()
+    };
+    let capabilities = serde_json::to_value(capabilities)
+        .map_err(|e| RuntimeError::LanguageServerError(e.to_string()))?;
+    connection
+        .initialize(capabilities)
+        .map_err(|e| RuntimeError::LanguageServerError(e.to_string()))?;
+
+    let mut server = Server {
+        connection,
+        documents: HashMap::new(),
+        options: ParseOptions {
+            lenient: false,
+            dialect,
+            keywords,
+        },
+        lang,
+    };
+    server.main_loop()?;
+    // Closing the connection lets the IO threads finish
+    drop(server);
+    io_threads
+        .join()
+        .map_err(|e| RuntimeError::LanguageServerError(e.to_string()))
+}
+
+impl Server {
+    fn main_loop(&mut self) -> Result<(), RuntimeError> {
+        while let Ok(message) = self.connection.receiver.recv() {
+            match message {
+                Message::Request(request) => {
+                    let shutdown = self
+                        .connection
+                        .handle_shutdown(&request)
+                        .map_err(|e| RuntimeError::LanguageServerError(e.to_string()))?;
+                    if shutdown {
+                        return Ok(());
+                    }
+                    let response = self.handle_request(request);
+                    self.send(Message::Response(response))?;
+                }
+                Message::Notification(notification) => self.handle_notification(notification)?,
+                Message::Response(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn send(&self, message: Message) -> Result<(), RuntimeError> {
+        self.connection
+            .sender
+            .send(message)
+            .map_err(|e| RuntimeError::LanguageServerError(e.to_string()))
+    }
+
+    fn handle_request(&self, request: Request) -> Response {
+        let id = request.id.clone();
+        let result = match request.method.as_str() {
+            GotoDefinition::METHOD => params(request).map(|p| self.definition(p)),
+            References::METHOD => params(request).map(|p| self.references(p)),
+            HoverRequest::METHOD => params(request).map(|p| self.hover(p)),
+            Completion::METHOD => params(request).map(|p| self.completion(p)),
+            Rename::METHOD => params(request).and_then(|p| self.rename(p)),
+            method => Err((
+                ErrorCode::MethodNotFound,
+                format!("Unsupported request `{method}`"),
+            )),
+        };
+        match result {
+            Ok(value) => Response::new_ok(id, value),
+            Err((code, message)) => Response::new_err(id, code as i32, message),
+        }
+    }
+
+    fn handle_notification(&mut self, notification: Notification) -> Result<(), RuntimeError> {
+        let uri = match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let Ok(p) = params::<DidOpenTextDocumentParams>(notification) else {
+                    return Ok(());
+                };
+                self.documents
+                    .insert(p.text_document.uri.clone(), p.text_document.text);
+                p.text_document.uri
+            }
+            DidChangeTextDocument::METHOD => {
+                let Ok(p) = params::<DidChangeTextDocumentParams>(notification) else {
+                    return Ok(());
+                };
+                // The whole document is sent on every change
+                if let Some(change) = p.content_changes.into_iter().last() {
+                    self.documents
+                        .insert(p.text_document.uri.clone(), change.text);
+                }
+                p.text_document.uri
+            }
+            DidCloseTextDocument::METHOD => {
+                let Ok(p) = params::<DidCloseTextDocumentParams>(notification) else {
+                    return Ok(());
+                };
+                self.documents.remove(&p.text_document.uri);
+                p.text_document.uri
+            }
+            _ => return Ok(()),
+        };
+        self.publish_diagnostics(uri)
+    }
+
+    fn publish_diagnostics(&self, uri: Url) -> Result<(), RuntimeError> {
+        let diagnostics = match self.documents.get(&uri) {
+            Some(source) => Analysis::new(source, self.options, self.lang)
+                .diagnostics()
+                .iter()
+                .map(|d| to_lsp_diagnostic(d, self.lang))
+                .collect(),
+            None => Vec::new(),
+        };
+        let params = PublishDiagnosticsParams {
+            uri,
+            diagnostics,
+            version: None,
+        };
+        self.send(Message::Notification(Notification::new(
+            PublishDiagnostics::METHOD.to_owned(),
+            params,
+        )))
+    }
+
+    /// The source of the document and the one-based line and byte column of the position.
+    fn locate(&self, uri: &Url, position: Position) -> Option<(&str, usize, usize)> {
+        let source = self.documents.get(uri)?;
+        let line = source
+            .lines()
+            .nth(position.line as usize)
+            .unwrap_or_default();
+        Some((
+            source,
+            position.line as usize + 1,
+            to_column(line, position.character),
+        ))
+    }
+
+    fn definition(&self, p: GotoDefinitionParams) -> Value {
+        let uri = p.text_document_position_params.text_document.uri;
+        let position = p.text_document_position_params.position;
+        let Some((source, line, column)) = self.locate(&uri, position) else {
+            return Value::Null;
+        };
+        let analysis = Analysis::new(source, self.options, self.lang);
+        match analysis.definition(line, column) {
+            Some(span) => to_value(GotoDefinitionResponse::Scalar(Location {
+                range: to_range(source, &span),
+                uri,
+            })),
+            None => Value::Null,
+        }
+    }
+
+    fn references(&self, p: ReferenceParams) -> Value {
+        let uri = p.text_document_position.text_document.uri;
+        let position = p.text_document_position.position;
+        let Some((source, line, column)) = self.locate(&uri, position) else {
+            return Value::Null;
+        };
+        let analysis = Analysis::new(source, self.options, self.lang);
+        let locations: Vec<Location> = analysis
+            .references(line, column, p.context.include_declaration)
+            .iter()
+            .map(|span| Location {
+                uri: uri.clone(),
+                range: to_range(source, span),
+            })
+            .collect();
+        to_value(locations)
+    }
+
+    fn hover(&self, p: HoverParams) -> Value {
+        let uri = p.text_document_position_params.text_document.uri;
+        let position = p.text_document_position_params.position;
+        let Some((source, line, column)) = self.locate(&uri, position) else {
+            return Value::Null;
+        };
+        let analysis = Analysis::new(source, self.options, self.lang);
+        match analysis.hover(line, column, self.lang) {
+            Some(value) => to_value(Hover {
+                contents: HoverContents::Markup(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value,
+                }),
+                range: None,
+            }),
+            None => Value::Null,
+        }
+    }
+
+    fn completion(&self, p: CompletionParams) -> Value {
+        let uri = p.text_document_position.text_document.uri;
+        let position = p.text_document_position.position;
+        let Some((source, line, column)) = self.locate(&uri, position) else {
+            return Value::Null;
+        };
+        let analysis = Analysis::new(source, self.options, self.lang);
+        let items: Vec<CompletionItem> = analysis
+            .completions(line, column, self.lang)
+            .into_iter()
+            .map(|completion| CompletionItem {
+                label: completion.label,
+                kind: Some(match completion.kind {
+                    CompletionKind::Keyword => CompletionItemKind::KEYWORD,
+                    CompletionKind::Label => CompletionItemKind::REFERENCE,
+                }),
+                detail: Some(completion.detail),
+                ..Default::default()
+            })
+            .collect();
+        to_value(CompletionResponse::Array(items))
+    }
+
+    fn rename(&self, p: RenameParams) -> Result<Value, (ErrorCode, String)> {
+        let uri = p.text_document_position.text_document.uri;
+        let position = p.text_document_position.position;
+        let Some((source, line, column)) = self.locate(&uri, position) else {
+            return Ok(Value::Null);
+        };
+        let analysis = Analysis::new(source, self.options, self.lang);
+        let edits: Vec<TextEdit> = analysis
+            .rename(line, column, &p.new_name)
+            .map_err(|e| (ErrorCode::RequestFailed, e.localize(self.lang)))?
+            .iter()
+            .map(|span| TextEdit {
+                range: to_range(source, span),
+                new_text: p.new_name.clone(),
+            })
+            .collect();
+        Ok(to_value(WorkspaceEdit {
+            changes: Some(HashMap::from([(uri, edits)])),
+            ..Default::default()
+        }))
+    }
+}
+
+fn params<P: serde::de::DeserializeOwned>(
+    message: impl Into<Message>,
+) -> Result<P, (ErrorCode, String)> {
+    let params = match message.into() {
+        Message::Request(request) => request.params,
+        Message::Notification(notification) => notification.params,
+        Message::Response(_) => Value::Null,
+    };
+    serde_json::from_value(params).map_err(|e| (ErrorCode::InvalidParams, e.to_string()))
+}
+
+fn to_value(value: impl serde::Serialize) -> Value {
+    serde_json::to_value(value).unwrap_or_default()
+}
+
+/// Byte offset in `line` of the UTF-16 code unit offset `character`.
+fn to_column(line: &str, character: u32) -> usize {
+    let mut units = 0;
+    for (index, c) in line.char_indices() {
+        if units >= character as usize {
+            return index;
+        }
+        units += c.len_utf16();
+    }
+    line.len()
+}
+
+/// UTF-16 code unit offset of the byte offset `column` in `line`.
+fn to_character(line: &str, column: usize) -> u32 {
+    line[..column.min(line.len())]
+        .chars()
+        .map(char::len_utf16)
+        .sum::<usize>() as u32
+}
+
+fn to_range(source: &str, span: &Span) -> Range {
+    let line = span
+        .line
+        .checked_sub(1)
+        .and_then(|index| source.lines().nth(index))
+        .unwrap_or_default();
+    let position = |column| Position {
+        line: span.line.saturating_sub(1) as u32,
+        character: to_character(line, column),
+    };
+    Range {
+        start: position(span.columns.start),
+        end: position(span.columns.end),
+    }
+}
+
+fn to_lsp_diagnostic(diagnostic: &Diagnostic, lang: Lang) -> LspDiagnostic {
+    let line = diagnostic.span.line.saturating_sub(1) as u32;
+    let position = |column| Position {
+        line,
+        character: to_character(&diagnostic.source_line, column),
+    };
+    let message = match &diagnostic.help {
+        Some(help) => format!(
+            "{}\n{}: {help}",
+            diagnostic.message,
+            locale::Message::Help.localize(lang)
+        ),
+        None => diagnostic.message.clone(),
+    };
+    LspDiagnostic {
+        range: Range {
+            start: position(diagnostic.span.columns.start),
+            end: position(diagnostic.span.columns.end),
+        },
+        severity: Some(match diagnostic.severity {
+            Severity::Error => DiagnosticSeverity::ERROR,
+            Severity::Warning => DiagnosticSeverity::WARNING,
+        }),
+        code: Some(NumberOrString::String(diagnostic.code.to_owned())),
+        source: Some("ram".to_owned()),
+        message,
+        ..Default::default()
+    }
+}
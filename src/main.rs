@@ -1,9 +1,18 @@
+// Errors carrying big integers are large, but a failed run is not on a hot path
+#![cfg_attr(feature = "bigint", allow(clippy::result_large_err))]
+
 use std::process::exit;
 
 use cli::app;
 
 mod cli;
+mod dap;
+mod debug;
+mod lsp;
+mod repl;
+mod tui;
 
+#[allow(clippy::single_match)]
 fn main() {
     match app() {
         Err(e) => {
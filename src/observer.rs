@@ -0,0 +1,111 @@
+use std::{
+    fmt,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use crate::history::StepDelta;
+use crate::instruction::Instruction;
+use crate::interpreter::RamMachine;
+use crate::operand::{CellAddress, CellValue};
+
+/// Callbacks invoked by `RamMachine::run_line`, attached with `RamMachine::add_observer`.
+///
+/// `before_instruction` is called first, the remaining callbacks after the instruction succeeded.
+#[allow(unused_variables)]
+pub trait ExecutionObserver {
+    fn before_instruction(&mut self, machine: &RamMachine, instruction: &Instruction) {}
+
+    fn on_tape_write(&mut self, address: CellAddress, old: Option<&CellValue>, new: &CellValue) {}
+
+    /// An input value was consumed, `index` is its position in the input.
+    fn on_read(&mut self, index: usize, value: &CellValue) {}
+
+    /// A value was appended to the output.
+    fn on_write(&mut self, value: &CellValue) {}
+
+    /// A string was written with `WRITE "text"` of the extended dialect.
+    fn on_write_text(&mut self, text: &str) {}
+
+    fn on_jump(&mut self, from: usize, to: usize) {}
+
+    fn on_halt(&mut self, machine: &RamMachine) {}
+
+    /// Called last, with everything the instruction changed. The machine records its history and
+    /// trace through this callback as well.
+    fn on_step(&mut self, machine: &RamMachine, delta: &StepDelta) {}
+}
+
+/// Lets the caller keep a handle to an observer after attaching it.
+impl<T: ExecutionObserver> ExecutionObserver for Arc<Mutex<T>> {
+    fn before_instruction(&mut self, machine: &RamMachine, instruction: &Instruction) {
+        self.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .before_instruction(machine, instruction)
+    }
+
+    fn on_tape_write(&mut self, address: CellAddress, old: Option<&CellValue>, new: &CellValue) {
+        self.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .on_tape_write(address, old, new)
+    }
+
+    fn on_read(&mut self, index: usize, value: &CellValue) {
+        self.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .on_read(index, value)
+    }
+
+    fn on_write(&mut self, value: &CellValue) {
+        self.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .on_write(value)
+    }
+
+    fn on_write_text(&mut self, text: &str) {
+        self.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .on_write_text(text)
+    }
+
+    fn on_jump(&mut self, from: usize, to: usize) {
+        self.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .on_jump(from, to)
+    }
+
+    fn on_halt(&mut self, machine: &RamMachine) {
+        self.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .on_halt(machine)
+    }
+
+    fn on_step(&mut self, machine: &RamMachine, delta: &StepDelta) {
+        self.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .on_step(machine, delta)
+    }
+}
+
+/// Observers attached to a machine. They are not cloned with it and don't take part in comparisons.
+#[derive(Default)]
+pub(crate) struct Observers(pub(crate) Vec<Box<dyn ExecutionObserver + Send>>);
+
+impl Clone for Observers {
+    fn clone(&self) -> Self {
+        Observers::default()
+    }
+}
+
+impl PartialEq for Observers {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for Observers {}
+
+impl fmt::Debug for Observers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Observers({})", self.0.len())
+    }
+}
@@ -0,0 +1,343 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt,
+    str::FromStr,
+};
+
+use thiserror::Error;
+
+use crate::{
+    arithmetic::{ArithmeticMode, Division, Overflow},
+    cfg::ControlFlowGraph,
+    instruction::Instruction,
+    locale::{Lang, Localize},
+    operand::{CellOperand, CellValue, Operand},
+    parser::RamCode,
+};
+
+/// A rewrite of the program which doesn't change what it reads and writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Pass {
+    /// `LOAD =a` followed by `ADD =b` becomes `LOAD =c`
+    ConstantFolding,
+    /// `LOAD x` right after `STORE x` is removed
+    RedundantLoads,
+    /// Jumps to a `JUMP` go straight to its target, a `JUMP` to the next instruction is removed
+    JumpThreading,
+    /// Blocks which can't be reached from the start are removed
+    UnreachableCode,
+    /// Labels which are never jumped to are removed, labels of the same instruction are merged
+    Labels,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{}", self.localize(Lang::En))]
+pub struct PassParseError(pub String);
+
+/// Every combination of arithmetic semantics, a folded value has to be the same in all of them.
+const MODES: [ArithmeticMode; 9] = {
+    const fn mode(overflow: Overflow, division: Division) -> ArithmeticMode {
+        ArithmeticMode { overflow, division }
+    }
+    [
+        mode(Overflow::Checked, Division::Truncating),
+        mode(Overflow::Checked, Division::Floor),
+        mode(Overflow::Checked, Division::Euclidean),
+        mode(Overflow::Wrapping, Division::Truncating),
+        mode(Overflow::Wrapping, Division::Floor),
+        mode(Overflow::Wrapping, Division::Euclidean),
+        mode(Overflow::Saturating, Division::Truncating),
+        mode(Overflow::Saturating, Division::Floor),
+        mode(Overflow::Saturating, Division::Euclidean),
+    ]
+};
+
+/// Passes are repeated until none of them changes the program, but at most this many times.
+const MAX_ROUNDS: usize = 16;
+
+impl Pass {
+    pub const ALL: [Pass; 5] = [
+        Pass::ConstantFolding,
+        Pass::RedundantLoads,
+        Pass::JumpThreading,
+        Pass::UnreachableCode,
+        Pass::Labels,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Pass::ConstantFolding => "constant-folding",
+            Pass::RedundantLoads => "redundant-loads",
+            Pass::JumpThreading => "jump-threading",
+            Pass::UnreachableCode => "unreachable-code",
+            Pass::Labels => "labels",
+        }
+    }
+
+    /// Applies the pass once, returns whether the program changed.
+    pub fn run(&self, code: &mut RamCode) -> bool {
+        match self {
+            Pass::ConstantFolding => fold_constants(code),
+            Pass::RedundantLoads => remove_redundant_loads(code),
+            Pass::JumpThreading => thread_jumps(code),
+            Pass::UnreachableCode => remove_unreachable_code(code),
+            Pass::Labels => clean_labels(code),
+        }
+    }
+}
+
+impl FromStr for Pass {
+    type Err = PassParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Pass::ALL
+            .into_iter()
+            .find(|pass| pass.name() == s)
+            .ok_or_else(|| PassParseError(s.to_owned()))
+    }
+}
+
+impl fmt::Display for Pass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Runs `passes` until the program stops changing.
+///
+/// The optimised program writes the same output and fails with the same error as `code`, for any
+/// input and arithmetic mode, it may only take fewer steps.
+pub fn optimize(code: &RamCode, passes: &[Pass]) -> RamCode {
+    let mut code = code.clone();
+    for _ in 0..MAX_ROUNDS {
+        let mut changed = false;
+        for pass in passes {
+            changed |= pass.run(&mut code);
+        }
+        if !changed {
+            break;
+        }
+    }
+    code
+}
+
+/// Result of applying `instruction` to a buffer holding `value`, if it is known at compile time.
+fn fold(value: &CellValue, instruction: &Instruction) -> Option<CellValue> {
+    use Instruction::*;
+    let (operation, operand): (fn(&ArithmeticMode, &CellValue, &CellValue) -> _, CellValue) =
+        match instruction {
+            Add(Operand::Number(operand)) => (ArithmeticMode::add, operand.clone()),
+            Sub(Operand::Number(operand)) => (ArithmeticMode::sub, operand.clone()),
+            Mult(Operand::Number(operand)) => (ArithmeticMode::mul, operand.clone()),
+            Div(Operand::Number(operand)) => (ArithmeticMode::div, operand.clone()),
+            Mod(Operand::Number(operand)) => (ArithmeticMode::rem, operand.clone()),
+            Inc => (ArithmeticMode::add, CellValue::from(1)),
+            Dec => (ArithmeticMode::sub, CellValue::from(1)),
+            _ => return None,
+        };
+    let mut results = MODES.iter().map(|mode| operation(mode, value, &operand));
+    let first = results.next()??;
+    results
+        .all(|result| result.as_ref() == Some(&first))
+        .then_some(first)
+}
+
+fn fold_constants(code: &mut RamCode) -> bool {
+    let targets = jump_targets(code);
+    let mut removed = vec![false; code.instructions.len()];
+    let mut index = 0;
+    while index + 1 < code.instructions.len() {
+        let Instruction::Load(Operand::Number(value)) = &code.instructions[index] else {
+            index += 1;
+            continue;
+        };
+        let next = index + 1;
+        match fold(value, &code.instructions[next]) {
+            Some(result) if !targets.contains(&next) => {
+                code.instructions[index] = Instruction::Load(Operand::Number(result));
+                // The folded instruction takes the place of the next one, so chains fold too
+                code.instructions.swap(index, next);
+                if next < code.source_map.instructions.len() {
+                    code.source_map.instructions.swap(index, next);
+                }
+                removed[index] = true;
+            }
+            _ => {}
+        }
+        index += 1;
+    }
+    remove(code, &removed)
+}
+
+fn remove_redundant_loads(code: &mut RamCode) -> bool {
+    let targets = jump_targets(code);
+    let removed: Vec<bool> = code
+        .instructions
+        .iter()
+        .enumerate()
+        .map(|(index, instruction)| {
+            let Instruction::Load(Operand::ValueInCell(cell)) = instruction else {
+                return false;
+            };
+            // `STORE ^x` may overwrite `x` itself, so only direct stores are safe
+            index > 0
+                && !targets.contains(&index)
+                && code.instructions[index - 1]
+                    == Instruction::Store(CellOperand::AddressOfCell(*cell))
+        })
+        .collect();
+    remove(code, &removed)
+}
+
+fn thread_jumps(code: &mut RamCode) -> bool {
+    let mut changed = false;
+    for index in 0..code.instructions.len() {
+        let Some(label) = code.instructions[index].label() else {
+            continue;
+        };
+        let mut label = label.to_owned();
+        let mut visited = HashSet::from([label.clone()]);
+        while let Some(Instruction::Jump(next)) = code
+            .jump_table
+            .get(&label)
+            .and_then(|target| code.instructions.get(*target))
+        {
+            if !visited.insert(next.clone()) || !code.jump_table.contains_key(next) {
+                break;
+            }
+            label = next.clone();
+        }
+        if code.instructions[index].label() != Some(label.as_str()) {
+            code.instructions[index] = with_label(&code.instructions[index], label);
+            changed = true;
+        }
+    }
+
+    let removed: Vec<bool> = code
+        .instructions
+        .iter()
+        .enumerate()
+        .map(|(index, instruction)| match instruction {
+            Instruction::Jump(label) => code.jump_table.get(label) == Some(&(index + 1)),
+            _ => false,
+        })
+        .collect();
+    remove(code, &removed) || changed
+}
+
+fn remove_unreachable_code(code: &mut RamCode) -> bool {
+    let graph = ControlFlowGraph::new(code);
+    let mut reachable = vec![false; graph.blocks.len()];
+    let mut queue = VecDeque::new();
+    if !graph.blocks.is_empty() {
+        reachable[0] = true;
+        queue.push_back(0);
+    }
+    while let Some(block) = queue.pop_front() {
+        for edge in graph.successors(block) {
+            if !reachable[edge.to] {
+                reachable[edge.to] = true;
+                queue.push_back(edge.to);
+            }
+        }
+    }
+
+    let mut removed = vec![false; code.instructions.len()];
+    for (block, reachable) in graph.blocks.iter().zip(reachable) {
+        if !reachable {
+            for index in block.instructions.clone() {
+                removed[index] = true;
+            }
+        }
+    }
+    remove(code, &removed)
+}
+
+fn clean_labels(code: &mut RamCode) -> bool {
+    let used: HashSet<&str> = code
+        .instructions
+        .iter()
+        .filter_map(Instruction::label)
+        .collect();
+    // The first used label of every instruction, in alphabetical order
+    let mut canonical: HashMap<usize, String> = HashMap::new();
+    let mut labels: Vec<(&String, &usize)> = code
+        .jump_table
+        .iter()
+        .filter(|(label, _)| used.contains(label.as_str()))
+        .collect();
+    labels.sort();
+    for (label, target) in labels {
+        canonical.entry(*target).or_insert_with(|| label.clone());
+    }
+    let renamed: HashMap<String, String> = code
+        .jump_table
+        .iter()
+        .filter_map(|(label, target)| {
+            let new = canonical.get(target)?;
+            (new != label).then(|| (label.clone(), new.clone()))
+        })
+        .collect();
+
+    let mut changed = false;
+    for instruction in &mut code.instructions {
+        if let Some(new) = instruction.label().and_then(|label| renamed.get(label)) {
+            *instruction = with_label(instruction, new.clone());
+            changed = true;
+        }
+    }
+    let before = code.jump_table.len();
+    code.jump_table
+        .retain(|label, target| canonical.get(target) == Some(label));
+    code.source_map
+        .labels
+        .retain(|label| code.jump_table.contains_key(&label.label));
+    changed || code.jump_table.len() != before
+}
+
+/// Indices of instructions which some label points at.
+fn jump_targets(code: &RamCode) -> HashSet<usize> {
+    code.jump_table.values().copied().collect()
+}
+
+/// The same jump to another label.
+fn with_label(instruction: &Instruction, label: String) -> Instruction {
+    use Instruction::*;
+    match instruction {
+        Jump(_) => Jump(label),
+        Jgtz(_) => Jgtz(label),
+        Jzero(_) => Jzero(label),
+        Jneg(_) => Jneg(label),
+        Jgez(_) => Jgez(label),
+        Jnzero(_) => Jnzero(label),
+        other => other.clone(),
+    }
+}
+
+/// Removes the marked instructions, labels of a removed instruction move to the next one.
+fn remove(code: &mut RamCode, removed: &[bool]) -> bool {
+    if !removed.contains(&true) {
+        return false;
+    }
+    let mut new_index = Vec::with_capacity(removed.len() + 1);
+    let mut kept = 0;
+    for removed in removed {
+        new_index.push(kept);
+        kept += usize::from(!removed);
+    }
+    new_index.push(kept);
+
+    let mut flags = removed.iter();
+    code.instructions
+        .retain(|_| !flags.next().copied().unwrap_or_default());
+    let mut flags = removed.iter();
+    code.source_map
+        .instructions
+        .retain(|_| !flags.next().copied().unwrap_or_default());
+    for target in code.jump_table.values_mut() {
+        *target = new_index.get(*target).copied().unwrap_or(kept);
+    }
+    for label in &mut code.source_map.labels {
+        label.target = new_index.get(label.target).copied().unwrap_or(kept);
+    }
+    true
+}
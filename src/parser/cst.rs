@@ -0,0 +1,202 @@
+use std::{fmt, ops::Range};
+
+use super::{instruction::string_literal_len, COMMENT_START, LABEL_END, PRAGMA_START};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Whitespace,
+    /// `\n` or `\r\n`
+    Newline,
+    Comment,
+    /// A comment starting with `#!` on a line without code, e.g. `#!dialect extended`
+    Pragma,
+    /// Label definition including the trailing `:`
+    Label,
+    Keyword,
+    /// Operand or jump target
+    Argument,
+    /// String literal of `WRITE "text"`
+    String,
+    /// Code after the argument, which the parser rejects
+    Unexpected,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+    /// Byte range in the parsed source
+    pub span: Range<usize>,
+}
+
+/// A single line of source, concatenating its tokens gives back the exact text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CstLine<'a> {
+    /// One-based line number
+    pub number: usize,
+    /// Byte offset of the start of the line in the source
+    pub start: usize,
+    pub text: &'a str,
+    pub tokens: Vec<Token<'a>>,
+}
+
+/// Lossless concrete syntax tree of a source file.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Cst<'a> {
+    pub lines: Vec<CstLine<'a>>,
+}
+
+impl TokenKind {
+    /// Whether the token has no meaning for the program.
+    pub fn is_trivia(self) -> bool {
+        matches!(
+            self,
+            TokenKind::Whitespace | TokenKind::Newline | TokenKind::Comment
+        )
+    }
+}
+
+impl<'a> CstLine<'a> {
+    /// Splits `text`, which starts at byte `start` of the source and may end with a newline.
+    pub fn parse(number: usize, start: usize, text: &'a str) -> Self {
+        let content = content(text);
+        let mut line = CstLine {
+            number,
+            start,
+            text,
+            tokens: Vec::new(),
+        };
+        let mut offset = 0;
+        while offset < content.len() {
+            let rest = &content[offset..];
+            let (kind, len) = if rest.starts_with(char::is_whitespace) {
+                (TokenKind::Whitespace, rest.len() - rest.trim_start().len())
+            } else if rest.starts_with(COMMENT_START) {
+                let kind = match line.code().next() {
+                    None if rest.starts_with(PRAGMA_START) => TokenKind::Pragma,
+                    _ => TokenKind::Comment,
+                };
+                (kind, rest.len())
+            } else {
+                line.classify(rest)
+            };
+            line.push(kind, offset..offset + len);
+            offset += len;
+        }
+        if content.len() < text.len() {
+            line.push(TokenKind::Newline, content.len()..text.len());
+        }
+        line
+    }
+
+    /// Kind and length of the code token at the start of `rest`.
+    fn classify(&self, rest: &str) -> (TokenKind, usize) {
+        let word = rest.split(char::is_whitespace).next().unwrap_or(rest);
+        let has = |kind| self.token(kind).is_some();
+        if !has(TokenKind::Label) && !has(TokenKind::Keyword) && word.ends_with(LABEL_END) {
+            (TokenKind::Label, word.len())
+        } else if !has(TokenKind::Keyword) {
+            (TokenKind::Keyword, word.len())
+        } else if self.argument().is_some() {
+            (TokenKind::Unexpected, word.len())
+        } else if word.starts_with('"') {
+            let code = rest.trim_end();
+            (
+                TokenKind::String,
+                string_literal_len(code).unwrap_or(code.len()),
+            )
+        } else {
+            (TokenKind::Argument, word.len())
+        }
+    }
+
+    fn push(&mut self, kind: TokenKind, columns: Range<usize>) {
+        self.tokens.push(Token {
+            kind,
+            text: &self.text[columns.clone()],
+            span: self.start + columns.start..self.start + columns.end,
+        });
+    }
+
+    /// The line without its newline.
+    pub fn content(&self) -> &'a str {
+        content(self.text)
+    }
+
+    /// Tokens which are neither trivia nor pragmas.
+    pub fn code(&self) -> impl Iterator<Item = &Token<'a>> {
+        self.tokens
+            .iter()
+            .filter(|t| !t.kind.is_trivia() && t.kind != TokenKind::Pragma)
+    }
+
+    pub fn token(&self, kind: TokenKind) -> Option<&Token<'a>> {
+        self.tokens.iter().find(|t| t.kind == kind)
+    }
+
+    /// Name of the label defined on this line.
+    pub fn label(&self) -> Option<&'a str> {
+        self.token(TokenKind::Label)
+            .map(|t| t.text.trim_end_matches(LABEL_END))
+    }
+
+    pub fn argument(&self) -> Option<&Token<'a>> {
+        self.tokens
+            .iter()
+            .find(|t| matches!(t.kind, TokenKind::Argument | TokenKind::String))
+    }
+
+    /// Comment or pragma, including the leading `#`.
+    pub fn comment(&self) -> Option<&Token<'a>> {
+        self.tokens
+            .iter()
+            .find(|t| matches!(t.kind, TokenKind::Comment | TokenKind::Pragma))
+    }
+
+    /// Name and value of a `#!name value` pragma.
+    pub fn pragma(&self) -> Option<(&'a str, &'a str)> {
+        let pragma = self.token(TokenKind::Pragma)?.text[PRAGMA_START.len()..].trim_end();
+        Some(
+            pragma
+                .split_once(char::is_whitespace)
+                .unwrap_or((pragma, "")),
+        )
+    }
+
+    /// Byte range of `token` within the line.
+    pub fn columns(&self, token: &Token) -> Range<usize> {
+        token.span.start - self.start..token.span.end - self.start
+    }
+}
+
+impl<'a> Cst<'a> {
+    pub fn parse(source: &'a str) -> Self {
+        let mut start = 0;
+        let lines = source
+            .split_inclusive('\n')
+            .enumerate()
+            .map(|(index, text)| {
+                let line = CstLine::parse(index + 1, start, text);
+                start += text.len();
+                line
+            })
+            .collect();
+        Cst { lines }
+    }
+
+    pub fn tokens(&self) -> impl Iterator<Item = &Token<'a>> {
+        self.lines.iter().flat_map(|line| &line.tokens)
+    }
+}
+
+/// Writes back the exact source the tree was parsed from.
+impl fmt::Display for Cst<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.tokens().try_for_each(|token| f.write_str(token.text))
+    }
+}
+
+fn content(text: &str) -> &str {
+    let text = text.strip_suffix('\n').unwrap_or(text);
+    text.strip_suffix('\r').unwrap_or(text)
+}
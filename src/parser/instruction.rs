@@ -1,5 +1,6 @@
-use std::fmt;
+use std::{fmt, str::FromStr};
 
+use crate::locale::{Lang, Localize};
 use crate::operand::{CellOperand, Operand, OperandParseError};
 use thiserror::Error;
 
@@ -17,18 +18,78 @@ pub enum Instruction {
     Jgtz(String),
     Jzero(String),
     Halt,
+    // Extended dialect
+    Mod(Operand),
+    Jneg(String),
+    Jgez(String),
+    Jnzero(String),
+    Inc,
+    Dec,
+    WriteString(String),
 }
 
+/// Set of accepted instructions, `Strict` is the minimal set from Szkup's thesis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dialect {
+    #[default]
+    Strict,
+    /// Adds `MOD`, `JNEG` (`JLTZ`), `JGEZ`, `JNZERO`, `INC`, `DEC` and `WRITE "text"`
+    Extended,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{}", self.localize(Lang::En))]
+pub struct DialectParseError(pub String);
+
 #[derive(Error, Debug, PartialEq, Eq)]
+#[error("{}", self.localize(Lang::En))]
 pub enum InstructionParseError {
-    #[error("Expected a label after keyword {0}, got nothing")]
     LabelNotFound(String),
-    #[error("Keyword `{0}` is not a valid keyword")]
     InvalidKeyword(String),
-    #[error(transparent)]
     OperandParseError(#[from] OperandParseError),
-    #[error("Expected nothing, found `{0}`")]
     UnexpectedArgument(String),
+    UnterminatedString(String),
+}
+
+pub const KEYWORDS: [&str; 12] = [
+    "load", "store", "add", "sub", "mult", "div", "read", "write", "jump", "jgtz", "jzero", "halt",
+];
+
+/// Keywords which are only valid in the extended dialect.
+pub const EXTENDED_KEYWORDS: [&str; 7] = ["mod", "jneg", "jltz", "jgez", "jnzero", "inc", "dec"];
+
+/// Polish aliases of the keywords, accepted next to the English ones with `Lang::Pl`.
+pub const POLISH_KEYWORDS: [(&str, &str); 22] = [
+    ("ładuj", "load"),
+    ("laduj", "load"),
+    ("zapamiętaj", "store"),
+    ("zapamietaj", "store"),
+    ("dodaj", "add"),
+    ("odejmij", "sub"),
+    ("mnóż", "mult"),
+    ("mnoz", "mult"),
+    ("dziel", "div"),
+    ("czytaj", "read"),
+    ("pisz", "write"),
+    ("skocz", "jump"),
+    ("skocz_dod", "jgtz"),
+    ("skocz_zero", "jzero"),
+    ("stop", "halt"),
+    ("reszta", "mod"),
+    ("skocz_ujem", "jneg"),
+    ("skocz_nieujem", "jgez"),
+    ("skocz_niezero", "jnzero"),
+    ("zwiększ", "inc"),
+    ("zwieksz", "inc"),
+    ("zmniejsz", "dec"),
+];
+
+/// English keyword of a lowercase Polish alias.
+pub fn polish_keyword(alias: &str) -> Option<&'static str> {
+    POLISH_KEYWORDS
+        .iter()
+        .find(|(polish, _)| *polish == alias)
+        .map(|(_, english)| *english)
 }
 
 fn parse_label(keyword: &str, s: Option<&str>) -> Result<String, InstructionParseError> {
@@ -38,10 +99,83 @@ fn parse_label(keyword: &str, s: Option<&str>) -> Result<String, InstructionPars
     }
 }
 
-impl TryFrom<(&str, Option<&str>)> for Instruction {
-    type Error = InstructionParseError;
-    fn try_from((keyword, argument): (&str, Option<&str>)) -> Result<Self, Self::Error> {
-        match keyword.to_lowercase().as_str() {
+fn expect_nothing(argument: Option<&str>) -> Result<(), InstructionParseError> {
+    match argument {
+        Some(v) => Err(InstructionParseError::UnexpectedArgument(v.to_owned())),
+        None => Ok(()),
+    }
+}
+
+/// Length in bytes of the string literal at the start of `s`, including the quotes.
+pub(crate) fn string_literal_len(s: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (index, c) in s.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return Some(index + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_string(s: &str) -> Result<String, InstructionParseError> {
+    let inner = match string_literal_len(s) {
+        Some(len) if len == s.len() => &s[1..len - 1],
+        _ => return Err(InstructionParseError::UnterminatedString(s.to_owned())),
+    };
+    let mut text = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        text.push(match (c, c == '\\') {
+            (_, true) => match chars.next() {
+                Some('n') => '\n',
+                Some('t') => '\t',
+                Some(c) => c,
+                None => '\\',
+            },
+            (c, false) => c,
+        });
+    }
+    Ok(text)
+}
+
+/// The string literal which `parse_string` reads back as `text`.
+fn quote_string(text: &str) -> String {
+    let mut literal = String::from('"');
+    for c in text.chars() {
+        match c {
+            '\n' => literal.push_str("\\n"),
+            '\t' => literal.push_str("\\t"),
+            '\\' | '"' => {
+                literal.push('\\');
+                literal.push(c);
+            }
+            c => literal.push(c),
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+impl Instruction {
+    pub fn parse(
+        keyword: &str,
+        argument: Option<&str>,
+        dialect: Dialect,
+        keywords: Lang,
+    ) -> Result<Self, InstructionParseError> {
+        let mut lowercase = keyword.to_lowercase();
+        if keywords == Lang::Pl {
+            if let Some(english) = polish_keyword(&lowercase) {
+                lowercase = english.to_owned();
+            }
+        }
+        if dialect == Dialect::Strict && EXTENDED_KEYWORDS.contains(&lowercase.as_str()) {
+            return Err(InstructionParseError::InvalidKeyword(keyword.to_owned()));
+        }
+        match lowercase.as_str() {
             "load" => Ok(Self::Load(Operand::try_from((argument, keyword))?)),
             "store" => Ok(Self::Store(CellOperand::try_from((argument, keyword))?)),
             "add" => Ok(Self::Add(Operand::try_from((argument, keyword))?)),
@@ -49,20 +183,62 @@ impl TryFrom<(&str, Option<&str>)> for Instruction {
             "mult" => Ok(Self::Mult(Operand::try_from((argument, keyword))?)),
             "div" => Ok(Self::Div(Operand::try_from((argument, keyword))?)),
             "read" => Ok(Self::Read(CellOperand::try_from((argument, keyword))?)),
-            "write" => Ok(Self::Write(Operand::try_from((argument, keyword))?)),
+            "write" => match argument {
+                Some(s) if dialect == Dialect::Extended && s.starts_with('"') => {
+                    Ok(Self::WriteString(parse_string(s)?))
+                }
+                _ => Ok(Self::Write(Operand::try_from((argument, keyword))?)),
+            },
             "jump" => Ok(Self::Jump(parse_label(keyword, argument)?)),
             "jgtz" => Ok(Self::Jgtz(parse_label(keyword, argument)?)),
             "jzero" => Ok(Self::Jzero(parse_label(keyword, argument)?)),
-            "halt" => {
-                if let Some(v) = argument {
-                    Err(InstructionParseError::UnexpectedArgument(v.to_owned()))
-                } else {
-                    Ok(Self::Halt)
-                }
-            }
+            "halt" => expect_nothing(argument).map(|_| Self::Halt),
+            "mod" => Ok(Self::Mod(Operand::try_from((argument, keyword))?)),
+            "jneg" | "jltz" => Ok(Self::Jneg(parse_label(keyword, argument)?)),
+            "jgez" => Ok(Self::Jgez(parse_label(keyword, argument)?)),
+            "jnzero" => Ok(Self::Jnzero(parse_label(keyword, argument)?)),
+            "inc" => expect_nothing(argument).map(|_| Self::Inc),
+            "dec" => expect_nothing(argument).map(|_| Self::Dec),
             _ => Err(InstructionParseError::InvalidKeyword(keyword.to_owned())),
         }
     }
+
+    /// The label this instruction may jump to.
+    pub fn label(&self) -> Option<&str> {
+        use Instruction::*;
+        match self {
+            Jump(label) | Jgtz(label) | Jzero(label) | Jneg(label) | Jgez(label)
+            | Jnzero(label) => Some(label),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<(&str, Option<&str>)> for Instruction {
+    type Error = InstructionParseError;
+    fn try_from((keyword, argument): (&str, Option<&str>)) -> Result<Self, Self::Error> {
+        Instruction::parse(keyword, argument, Dialect::Strict, Lang::En)
+    }
+}
+
+impl FromStr for Dialect {
+    type Err = DialectParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "strict" => Ok(Dialect::Strict),
+            "extended" => Ok(Dialect::Extended),
+            _ => Err(DialectParseError(s.to_owned())),
+        }
+    }
+}
+
+impl fmt::Display for Dialect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Dialect::Strict => write!(f, "strict"),
+            Dialect::Extended => write!(f, "extended"),
+        }
+    }
 }
 
 impl fmt::Display for Instruction {
@@ -80,6 +256,13 @@ impl fmt::Display for Instruction {
             Instruction::Jgtz(label) => write!(f, "JGTZ {}", label),
             Instruction::Jzero(label) => write!(f, "JZERO {}", label),
             Instruction::Halt => write!(f, "HALT"),
+            Instruction::Mod(op) => write!(f, "MOD {}", op),
+            Instruction::Jneg(label) => write!(f, "JNEG {}", label),
+            Instruction::Jgez(label) => write!(f, "JGEZ {}", label),
+            Instruction::Jnzero(label) => write!(f, "JNZERO {}", label),
+            Instruction::Inc => write!(f, "INC"),
+            Instruction::Dec => write!(f, "DEC"),
+            Instruction::WriteString(text) => write!(f, "WRITE {}", quote_string(text)),
         }
     }
 }
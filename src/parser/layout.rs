@@ -0,0 +1,51 @@
+use super::cst::{CstLine, TokenKind};
+
+/// The parts of a source line, keeping the comments which `RamCode` discards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LineLayout<'a> {
+    /// Label without the trailing `:`
+    pub label: Option<&'a str>,
+    pub keyword: Option<&'a str>,
+    /// Operand, label or string literal following the keyword
+    pub argument: Option<&'a str>,
+    /// Any other code before the comment, which the parser rejects
+    pub extra: Option<&'a str>,
+    /// Comment including the leading `#`
+    pub comment: Option<&'a str>,
+}
+
+impl<'a> LineLayout<'a> {
+    pub fn parse(line: &'a str) -> Self {
+        LineLayout::from(&CstLine::parse(1, 0, line))
+    }
+
+    pub fn is_blank(&self) -> bool {
+        *self == LineLayout::default()
+    }
+
+    /// Whether the line holds code, as opposed to being blank or only a comment.
+    pub fn has_code(&self) -> bool {
+        self.label.is_some() || self.keyword.is_some()
+    }
+}
+
+impl<'a> From<&CstLine<'a>> for LineLayout<'a> {
+    fn from(line: &CstLine<'a>) -> Self {
+        let mut unexpected = line
+            .tokens
+            .iter()
+            .filter(|t| t.kind == TokenKind::Unexpected)
+            .map(|t| line.columns(t));
+        let extra = unexpected.next().map(|first| {
+            let end = unexpected.next_back().unwrap_or(first.clone()).end;
+            &line.text[first.start..end]
+        });
+        LineLayout {
+            label: line.label(),
+            keyword: line.token(TokenKind::Keyword).map(|t| t.text),
+            argument: line.argument().map(|t| t.text),
+            extra,
+            comment: line.comment().map(|t| t.text.trim_end()),
+        }
+    }
+}
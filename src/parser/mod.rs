@@ -1,96 +1,230 @@
+pub mod cst;
 pub mod instruction;
+pub mod layout;
 pub mod operand;
-
-use crate::{instruction::Instruction, instruction::InstructionParseError};
-use std::{collections::HashMap, str::FromStr};
+pub mod source_map;
+pub mod validate;
+
+use crate::{
+    error::ParserErrorChain,
+    instruction::{Dialect, DialectParseError, Instruction, InstructionParseError},
+    locale::{Lang, LangParseError, Localize},
+};
+use std::{collections::HashMap, fmt, str::FromStr};
 use thiserror::Error;
 
 use super::operand::CellAddress;
+use cst::{Cst, CstLine, TokenKind};
+use source_map::{LabelSpan, SourceMap, Span};
+use validate::ValidationError;
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct RamCode {
     pub instructions: Vec<Instruction>,
     pub jump_table: HashMap<String, CellAddress>,
+    pub source_map: SourceMap,
+    pub dialect: Dialect,
+    pub keywords: Lang,
 }
 
 #[derive(Error, Debug, PartialEq, Eq)]
+#[error("{}", self.localize(Lang::En))]
 pub enum CodeParseError {
-    #[error("Expected EOL, found `{0}`")]
     UnexpectedArgument(String),
-    #[error(transparent)]
     InstructionParseError(#[from] InstructionParseError),
+    /// Every validation error of the program from `RamCode::parse`, one per line from
+    /// `RamCode::parse_all`
+    ValidationErrors(Vec<ValidationError>),
+    DialectParseError(#[from] DialectParseError),
+    LangParseError(#[from] LangParseError),
 }
 
-macro_rules! return_if_comment {
-    ($e:expr) => {
-        if $e.starts_with('#') {
-            return Ok(());
-        }
-    };
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ParseOptions {
+    /// Skip the validation of labels and jump targets
+    pub lenient: bool,
+    /// Dialect used until a `#!dialect` pragma changes it
+    pub dialect: Dialect,
+    /// Language of the accepted keywords until a `#!keywords` pragma changes it
+    pub keywords: Lang,
 }
 
-const LABEL_END: char = ':';
+pub(crate) const LABEL_END: char = ':';
+pub(crate) const COMMENT_START: char = '#';
+const PRAGMA_START: &str = "#!";
 
 impl RamCode {
     pub fn new() -> RamCode {
         RamCode {
             instructions: Vec::new(),
             jump_table: HashMap::new(),
+            source_map: SourceMap::default(),
+            dialect: Dialect::default(),
+            keywords: Lang::default(),
         }
     }
 
-    pub fn push_line(&mut self, line: &str) -> Result<(), CodeParseError> {
-        let mut slices = line.split_whitespace().filter(|s| !s.is_empty());
-
-        let mut slice = match slices.next() {
-            None => return Ok(()),
-            Some(val) => val,
-        };
-
-        return_if_comment!(slice);
+    pub fn with_dialect(dialect: Dialect) -> RamCode {
+        RamCode {
+            dialect,
+            ..RamCode::new()
+        }
+    }
 
-        if slice.ends_with(LABEL_END) {
-            self.jump_table.insert(
-                slice.trim_end_matches(':').to_owned(),
-                self.instructions.len(),
-            );
+    pub fn push_line(&mut self, line: &str) -> Result<(), CodeParseError> {
+        self.push_cst_line(&CstLine::parse(self.source_map.lines + 1, 0, line))
+    }
 
-            slice = match slices.next() {
-                Some(val) => val,
-                None => return Ok(()),
-            }
+    /// Lowers a line of the concrete syntax tree into instructions and labels.
+    pub fn push_cst_line(&mut self, line: &CstLine) -> Result<(), CodeParseError> {
+        self.source_map.lines += 1;
+        let line_number = self.source_map.lines;
+        let content = line.content();
+        // Any other pragma, e.g. a shebang, is a comment
+        match line.pragma() {
+            Some(("dialect", value)) => self.dialect = value.trim().parse()?,
+            Some(("keywords", value)) => self.keywords = value.trim().parse()?,
+            _ => {}
         }
 
-        return_if_comment!(slice);
+        if let (Some(label), Some(token)) = (line.label(), line.token(TokenKind::Label)) {
+            let start = line.columns(token).start;
+            self.jump_table
+                .insert(label.to_owned(), self.instructions.len());
+            self.source_map.labels.push(LabelSpan {
+                label: label.to_owned(),
+                span: Span::new(line_number, content, start..start + label.len()),
+                target: self.instructions.len(),
+            });
+        }
 
-        let argument = slices.next();
+        let Some(keyword) = line.token(TokenKind::Keyword) else {
+            return Ok(());
+        };
+        let argument = line.argument();
+        let start = line.columns(keyword).start;
+        let end = line.columns(argument.unwrap_or(keyword)).end;
+
+        let instruction = Instruction::parse(
+            keyword.text,
+            argument.map(|token| token.text),
+            self.dialect,
+            self.keywords,
+        )?;
+        self.add_instruction_at(instruction, Span::new(line_number, content, start..end));
+
+        if let Some(token) = line.token(TokenKind::Unexpected) {
+            return Err(CodeParseError::UnexpectedArgument(token.text.to_owned()));
+        }
 
-        let instruction = Instruction::try_from((slice, argument))?;
-        self.add_instruction(instruction);
+        Ok(())
+    }
 
-        let rest = slices.next();
+    pub fn parse(s: &str, options: ParseOptions) -> Result<RamCode, CodeParseError> {
+        let mut code = RamCode {
+            keywords: options.keywords,
+            ..RamCode::with_dialect(options.dialect)
+        };
+        for line in &Cst::parse(s).lines {
+            code.push_cst_line(line)?;
+        }
+        if !options.lenient {
+            code.validate().map_err(CodeParseError::ValidationErrors)?;
+        }
+        Ok(code)
+    }
 
-        if let Some(v) = rest {
-            return_if_comment!(v);
-            return Err(CodeParseError::UnexpectedArgument(v.to_string()));
+    /// Parses every line, collecting all errors instead of stopping at the first one.
+    pub fn parse_all(s: &str, options: ParseOptions) -> Result<RamCode, ParserErrorChain> {
+        let (code, errors) = RamCode::parse_with_errors(s, options);
+        if !errors.is_empty() {
+            return Err(errors);
         }
+        Ok(code)
+    }
 
-        Ok(())
+    /// Like `parse_all`, but also returns the code built from the lines which could be parsed.
+    pub fn parse_with_errors(s: &str, options: ParseOptions) -> (RamCode, ParserErrorChain) {
+        let mut code = RamCode {
+            keywords: options.keywords,
+            ..RamCode::with_dialect(options.dialect)
+        };
+        let mut errors = ParserErrorChain::new();
+        for line in &Cst::parse(s).lines {
+            if let Err(err) = code.push_cst_line(line) {
+                errors.add((to_line_number(line.number), err));
+            }
+        }
+        // Suggestions for unknown keywords use the keywords in effect after every pragma
+        errors.1 = code.keywords;
+        if errors.is_empty() && !options.lenient {
+            for (line, err) in code.located_validation_errors() {
+                let err = CodeParseError::ValidationErrors(vec![err]);
+                errors.add((to_line_number(line.unwrap_or_default()), err));
+            }
+        }
+        (code, errors)
     }
 
+    /// Appends an instruction which has no place in the source, e.g. one built by a program.
     pub fn add_instruction(&mut self, instruction: Instruction) {
-        self.instructions.push(instruction)
+        self.add_instruction_at(instruction, Span::default())
+    }
+
+    /// Appends an instruction and its span, keeping both lists the same length.
+    fn add_instruction_at(&mut self, instruction: Instruction, span: Span) {
+        self.instructions.push(instruction);
+        self.source_map.instructions.push(span);
+    }
+}
+
+fn to_line_number(line: usize) -> u32 {
+    line.try_into()
+        .expect("Could not convert the line number to u32")
+}
+
+/// Programs are equal when they have the same instructions and labels, regardless of layout.
+impl PartialEq for RamCode {
+    fn eq(&self, other: &Self) -> bool {
+        self.instructions == other.instructions && self.jump_table == other.jump_table
     }
 }
 
+impl Eq for RamCode {}
+
 impl FromStr for RamCode {
     type Err = CodeParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut code = RamCode::new();
-        let lines = s.lines().filter(|line| !line.is_empty());
-        for line in lines {
-            code.push_line(line)?;
+        RamCode::parse(s, ParseOptions::default())
+    }
+}
+
+/// Source of the program with one instruction per line, parsing it gives an equal program.
+impl fmt::Display for RamCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.dialect != Dialect::default() {
+            writeln!(f, "{PRAGMA_START}dialect {}", self.dialect)?;
         }
-        Ok(code)
+        let mut labels: Vec<(&String, &CellAddress)> = self.jump_table.iter().collect();
+        labels.sort_by_key(|(label, target)| (**target, *label));
+        let mut labels = labels.into_iter().peekable();
+        for index in 0..self.instructions.len() {
+            let instruction = &self.instructions[index];
+            let mut label = None;
+            while let Some((next, _)) = labels.next_if(|(_, target)| **target <= index) {
+                if let Some(label) = label.replace(next) {
+                    writeln!(f, "{label}{LABEL_END}")?;
+                }
+            }
+            match label {
+                Some(label) => writeln!(f, "{label}{LABEL_END} {instruction}")?,
+                None => writeln!(f, "{instruction}")?,
+            }
+        }
+        // Labels placed after the last instruction
+        for (label, _) in labels {
+            writeln!(f, "{label}{LABEL_END}")?;
+        }
+        Ok(())
     }
 }
@@ -1,8 +1,14 @@
+use num_traits::ToPrimitive;
 use std::{fmt, str::FromStr};
 use thiserror::Error;
 
+use crate::locale::{Lang, Localize};
+
 pub type CellAddress = usize;
+#[cfg(not(feature = "bigint"))]
 pub type CellValue = i64;
+#[cfg(feature = "bigint")]
+pub type CellValue = num_bigint::BigInt;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Operand {
@@ -12,14 +18,13 @@ pub enum Operand {
 }
 
 #[derive(Error, Debug, PartialEq, Eq)]
-#[error("Operand {0} is not a valid operand")]
-pub struct InvalidOperandError(String);
+#[error("{}", self.localize(Lang::En))]
+pub struct InvalidOperandError(pub(crate) String);
 
 #[derive(Error, Debug, PartialEq, Eq)]
+#[error("{}", self.localize(Lang::En))]
 pub enum OperandParseError {
-    #[error("Operand {0} is not a valid operand for keyword `{1}`")]
     InvalidOperand(String, String),
-    #[error("Expected operand for keyword `{0}`, found nothing")]
     OperandNotFound(String),
 }
 
@@ -29,50 +34,50 @@ pub enum CellOperand {
     AddressOfCellInCell(CellAddress), // ^x
 }
 
-#[derive(Error, Debug, PartialEq, Eq)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{}", self.localize(Lang::En))]
 pub enum ExpandError {
-    #[error("Value `{0}` in cell `{1}` could not be converted to a tape index.")]
     ConvertError(CellValue, CellAddress),
-    #[error("Tried reading from cell with address `{0}`, which was never set.")]
     ValueNotSet(CellAddress),
 }
 
+/// Reads the value of `cell` as a tape address.
+pub(crate) fn address_in_cell(
+    tape: &[Option<CellValue>],
+    cell: CellAddress,
+) -> Result<CellAddress, ExpandError> {
+    let value = tape
+        .get(cell)
+        .and_then(|val| val.as_ref())
+        .ok_or(ExpandError::ValueNotSet(cell))?;
+    value
+        .to_usize()
+        .ok_or_else(|| ExpandError::ConvertError(value.clone(), cell))
+}
+
+/// Stores `value` in the cell at `index`, growing the tape as needed.
+#[inline]
+pub(crate) fn write_cell(tape: &mut Vec<Option<CellValue>>, index: CellAddress, value: CellValue) {
+    if tape.len() <= index {
+        tape.resize(index + 1, None);
+    }
+    tape[index] = Some(value);
+}
+
 impl Operand {
     pub fn expand<'a>(
         &'a self,
         tape: &'a [Option<CellValue>],
     ) -> Result<&'a CellValue, ExpandError> {
         use Operand::*;
-        match self {
-            Number(v) => Ok(v),
-            ValueInCell(cell) => Ok(tape
-                .get(*cell)
-                .and_then(|val| val.as_ref())
-                .ok_or(ExpandError::ValueNotSet(*cell))?),
-            ValueOfValueInCell(cell) => tape
-                .get(
-                    CellAddress::try_from(
-                        tape.get(*cell)
-                            .ok_or(ExpandError::ValueNotSet(*cell))?
-                            .ok_or(ExpandError::ValueNotSet(*cell))?,
-                    )
-                    .map_err(|_| {
-                        ExpandError::ConvertError(
-                            tape.get(*cell).expect("Would've failed before").unwrap(),
-                            *cell,
-                        )
-                    })?,
-                )
-                .and_then(|val| val.as_ref())
-                .ok_or_else(|| {
-                    ExpandError::ValueNotSet(
-                        CellAddress::try_from(
-                            tape.get(*cell).expect("Would've failed before").unwrap(),
-                        )
-                        .expect("Would've failed before"),
-                    )
-                }),
-        }
+        let address = match self {
+            Number(v) => return Ok(v),
+            ValueInCell(cell) => *cell,
+            ValueOfValueInCell(cell) => address_in_cell(tape, *cell)?,
+        };
+        tape.get(address)
+            .and_then(|val| val.as_ref())
+            .ok_or(ExpandError::ValueNotSet(address))
     }
 }
 
@@ -81,17 +86,7 @@ impl CellOperand {
         use CellOperand::*;
         match self {
             AddressOfCell(cell) => Ok(*cell),
-            AddressOfCellInCell(cell) => CellAddress::try_from(
-                tape.get(*cell)
-                    .ok_or(ExpandError::ValueNotSet(*cell))?
-                    .ok_or(ExpandError::ValueNotSet(*cell))?,
-            )
-            .map_err(|_| {
-                ExpandError::ConvertError(
-                    tape.get(*cell).expect("Didn't fail previously").unwrap(),
-                    *cell,
-                )
-            }),
+            AddressOfCellInCell(cell) => address_in_cell(tape, *cell),
         }
     }
 }
@@ -0,0 +1,46 @@
+use std::ops::Range;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Span {
+    pub line: usize,
+    pub columns: Range<usize>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LabelSpan {
+    pub label: String,
+    pub span: Span,
+    pub target: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SourceMap {
+    pub lines: usize,
+    pub instructions: Vec<Span>,
+    pub labels: Vec<LabelSpan>,
+}
+
+impl Span {
+    pub fn new(line: usize, source: &str, columns: Range<usize>) -> Self {
+        Span {
+            line,
+            text: source[columns.clone()].to_owned(),
+            columns,
+        }
+    }
+}
+
+impl SourceMap {
+    /// Span of the instruction, `None` if it was added without a place in the source.
+    pub fn instruction(&self, index: usize) -> Option<&Span> {
+        self.instructions.get(index).filter(|span| span.line > 0)
+    }
+
+    pub fn label(&self, label: &str) -> Option<&Span> {
+        self.labels
+            .iter()
+            .find(|l| l.label == label)
+            .map(|l| &l.span)
+    }
+}
@@ -0,0 +1,65 @@
+use std::collections::HashSet;
+
+use thiserror::Error;
+
+use super::{source_map::LabelSpan, RamCode};
+use crate::locale::{Lang, Localize};
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{}", self.localize(Lang::En))]
+pub enum ValidationError {
+    DuplicateLabel(String),
+    UndefinedLabel(String, usize),
+}
+
+impl RamCode {
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let errors: Vec<ValidationError> = self
+            .located_validation_errors()
+            .into_iter()
+            .map(|(_, err)| err)
+            .collect();
+        if errors.is_empty() {
+            return Ok(());
+        }
+        Err(errors)
+    }
+
+    /// Labels placed after the last instruction. They are valid, but jumping to one simply runs
+    /// past the end of the program.
+    pub fn trailing_labels(&self) -> impl Iterator<Item = &LabelSpan> {
+        self.source_map
+            .labels
+            .iter()
+            .filter(|label| label.target == self.instructions.len())
+    }
+
+    /// Validation errors together with the source line they originate from, if known.
+    pub(crate) fn located_validation_errors(&self) -> Vec<(Option<usize>, ValidationError)> {
+        let mut errors = Vec::new();
+
+        let mut defined = HashSet::new();
+        for label in &self.source_map.labels {
+            if !defined.insert(label.label.as_str()) {
+                errors.push((
+                    Some(label.span.line),
+                    ValidationError::DuplicateLabel(label.label.clone()),
+                ));
+            }
+        }
+
+        for (index, instruction) in self.instructions.iter().enumerate() {
+            if let Some(label) = instruction.label() {
+                if !self.jump_table.contains_key(label) {
+                    errors.push((
+                        self.source_map.instruction(index).map(|span| span.line),
+                        ValidationError::UndefinedLabel(label.to_owned(), index),
+                    ));
+                }
+            }
+        }
+
+        errors.sort_by_key(|(line, _)| *line);
+        errors
+    }
+}
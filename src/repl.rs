@@ -0,0 +1,162 @@
+use std::fs;
+use std::io::{self, BufRead, Write};
+
+use ram_machine::interpreter::{RamMachine, RunState};
+use ram_machine::locale::{Lang, Localize, Message};
+use ram_machine::operand::CellValue;
+use ram_machine::parser::RamCode;
+
+use crate::cli::RuntimeError;
+
+const PROMPT: &str = "ram> ";
+struct Session {
+    machine: RamMachine,
+    history: Vec<RamMachine>,
+    max_steps: Option<u64>,
+    lang: Lang,
+    keywords: Lang,
+}
+
+impl Session {
+    fn new(max_steps: Option<u64>, lang: Lang, keywords: Lang) -> Self {
+        let code = RamCode {
+            keywords,
+            ..RamCode::new()
+        };
+        Session {
+            machine: RamMachine::new(code, Vec::new()),
+            history: Vec::new(),
+            max_steps,
+            lang,
+            keywords,
+        }
+    }
+
+    /// Whether the machine ran past the last instruction and will continue with the next line.
+    fn is_waiting_for_instruction(&self) -> bool {
+        self.machine.pointer() >= self.machine.code().instructions.len()
+    }
+
+    /// Adds a line to the program and runs it if the machine was waiting for the next instruction.
+    fn eval_line(&mut self, line: &str) -> Result<(), String> {
+        let was_waiting = self.is_waiting_for_instruction();
+        self.machine
+            .push_line(line)
+            .map_err(|e| e.localize(self.lang))?;
+        if !was_waiting {
+            return Ok(());
+        }
+
+        self.machine
+            .set_max_steps(self.max_steps.map(|limit| self.machine.steps() + limit));
+        while self
+            .machine
+            .run_line()
+            .map_err(|e| format!("{} — {}", e.instruction, e.error.localize(self.lang)))?
+            == RunState::Running
+        {}
+        Ok(())
+    }
+
+    /// Runs `f` on the session and keeps its effect only if it succeeded.
+    fn transaction(&mut self, f: impl FnOnce(&mut Self) -> Result<(), String>) {
+        let snapshot = self.machine.clone();
+        let output_len = snapshot.output().len();
+        match f(self) {
+            Ok(()) => {
+                self.history.push(snapshot);
+                let new_output = &self.machine.output()[output_len..];
+                if !new_output.is_empty() {
+                    println!("{}", self.localize(Message::Output(&join(new_output))));
+                }
+                if !self.is_waiting_for_instruction() {
+                    println!("{}", self.localize(Message::ReplHalted));
+                }
+            }
+            Err(e) => {
+                self.machine = snapshot;
+                println!("{e}");
+            }
+        }
+    }
+
+    fn eval(&mut self, line: &str) -> bool {
+        let line = line.trim();
+        let (command, argument) = line.split_once(' ').unwrap_or((line, ""));
+        match command {
+            "" => {}
+            ":quit" | ":q" => return false,
+            ":help" | ":h" => println!("{}", self.localize(Message::ReplHelp)),
+            ":tape" | ":t" => println!("{}", self.machine.state_table_in(self.lang)),
+            ":input" | ":i" => {
+                let values: Result<Vec<CellValue>, _> = argument
+                    .split_whitespace()
+                    .map(|value| {
+                        value
+                            .parse()
+                            .map_err(|_| RuntimeError::ConvertInputError(value.to_owned()))
+                    })
+                    .collect();
+                match values {
+                    Ok(values) => self.transaction(|session| {
+                        session.machine.push_input(values);
+                        Ok(())
+                    }),
+                    Err(e) => println!("{}", self.localize(e)),
+                }
+            }
+            ":load" | ":l" => match fs::read_to_string(argument.trim()) {
+                Ok(file) => self.transaction(|session| {
+                    for (index, line) in file.lines().enumerate() {
+                        session
+                            .eval_line(line)
+                            .map_err(|e| format!("{argument}:{}: {e}", index + 1))?;
+                    }
+                    Ok(())
+                }),
+                Err(e) => println!("{}", self.localize(RuntimeError::ReadCodeError(e))),
+            },
+            ":reset" => *self = Session::new(self.max_steps, self.lang, self.keywords),
+            ":undo" | ":u" => match self.history.pop() {
+                Some(machine) => self.machine = machine,
+                None => println!("{}", self.localize(Message::NothingToUndo)),
+            },
+            c if c.starts_with(':') => {
+                println!("{}", self.localize(Message::UnknownCommand(c, ":help")))
+            }
+            _ => self.transaction(|session| session.eval_line(line)),
+        }
+        true
+    }
+
+    fn localize(&self, message: impl Localize) -> String {
+        message.localize(self.lang)
+    }
+}
+
+fn join(values: &[CellValue]) -> String {
+    values
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+pub fn repl(max_steps: Option<u64>, lang: Lang, keywords: Lang) -> Result<(), RuntimeError> {
+    let mut session = Session::new(max_steps, lang, keywords);
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+
+    println!("{}", session.localize(Message::ReplGreeting));
+    loop {
+        print!("{PROMPT}");
+        io::stdout().flush().map_err(RuntimeError::TerminalError)?;
+        let Some(line) = lines.next() else {
+            println!();
+            return Ok(());
+        };
+        if !session.eval(&line.map_err(RuntimeError::TerminalError)?) {
+            return Ok(());
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use std::fmt;
+
+use crate::locale::{Lang, Localize};
+use crate::operand::{CellAddress, CellValue};
+
+/// Time and memory complexity of a run under the uniform and the logarithmic cost criterion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RunStats {
+    pub uniform_time: u64,
+    pub logarithmic_time: u64,
+    pub uniform_space: u64,
+    pub logarithmic_space: u64,
+}
+
+/// Length of the binary representation of a value, `l(i)` in the cost tables.
+pub fn value_cost(value: &CellValue) -> u64 {
+    #[cfg(not(feature = "bigint"))]
+    let bits = u64::from(u64::BITS - value.unsigned_abs().leading_zeros());
+    #[cfg(feature = "bigint")]
+    let bits = value.bits();
+    bits.max(1)
+}
+
+pub fn address_cost(address: CellAddress) -> u64 {
+    match address {
+        0 => 1,
+        a => u64::from(usize::BITS - a.leading_zeros()),
+    }
+}
+
+impl fmt::Display for RunStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.localize(Lang::En))
+    }
+}
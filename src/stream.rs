@@ -0,0 +1,242 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, IsTerminal, Stdin, Stdout, Write};
+use std::path::Path;
+use std::sync::mpsc::{Receiver, Sender};
+
+use thiserror::Error;
+
+use crate::interpreter::{InputAccessError, RamMachineError};
+use crate::locale::{Lang, Localize};
+use crate::operand::CellValue;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{}", self.localize(Lang::En))]
+pub enum StreamError {
+    ReadFailed(String),
+    InvalidInput(String),
+    WriteFailed(String),
+}
+
+/// Where `READ` takes its values from once the buffered input is used up.
+pub trait InputSource {
+    /// Returns the next value, or `None` if the input is exhausted.
+    fn read(&mut self) -> Result<Option<CellValue>, StreamError>;
+}
+
+/// Receives every value as soon as `WRITE` executes.
+pub trait OutputSink {
+    fn write(&mut self, value: &CellValue) -> Result<(), StreamError>;
+
+    /// Receives the text of `WRITE "text"`, it is dropped by default.
+    #[allow(unused_variables)]
+    fn write_str(&mut self, text: &str) -> Result<(), StreamError> {
+        Ok(())
+    }
+}
+
+impl InputSource for VecDeque<CellValue> {
+    fn read(&mut self) -> Result<Option<CellValue>, StreamError> {
+        Ok(self.pop_front())
+    }
+}
+
+impl OutputSink for Vec<CellValue> {
+    fn write(&mut self, value: &CellValue) -> Result<(), StreamError> {
+        self.push(value.clone());
+        Ok(())
+    }
+}
+
+impl<S: InputSource + ?Sized> InputSource for Box<S> {
+    fn read(&mut self) -> Result<Option<CellValue>, StreamError> {
+        (**self).read()
+    }
+}
+
+impl<S: OutputSink + ?Sized> OutputSink for Box<S> {
+    fn write(&mut self, value: &CellValue) -> Result<(), StreamError> {
+        (**self).write(value)
+    }
+
+    fn write_str(&mut self, text: &str) -> Result<(), StreamError> {
+        (**self).write_str(text)
+    }
+}
+
+impl InputSource for Receiver<CellValue> {
+    /// Blocks until a value arrives, the input ends when all senders are dropped.
+    fn read(&mut self) -> Result<Option<CellValue>, StreamError> {
+        Ok(self.recv().ok())
+    }
+}
+
+impl OutputSink for Sender<CellValue> {
+    fn write(&mut self, value: &CellValue) -> Result<(), StreamError> {
+        self.send(value.clone())
+            .map_err(|e| StreamError::WriteFailed(e.to_string()))
+    }
+}
+
+/// Lazily reads whitespace separated integers, a line at a time.
+pub struct ReaderSource<R> {
+    reader: R,
+    pending: VecDeque<String>,
+    prompt: Option<String>,
+}
+
+impl<R: BufRead> ReaderSource<R> {
+    pub fn new(reader: R) -> Self {
+        ReaderSource {
+            reader,
+            pending: VecDeque::new(),
+            prompt: None,
+        }
+    }
+
+    /// Prints `prompt` to STDERR whenever a new line has to be read.
+    pub fn with_prompt(mut self, prompt: &str) -> Self {
+        self.prompt = Some(prompt.to_owned());
+        self
+    }
+}
+
+impl ReaderSource<BufReader<Stdin>> {
+    /// Reads from STDIN, prompting for values when it is a terminal.
+    pub fn stdin() -> Self {
+        let stdin = io::stdin();
+        let interactive = stdin.is_terminal();
+        let source = ReaderSource::new(BufReader::new(stdin));
+        if interactive {
+            source.with_prompt("input> ")
+        } else {
+            source
+        }
+    }
+}
+
+impl ReaderSource<BufReader<File>> {
+    pub fn file(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(ReaderSource::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+impl<R: BufRead> InputSource for ReaderSource<R> {
+    fn read(&mut self) -> Result<Option<CellValue>, StreamError> {
+        while self.pending.is_empty() {
+            if let Some(prompt) = &self.prompt {
+                eprint!("{prompt}");
+            }
+            let mut line = String::new();
+            let read = self
+                .reader
+                .read_line(&mut line)
+                .map_err(|e| StreamError::ReadFailed(e.to_string()))?;
+            if read == 0 {
+                return Ok(None);
+            }
+            self.pending
+                .extend(line.split_whitespace().map(str::to_owned));
+        }
+        let token = self.pending.pop_front().expect("Checked above");
+        token
+            .parse()
+            .map(Some)
+            .map_err(|_| StreamError::InvalidInput(token))
+    }
+}
+
+/// Writes every value on its own line and flushes it right away.
+pub struct WriterSink<W> {
+    writer: W,
+}
+
+impl<W: Write> WriterSink<W> {
+    pub fn new(writer: W) -> Self {
+        WriterSink { writer }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl WriterSink<Stdout> {
+    pub fn stdout() -> Self {
+        WriterSink::new(io::stdout())
+    }
+}
+
+impl WriterSink<File> {
+    pub fn file(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(WriterSink::new(File::create(path)?))
+    }
+}
+
+impl<W: Write> OutputSink for WriterSink<W> {
+    fn write(&mut self, value: &CellValue) -> Result<(), StreamError> {
+        writeln!(self.writer, "{value}")
+            .and_then(|_| self.writer.flush())
+            .map_err(|e| StreamError::WriteFailed(e.to_string()))
+    }
+
+    /// Text is written as is, it ends the line only if it contains `\n`.
+    fn write_str(&mut self, text: &str) -> Result<(), StreamError> {
+        write!(self.writer, "{text}")
+            .and_then(|_| self.writer.flush())
+            .map_err(|e| StreamError::WriteFailed(e.to_string()))
+    }
+}
+
+/// The streams attached to a machine. They are not cloned with it and don't take part in comparisons.
+#[derive(Default)]
+pub(crate) struct Streams {
+    pub(crate) source: Option<Box<dyn InputSource + Send>>,
+    pub(crate) sink: Option<Box<dyn OutputSink + Send>>,
+}
+
+impl Streams {
+    /// Returns `input[pointer]` and advances `pointer`, first reading from the source once
+    /// `input` is used up.
+    pub(crate) fn read_input(
+        &mut self,
+        input: &mut Vec<CellValue>,
+        pointer: &mut usize,
+    ) -> Result<CellValue, RamMachineError> {
+        if *pointer == input.len() {
+            if let Some(source) = &mut self.source {
+                input.extend(source.read()?);
+            }
+        }
+        let value = input
+            .get(*pointer)
+            .cloned()
+            .ok_or(InputAccessError::NotExistentInput(*pointer))?;
+        *pointer += 1;
+        Ok(value)
+    }
+}
+
+impl Clone for Streams {
+    fn clone(&self) -> Self {
+        Streams::default()
+    }
+}
+
+impl PartialEq for Streams {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for Streams {}
+
+impl fmt::Debug for Streams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Streams")
+            .field("source", &self.source.is_some())
+            .field("sink", &self.sink.is_some())
+            .finish()
+    }
+}
@@ -0,0 +1,124 @@
+use num_traits::ToPrimitive;
+use serde_json::{json, Value};
+
+use crate::history::StepDelta;
+use crate::instruction::Instruction;
+use crate::interpreter::RamMachine;
+use crate::observer::ExecutionObserver;
+use crate::operand::{CellAddress, CellValue};
+
+pub const CSV_HEADER: &str = "step,pointer,instruction,line,buffer_before,buffer_after,\
+write_address,write_value,input,output";
+
+/// What a single executed instruction did, as recorded by `RamMachine::with_trace`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceRecord {
+    pub step: u64,
+    pub pointer: usize,
+    pub instruction: String,
+    pub line: Option<usize>,
+    pub buffer_before: Option<CellValue>,
+    pub buffer_after: Option<CellValue>,
+    pub tape_write: Option<(CellAddress, CellValue)>,
+    pub input: Option<CellValue>,
+    pub output: Option<CellValue>,
+}
+
+impl TraceRecord {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "step": self.step,
+            "pointer": self.pointer,
+            "instruction": self.instruction,
+            "line": self.line,
+            "buffer_before": self.buffer_before.as_ref().map(json_value),
+            "buffer_after": self.buffer_after.as_ref().map(json_value),
+            "tape_write": self.tape_write.as_ref().map(|(address, value)| json!({
+                "address": address,
+                "value": json_value(value),
+            })),
+            "input": self.input.as_ref().map(json_value),
+            "output": self.output.as_ref().map(json_value),
+        })
+    }
+
+    /// A row matching `CSV_HEADER`, unknown values are left empty.
+    pub fn to_csv(&self) -> String {
+        fn field<T: ToString>(value: Option<T>) -> String {
+            value.map_or(String::new(), |v| v.to_string())
+        }
+        [
+            self.step.to_string(),
+            self.pointer.to_string(),
+            csv_escape(&self.instruction),
+            field(self.line),
+            field(self.buffer_before.as_ref()),
+            field(self.buffer_after.as_ref()),
+            field(self.tape_write.as_ref().map(|(address, _)| address)),
+            field(self.tape_write.as_ref().map(|(_, value)| value)),
+            field(self.input.as_ref()),
+            field(self.output.as_ref()),
+        ]
+        .join(",")
+    }
+}
+
+/// Collects a `TraceRecord` for every executed instruction.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Tracer {
+    pub(crate) records: Vec<TraceRecord>,
+    current: Option<TraceRecord>,
+}
+
+impl ExecutionObserver for Tracer {
+    fn before_instruction(&mut self, machine: &RamMachine, instruction: &Instruction) {
+        let pointer = machine.pointer();
+        self.current = Some(TraceRecord {
+            step: machine.steps(),
+            pointer,
+            instruction: instruction.to_string(),
+            line: machine
+                .code()
+                .source_map
+                .instruction(pointer)
+                .map(|s| s.line),
+            buffer_before: machine.cell(0),
+            buffer_after: None,
+            tape_write: None,
+            input: None,
+            output: None,
+        });
+    }
+
+    fn on_step(&mut self, machine: &RamMachine, delta: &StepDelta) {
+        // A failed instruction leaves no record, the next one replaces it
+        let Some(mut record) = self.current.take() else {
+            return;
+        };
+        record.buffer_after = machine.cell(0);
+        record.tape_write = delta
+            .tape_write
+            .as_ref()
+            .map(|w| (w.address, w.new.clone()));
+        record.input = (machine.input_pointer() > delta.input_pointer)
+            .then(|| machine.input()[delta.input_pointer].clone());
+        record.output = delta.output.clone();
+        self.records.push(record);
+    }
+}
+
+/// Values which don't fit a JSON number are written as strings.
+fn json_value(value: &CellValue) -> Value {
+    match value.to_i64() {
+        Some(v) => Value::from(v),
+        None => Value::String(value.to_string()),
+    }
+}
+
+fn csv_escape(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_owned()
+    }
+}
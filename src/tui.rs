@@ -0,0 +1,385 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+
+use ram_machine::debugger::{Breakpoint, Debugger, StopReason};
+use ram_machine::interpreter::RamMachine;
+use ram_machine::locale::{Lang, Localize, Message};
+use ram_machine::operand::CellAddress;
+
+use crate::cli::RuntimeError;
+
+/// Number of most recent tape writes which are highlighted.
+const RECENT_WRITES: usize = 5;
+/// Instructions executed between checking for key presses during a run.
+const RUN_CHUNK: usize = 10_000;
+const KEYS: [(&str, Message); 9] = [
+    ("s", Message::KeyStep),
+    ("S", Message::KeyStepBack),
+    ("r", Message::KeyRun),
+    ("R", Message::KeyReverseRun),
+    ("f", Message::KeyFinish),
+    ("b", Message::KeyBreakpoint),
+    ("Esc", Message::KeyStopRun),
+    ("↑↓", Message::KeySelectLine),
+    ("q", Message::KeyQuit),
+];
+
+/// A run which continues in chunks until it stops or is interrupted.
+#[derive(Clone, Copy)]
+enum Run {
+    Resume,
+    Finish,
+}
+
+struct App<'a> {
+    debugger: Debugger,
+    source: Vec<&'a str>,
+    selected: ListState,
+    status: String,
+    lang: Lang,
+    running: Option<Run>,
+}
+
+impl<'a> App<'a> {
+    fn new(machine: RamMachine, source: &'a str, lang: Lang) -> Self {
+        let mut app = App {
+            debugger: Debugger::new(machine),
+            source: source.lines().collect(),
+            selected: ListState::default(),
+            status: Message::TuiStart.localize(lang),
+            lang,
+            running: None,
+        };
+        app.follow_pointer();
+        app
+    }
+
+    /// Line of the next instruction, 0-based.
+    fn current_line(&self) -> Option<usize> {
+        if self.debugger.is_halted() {
+            return None;
+        }
+        let machine = self.debugger.machine();
+        machine
+            .code()
+            .source_map
+            .instruction(machine.pointer())
+            .map(|span| span.line - 1)
+    }
+
+    fn follow_pointer(&mut self) {
+        if let Some(line) = self.current_line() {
+            self.selected.select(Some(line));
+        }
+    }
+
+    fn breakpoint_lines(&self) -> HashSet<usize> {
+        let source_map = &self.debugger.machine().code().source_map;
+        self.debugger
+            .breakpoints()
+            .iter()
+            .filter_map(|b| source_map.instruction(b.location))
+            .map(|span| span.line - 1)
+            .collect()
+    }
+
+    fn recent_writes(&self) -> Vec<CellAddress> {
+        self.debugger
+            .machine()
+            .history()
+            .into_iter()
+            .flat_map(|history| history.iter().rev())
+            .filter_map(|delta| delta.tape_write.as_ref().map(|write| write.address))
+            .take(RECENT_WRITES)
+            .collect()
+    }
+
+    fn toggle_breakpoint(&mut self) {
+        let Some(line) = self.selected.selected() else {
+            return;
+        };
+        let location = match self.debugger.resolve_line(line + 1) {
+            Ok(location) => location,
+            Err(e) => {
+                self.status = e.localize(self.lang);
+                return;
+            }
+        };
+        match self
+            .debugger
+            .breakpoints()
+            .iter()
+            .position(|b| b.location == location)
+        {
+            Some(index) => {
+                self.debugger.remove_breakpoint(index);
+                self.status = self.localize(Message::BreakpointRemoved(location));
+            }
+            None => {
+                self.debugger.add_breakpoint(Breakpoint {
+                    location,
+                    condition: None,
+                });
+                self.status = self.localize(Message::BreakpointSet(location));
+            }
+        }
+    }
+
+    fn report(&mut self, reason: StopReason) {
+        let steps = self.debugger.machine().steps();
+        self.status = match reason {
+            StopReason::Step => self.localize(Message::Step(steps)),
+            StopReason::Breakpoint(index) => {
+                self.localize(Message::StepBreakpointHit(steps, index))
+            }
+            StopReason::Watchpoint(cell, _, _) => {
+                self.localize(Message::StepCellChanged(steps, cell))
+            }
+            StopReason::Halted => self.localize(Message::HaltedAfter(steps)),
+            StopReason::StartOfHistory => self.localize(Message::StartOfHistory),
+            StopReason::Error(e) => self.localize(e),
+        };
+        self.follow_pointer();
+    }
+
+    /// Executes the next chunk of a run in progress.
+    fn continue_run(&mut self, run: Run) {
+        let reason = match run {
+            Run::Resume => self.debugger.resume_for(RUN_CHUNK),
+            Run::Finish => self.debugger.finish_for(RUN_CHUNK),
+        };
+        match reason {
+            Some(reason) => {
+                self.running = None;
+                self.report(reason);
+            }
+            None => {
+                let steps = self.debugger.machine().steps();
+                self.status = self.localize(Message::Running(steps));
+            }
+        }
+    }
+
+    /// Handles a key press, returns false when the user wants to quit.
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let interrupt = key.code == KeyCode::Esc
+            || key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+        if self.running.is_some() {
+            // Only stopping or quitting is possible while the machine runs
+            if interrupt {
+                self.running = None;
+                let steps = self.debugger.machine().steps();
+                self.status = self.localize(Message::StoppedAfter(steps));
+                self.follow_pointer();
+            }
+            return key.code != KeyCode::Char('q');
+        }
+        if interrupt {
+            return false;
+        }
+        match key.code {
+            KeyCode::Char('q') => return false,
+            KeyCode::Char('s') => {
+                let reason = self.debugger.step(1);
+                self.report(reason)
+            }
+            KeyCode::Char('S') => {
+                let reason = self.debugger.step_back(1);
+                self.report(reason)
+            }
+            KeyCode::Char('r') => self.running = Some(Run::Resume),
+            KeyCode::Char('R') => {
+                let reason = self.debugger.reverse_resume();
+                self.report(reason)
+            }
+            KeyCode::Char('f') => self.running = Some(Run::Finish),
+            KeyCode::Char('b') => self.toggle_breakpoint(),
+            KeyCode::Up | KeyCode::Char('k') => self.selected.select_previous(),
+            KeyCode::Down | KeyCode::Char('j') => {
+                let next = self.selected.selected().map_or(0, |line| line + 1);
+                self.selected
+                    .select(Some(next.min(self.source.len().saturating_sub(1))));
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn localize(&self, message: impl Localize) -> String {
+        message.localize(self.lang)
+    }
+
+    fn title(&self, title: Message) -> String {
+        format!(" {} ", self.localize(title))
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, status, keys] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [source, side] =
+            Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+                .areas(main);
+        let [tape, input, output] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(3),
+            Constraint::Length(3),
+        ])
+        .areas(side);
+
+        self.draw_source(frame, source);
+        self.draw_tape(frame, tape);
+        self.draw_input(frame, input);
+        self.draw_output(frame, output);
+        frame.render_widget(Paragraph::new(self.status.as_str()).bold(), status);
+        let bar: Vec<Span> = KEYS
+            .iter()
+            .flat_map(|(key, action)| {
+                [
+                    Span::from(format!(" {key} ")).reversed(),
+                    Span::from(format!(" {}  ", action.localize(self.lang))),
+                ]
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(Line::from(bar)), keys);
+    }
+
+    fn draw_source(&mut self, frame: &mut Frame, area: Rect) {
+        let current = self.current_line();
+        let breakpoints = self.breakpoint_lines();
+        let width = self.source.len().to_string().len();
+        let items: Vec<ListItem> = self
+            .source
+            .iter()
+            .enumerate()
+            .map(|(index, text)| {
+                let marker = if breakpoints.contains(&index) {
+                    Span::from("● ").red()
+                } else {
+                    Span::from("  ")
+                };
+                let line = Line::from(vec![
+                    marker,
+                    Span::from(format!("{:>width$} ", index + 1)).dark_gray(),
+                    Span::from(text.replace('\t', "    ")),
+                ]);
+                if current == Some(index) {
+                    ListItem::new(line).on_blue()
+                } else {
+                    ListItem::new(line)
+                }
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::bordered().title(self.title(Message::SourceTitle)))
+            .highlight_symbol("▶")
+            .highlight_style(Style::new().add_modifier(Modifier::BOLD));
+        frame.render_stateful_widget(list, area, &mut self.selected);
+    }
+
+    fn draw_tape(&self, frame: &mut Frame, area: Rect) {
+        let recent = self.recent_writes();
+        let tape = self.debugger.machine().tape();
+        let items: Vec<ListItem> = tape
+            .iter()
+            .enumerate()
+            .map(|(address, value)| {
+                let value = value.as_ref().map_or("?".to_owned(), |v| v.to_string());
+                let name = match address {
+                    0 => "acc".to_owned(),
+                    address => address.to_string(),
+                };
+                let item = ListItem::new(format!("[{name:>3}] {value}"));
+                match recent.iter().position(|a| *a == address) {
+                    Some(0) => item.black().on_yellow(),
+                    Some(_) => item.yellow(),
+                    None => item,
+                }
+            })
+            .collect();
+        let mut state = ListState::default().with_selected(recent.first().copied());
+        frame.render_stateful_widget(
+            List::new(items).block(Block::bordered().title(self.title(Message::TapeTitle))),
+            area,
+            &mut state,
+        );
+    }
+
+    fn draw_input(&self, frame: &mut Frame, area: Rect) {
+        let machine = self.debugger.machine();
+        let consumed = machine.input_pointer();
+        let mut spans: Vec<Span> = Vec::new();
+        for (index, value) in machine.input().iter().enumerate() {
+            let span = Span::from(format!("{value} "));
+            spans.push(match index {
+                i if i < consumed => span.dark_gray().crossed_out(),
+                i if i == consumed => span.bold().underlined(),
+                _ => span,
+            });
+            if index + 1 == consumed {
+                spans.push(Span::from("▏").yellow());
+            }
+        }
+        let input = self.localize(Message::InputTitle);
+        let title = format!(" {input} {consumed}/{} ", machine.input().len());
+        frame.render_widget(
+            Paragraph::new(Line::from(spans)).block(Block::bordered().title(title)),
+            area,
+        );
+    }
+
+    fn draw_output(&self, frame: &mut Frame, area: Rect) {
+        let output = self
+            .debugger
+            .machine()
+            .output()
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<String>>()
+            .join(" ");
+        frame.render_widget(
+            Paragraph::new(output).block(Block::bordered().title(self.title(Message::OutputTitle))),
+            area,
+        );
+    }
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, mut app: App) -> Result<(), RuntimeError> {
+    loop {
+        terminal
+            .draw(|frame| app.draw(frame))
+            .map_err(RuntimeError::TerminalError)?;
+        // While the machine runs, key presses are only checked for between chunks
+        let pending = match app.running {
+            Some(_) => event::poll(Duration::ZERO).map_err(RuntimeError::TerminalError)?,
+            None => true,
+        };
+        if pending {
+            if let Event::Key(key) = event::read().map_err(RuntimeError::TerminalError)? {
+                if key.kind == KeyEventKind::Press && !app.handle_key(key) {
+                    return Ok(());
+                }
+            }
+        }
+        if let Some(run) = app.running {
+            app.continue_run(run);
+        }
+    }
+}
+
+pub fn tui(machine: RamMachine, source: &str, lang: Lang) -> Result<(), RuntimeError> {
+    let mut terminal = ratatui::try_init().map_err(RuntimeError::TerminalError)?;
+    let result = event_loop(&mut terminal, App::new(machine, source, lang));
+    ratatui::try_restore().map_err(RuntimeError::TerminalError)?;
+    result
+}
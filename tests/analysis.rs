@@ -0,0 +1,90 @@
+use ram_machine::analysis::{Analysis, CompletionKind, RenameError};
+use ram_machine::locale::Lang;
+use ram_machine::parser::ParseOptions;
+
+const SOURCE: &str = "\
+read 1
+loop: load 1
+      jzero end
+      sub =1
+      store 1
+      jump loop
+end:  halt
+";
+
+#[test]
+fn definition_and_references() {
+    let analysis = Analysis::new(SOURCE, ParseOptions::default(), Lang::En);
+    assert!(analysis.diagnostics().is_empty());
+
+    // On `loop` in `jump loop`
+    let definition = analysis.definition(6, 12).unwrap();
+    assert_eq!((definition.line, definition.columns), (2, 0..4));
+
+    let references: Vec<(usize, usize)> = analysis
+        .references(2, 1, true)
+        .iter()
+        .map(|span| (span.line, span.columns.start))
+        .collect();
+    assert_eq!(references, [(2, 0), (6, 11)]);
+    assert_eq!(analysis.references(2, 1, false).len(), 1);
+    assert!(analysis.definition(4, 11).is_none());
+}
+
+#[test]
+fn hover() {
+    let analysis = Analysis::new(SOURCE, ParseOptions::default(), Lang::En);
+    assert!(analysis
+        .hover(3, 7, Lang::En)
+        .unwrap()
+        .contains("JZERO label"));
+    assert!(analysis.hover(4, 11, Lang::En).unwrap().contains("number"));
+    assert!(analysis.hover(1, 5, Lang::Pl).unwrap().contains("komórka"));
+    assert!(analysis
+        .hover(3, 13, Lang::En)
+        .unwrap()
+        .contains("instruction 6"));
+}
+
+#[test]
+fn completions() {
+    let analysis = Analysis::new("start: \n  jump \n", ParseOptions::default(), Lang::En);
+    let keywords = analysis.completions(1, 7, Lang::En);
+    assert!(keywords.iter().any(|c| c.label == "load"));
+    assert!(keywords.iter().all(|c| c.kind == CompletionKind::Keyword));
+    assert!(!keywords.iter().any(|c| c.label == "mod"));
+
+    let labels = analysis.completions(2, 7, Lang::En);
+    assert_eq!(labels.len(), 1);
+    assert_eq!(labels[0].label, "start");
+    assert_eq!(labels[0].kind, CompletionKind::Label);
+}
+
+#[test]
+fn rename() {
+    let analysis = Analysis::new(SOURCE, ParseOptions::default(), Lang::En);
+    let spans = analysis.rename(3, 13, "finish").unwrap();
+    let lines: Vec<usize> = spans.iter().map(|span| span.line).collect();
+    assert_eq!(lines, [3, 7]);
+
+    assert_eq!(
+        analysis.rename(3, 13, "loop"),
+        Err(RenameError::AlreadyDefined("loop".to_owned()))
+    );
+    assert_eq!(
+        analysis.rename(3, 13, "a b"),
+        Err(RenameError::InvalidName("a b".to_owned()))
+    );
+    assert_eq!(analysis.rename(4, 8, "x"), Err(RenameError::NoLabel));
+}
+
+#[test]
+fn analysis_with_errors() {
+    let analysis = Analysis::new(
+        "loop: lod 1\njump loop\n",
+        ParseOptions::default(),
+        Lang::En,
+    );
+    assert_eq!(analysis.diagnostics().len(), 1);
+    assert_eq!(analysis.definition(2, 6).unwrap().line, 1);
+}
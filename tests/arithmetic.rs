@@ -0,0 +1,133 @@
+// `CellValue` is `i64` unless the `bigint` feature is enabled
+#![allow(clippy::useless_conversion)]
+
+use ram_machine::arithmetic::{ArithmeticMode, Division, Overflow};
+use ram_machine::interpreter::RamMachine;
+#[cfg(not(feature = "bigint"))]
+use ram_machine::interpreter::RamMachineError;
+use ram_machine::operand::CellValue;
+
+fn division(division: Division) -> ArithmeticMode {
+    ArithmeticMode {
+        division,
+        ..Default::default()
+    }
+}
+
+fn div(mode: ArithmeticMode, a: i64, b: i64) -> Option<CellValue> {
+    mode.div(&a.into(), &b.into())
+}
+
+#[cfg(not(feature = "bigint"))]
+fn overflow(overflow: Overflow) -> ArithmeticMode {
+    ArithmeticMode {
+        overflow,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn negative_division() {
+    // (dividend, divisor, truncating, floor, euclidean)
+    let cases = [
+        (7, 2, 3, 3, 3),
+        (-7, 2, -3, -4, -4),
+        (7, -2, -3, -4, -3),
+        (-7, -2, 3, 3, 4),
+        (-8, 2, -4, -4, -4),
+        (-8, -2, 4, 4, 4),
+        (0, -3, 0, 0, 0),
+    ];
+    for (a, b, truncating, floor, euclidean) in cases {
+        let truncating_mode = division(Division::Truncating);
+        assert_eq!(div(truncating_mode, a, b), Some(truncating.into()));
+        assert_eq!(div(division(Division::Floor), a, b), Some(floor.into()));
+        assert_eq!(
+            div(division(Division::Euclidean), a, b),
+            Some(euclidean.into())
+        );
+    }
+}
+
+#[test]
+fn division_by_zero() {
+    for mode in ["checked", "wrapping,floor", "saturating,euclidean"] {
+        let mode: ArithmeticMode = mode.parse().unwrap();
+        assert_eq!(div(mode, 5, 0), None);
+        assert_eq!(div(mode, -5, 0), None);
+    }
+}
+
+// Big integers never overflow, see tests/bigint.rs
+#[cfg(not(feature = "bigint"))]
+#[test]
+fn overflowing() {
+    let checked = overflow(Overflow::Checked);
+    let wrapping = overflow(Overflow::Wrapping);
+    let saturating = overflow(Overflow::Saturating);
+
+    assert_eq!(checked.add(&i64::MAX, &1), None);
+    assert_eq!(wrapping.add(&i64::MAX, &1), Some(i64::MIN));
+    assert_eq!(saturating.add(&i64::MAX, &1), Some(i64::MAX));
+
+    assert_eq!(checked.sub(&i64::MIN, &1), None);
+    assert_eq!(wrapping.sub(&i64::MIN, &1), Some(i64::MAX));
+    assert_eq!(saturating.sub(&i64::MIN, &1), Some(i64::MIN));
+
+    assert_eq!(checked.mul(&i64::MIN, &-1), None);
+    assert_eq!(wrapping.mul(&i64::MIN, &-1), Some(i64::MIN));
+    assert_eq!(saturating.mul(&i64::MIN, &-2), Some(i64::MAX));
+
+    assert_eq!(checked.div(&i64::MIN, &-1), None);
+    assert_eq!(wrapping.div(&i64::MIN, &-1), Some(i64::MIN));
+    assert_eq!(saturating.div(&i64::MIN, &-1), Some(i64::MAX));
+
+    assert_eq!(saturating.sub(&-5, &-7), Some(2));
+    assert_eq!(wrapping.mul(&-3, &4), Some(-12));
+}
+
+#[test]
+fn parse_arithmetic_mode() {
+    assert_eq!(
+        "saturating,floor".parse(),
+        Ok(ArithmeticMode {
+            overflow: Overflow::Saturating,
+            division: Division::Floor,
+        })
+    );
+    assert_eq!("euclidean".parse(), Ok(division(Division::Euclidean)));
+    assert!("wrapping,round".parse::<ArithmeticMode>().is_err());
+    assert_eq!(ArithmeticMode::default().to_string(), "checked,truncating");
+}
+
+#[test]
+fn machine_arithmetic_mode() {
+    let code = "read 0\ndiv =2\nwrite 0";
+    let run = |mode: &str| {
+        RamMachine::from_str(code, vec![CellValue::from(-7)])
+            .unwrap()
+            .with_arithmetic_mode(mode.parse().unwrap())
+            .run()
+            .unwrap()
+    };
+    assert_eq!(run("wrapping"), vec![CellValue::from(-3)]);
+    assert_eq!(run("saturating,floor"), vec![CellValue::from(-4)]);
+}
+
+#[cfg(not(feature = "bigint"))]
+#[test]
+fn machine_overflow_mode() {
+    let code = "read 0\ndiv =2\nwrite 0\nread 0\nadd =1\nwrite 0";
+    let run = |mode: &str| {
+        RamMachine::from_str(code, vec![-7, i64::MAX])
+            .unwrap()
+            .with_arithmetic_mode(mode.parse().unwrap())
+            .run()
+    };
+    assert_eq!(
+        run("checked").unwrap_err().error,
+        RamMachineError::AdditionFailed(1, i64::MAX)
+    );
+    assert_eq!(run("wrapping").unwrap(), vec![-3, i64::MIN]);
+    assert_eq!(run("saturating,floor").unwrap(), vec![-4, i64::MAX]);
+}
@@ -0,0 +1,83 @@
+#![cfg(feature = "bigint")]
+
+use std::io::Cursor;
+
+use num_bigint::BigInt;
+use ram_machine::arithmetic::ArithmeticMode;
+use ram_machine::instruction::Instruction;
+use ram_machine::interpreter::RamMachine;
+use ram_machine::operand::Operand;
+use ram_machine::parser::RamCode;
+use ram_machine::stats::value_cost;
+use ram_machine::stream::ReaderSource;
+use std::str::FromStr;
+
+const FACTORIAL: &str = "
+      read 1
+      load =1
+      store 2
+LOOP: load 1
+      jzero END
+      mult 2
+      store 2
+      load 1
+      sub =1
+      store 1
+      jump LOOP
+END:  write 2
+      halt
+";
+
+fn big(s: &str) -> BigInt {
+    s.parse().unwrap()
+}
+
+#[test]
+fn factorial_beyond_i64() {
+    let output = RamMachine::from_str(FACTORIAL, vec![BigInt::from(30)])
+        .unwrap()
+        .run()
+        .unwrap();
+    assert_eq!(output, vec![big("265252859812191058636308480000000")]);
+}
+
+#[test]
+fn parse_long_literals() {
+    let code = RamCode::from_str("load =-123456789012345678901234567890").unwrap();
+    assert_eq!(
+        code.instructions,
+        vec![Instruction::Load(Operand::Number(big(
+            "-123456789012345678901234567890"
+        )))]
+    );
+}
+
+#[test]
+fn stream_big_input() {
+    let output = RamMachine::from_str("read 0\nadd 0\nwrite 0", Vec::new())
+        .unwrap()
+        .with_input_source(ReaderSource::new(Cursor::new("99999999999999999999")))
+        .run()
+        .unwrap();
+    assert_eq!(output, vec![big("199999999999999999998")]);
+}
+
+#[test]
+fn big_value_cost() {
+    assert_eq!(value_cost(&BigInt::from(0)), 1);
+    assert_eq!(value_cost(&BigInt::from(-8)), 4);
+    assert_eq!(value_cost(&(BigInt::from(1) << 100)), 101);
+}
+
+#[test]
+fn big_floor_division() {
+    let mode: ArithmeticMode = "wrapping,floor".parse().unwrap();
+    assert_eq!(
+        mode.div(&big("-100000000000000000001"), &BigInt::from(10)),
+        Some(big("-10000000000000000001"))
+    );
+    assert_eq!(
+        mode.add(&big("9223372036854775807"), &BigInt::from(1)),
+        Some(big("9223372036854775808"))
+    );
+}
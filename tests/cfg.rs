@@ -0,0 +1,90 @@
+use std::str::FromStr;
+
+use ram_machine::cfg::{ControlFlowGraph, Edge, EdgeKind};
+use ram_machine::parser::RamCode;
+
+const SEQUENCE_LENGTH: &str = "
+      load =0
+      store 1
+LOOP: read 0
+      jzero END
+      load =1
+      add 1
+      store 1
+      jump LOOP
+END:  write 1
+";
+
+#[test]
+fn basic_blocks() {
+    let code = RamCode::from_str(SEQUENCE_LENGTH).unwrap();
+    let graph = ControlFlowGraph::new(&code);
+    let blocks: Vec<_> = graph
+        .blocks
+        .iter()
+        .map(|block| (block.instructions.clone(), block.labels.clone()))
+        .collect();
+    assert_eq!(
+        blocks,
+        [
+            (0..2, vec![]),
+            (2..4, vec!["LOOP".to_owned()]),
+            (4..8, vec![]),
+            (8..9, vec!["END".to_owned()]),
+        ]
+    );
+    let edge = |from, to, kind| Edge { from, to, kind };
+    assert_eq!(
+        graph.edges,
+        [
+            edge(0, 1, EdgeKind::Unconditional),
+            edge(1, 3, EdgeKind::True),
+            edge(1, 2, EdgeKind::False),
+            edge(2, 1, EdgeKind::Unconditional),
+        ]
+    );
+    assert_eq!(graph.block_of(5), Some(2));
+    assert_eq!(graph.predecessors(1).count(), 2);
+}
+
+#[test]
+fn halt_ends_block() {
+    let code = RamCode::from_str("read 0\nhalt\nwrite 0\n").unwrap();
+    let graph = ControlFlowGraph::new(&code);
+    assert_eq!(graph.blocks.len(), 2);
+    assert!(graph.edges.is_empty());
+}
+
+#[test]
+fn export() {
+    let code = RamCode::from_str(SEQUENCE_LENGTH).unwrap();
+    let graph = ControlFlowGraph::new(&code);
+
+    let dot = graph.to_dot(&code);
+    assert!(dot.starts_with("digraph cfg {\n"));
+    assert!(dot.contains("b1 [label=\"LOOP:\\lread 0\\ljzero END\\l\"];"));
+    assert!(dot.contains("b1 -> b3 [label=\"true\"];"));
+    assert!(dot.contains("b1 -> b2 [label=\"false\"];"));
+    assert!(dot.contains("b3 -> exit;"));
+
+    let mermaid = graph.to_mermaid(&code);
+    assert!(mermaid.starts_with("flowchart TD\n"));
+    assert!(mermaid.contains("b1[\"LOOP:<br/>read 0<br/>jzero END\"]"));
+    assert!(mermaid.contains("b1 -->|true| b3"));
+    assert!(mermaid.contains("entry --> b0"));
+}
+
+#[test]
+fn conditional_jump_at_the_end() {
+    let code = RamCode::from_str("L: read 0\njgtz L\n").unwrap();
+    let graph = ControlFlowGraph::new(&code);
+    assert_eq!(
+        graph.edges,
+        [Edge {
+            from: 0,
+            to: 0,
+            kind: EdgeKind::True
+        }]
+    );
+    assert!(graph.to_mermaid(&code).contains("b0 -->|false| exit"));
+}
@@ -0,0 +1,111 @@
+// `CellValue` is `i64` unless the `bigint` feature is enabled
+#![allow(clippy::useless_conversion)]
+
+use std::fs;
+use std::time::Duration;
+
+use ram_machine::arithmetic::ArithmeticMode;
+use ram_machine::compiled::{CompiledCode, CompiledMachine};
+use ram_machine::instruction::Dialect;
+use ram_machine::interpreter::{RamMachine, RamMachineError};
+use ram_machine::limits::Limits;
+use ram_machine::locale::Lang;
+use ram_machine::operand::CellValue;
+use ram_machine::parser::{ParseOptions, RamCode};
+
+const LENIENT: ParseOptions = ParseOptions {
+    lenient: true,
+    dialect: Dialect::Extended,
+    keywords: Lang::En,
+};
+
+/// Runs the program with both engines and checks that they agree.
+fn assert_same(source: &str, input: &[i64]) {
+    let code = RamCode::parse(source, LENIENT).unwrap();
+    let compiled = CompiledCode::new(&code);
+    let input: Vec<CellValue> = input.iter().map(|&v| v.into()).collect();
+    let expected = RamMachine::new(code, input.clone())
+        .with_max_steps(10_000)
+        .run();
+    let actual = CompiledMachine::new(&compiled, input)
+        .with_max_steps(10_000)
+        .run();
+    assert_eq!(actual, expected, "{source}");
+}
+
+#[test]
+fn examples() {
+    let inputs: [&[i64]; 5] = [&[], &[0], &[7, 3, 5], &[-12, 4, 1], &[1024, 2, 3]];
+    for entry in fs::read_dir("examples").unwrap() {
+        let source = fs::read_to_string(entry.unwrap().path()).unwrap();
+        for input in inputs {
+            assert_same(&source, input);
+        }
+    }
+}
+
+#[test]
+fn errors() {
+    for source in [
+        "write 1\n",
+        "add =1\n",
+        "store 1\n",
+        "read 1\nwrite ^1\n",
+        "read 1\nstore ^1\n",
+        "read 0\ndiv =0\n",
+        "read 0\nmod 0\n",
+        "read 0\njgtz MISSING\nhalt\n",
+        "read 0\njump MISSING\n",
+        "A: jump A\n",
+        "read 0\nread 0\n",
+    ] {
+        assert_same(source, &[-3]);
+        assert_same(source, &[4]);
+    }
+}
+
+#[test]
+fn limits() {
+    let code = RamCode::parse("A: jump A\n", LENIENT).unwrap();
+    let compiled = CompiledCode::new(&code);
+    let limits = Limits::new(Some(100), Some(Duration::from_secs(60)));
+    let expected = RamMachine::new(code, Vec::new())
+        .with_limits(limits.clone())
+        .run()
+        .map_err(|e| e.error);
+    let actual = CompiledMachine::new(&compiled, Vec::new())
+        .with_limits(limits)
+        .run()
+        .map_err(|e| e.error);
+    assert_eq!(actual, expected);
+    assert_eq!(actual, Err(RamMachineError::StepLimitExceeded(100)));
+}
+
+#[test]
+fn extended() {
+    assert_same(
+        "read 1\nload =0\nLOOP: add 1\ninc\ndec\nstore 2\nload 1\ndec\nstore 1\njnzero NEXT\njump END\nNEXT: load 2\njump LOOP\nEND: write 2\nwrite \"done\"\n",
+        &[5],
+    );
+    assert_same("read 0\njneg N\njgez END\nN: write 0\nEND: halt\n", &[-1]);
+    assert_same("read 0\njneg N\njgez END\nN: write 0\nEND: halt\n", &[1]);
+}
+
+#[test]
+fn arithmetic_mode() {
+    let code = RamCode::parse("read 0\nmult =2\ndiv =-3\nwrite 0\n", LENIENT).unwrap();
+    let compiled = CompiledCode::new(&code);
+    for mode in ["checked", "wrapping,floor", "saturating,euclidean"] {
+        let mode: ArithmeticMode = mode.parse().unwrap();
+        for input in [i64::MAX, -7] {
+            assert_eq!(
+                CompiledMachine::new(&compiled, vec![input.into()])
+                    .with_arithmetic_mode(mode)
+                    .run(),
+                RamMachine::new(code.clone(), vec![input.into()])
+                    .with_arithmetic_mode(mode)
+                    .run()
+            );
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use std::fs;
+
+use ram_machine::parser::cst::{Cst, TokenKind};
+
+#[test]
+fn round_trip() {
+    for entry in fs::read_dir("examples").unwrap() {
+        let source = fs::read_to_string(entry.unwrap().path()).unwrap();
+        assert_eq!(Cst::parse(&source).to_string(), source);
+    }
+    let messy = "#!dialect extended\r\n\tloop:\tWRITE \"a # b\"  # x\r\n\n  load 1 add 2\nhalt";
+    assert_eq!(Cst::parse(messy).to_string(), messy);
+}
+
+#[test]
+fn token_kinds() {
+    let cst = Cst::parse("loop: load =1 # one\n");
+    let kinds: Vec<TokenKind> = cst.tokens().map(|t| t.kind).collect();
+    use TokenKind::*;
+    assert_eq!(
+        kinds,
+        [Label, Whitespace, Keyword, Whitespace, Argument, Whitespace, Comment, Newline]
+    );
+    let line = &cst.lines[0];
+    assert_eq!(line.label(), Some("loop"));
+    assert_eq!(line.argument().map(|t| t.text), Some("=1"));
+
+    let cst = Cst::parse("#!keywords pl\nwrite \"x y\" 2\n");
+    assert_eq!(cst.lines[0].pragma(), Some(("keywords", "pl")));
+    let kinds: Vec<TokenKind> = cst.lines[1].code().map(|t| t.kind).collect();
+    assert_eq!(kinds, [Keyword, String, Unexpected]);
+}
+
+#[test]
+fn spans() {
+    let source = "read 0\n  l: jump l # back\r\nhalt";
+    let cst = Cst::parse(source);
+    for token in cst.tokens() {
+        assert_eq!(&source[token.span.clone()], token.text);
+    }
+    assert_eq!(cst.lines[1].number, 2);
+    assert_eq!(cst.lines[1].start, 7);
+}
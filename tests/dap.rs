@@ -0,0 +1,204 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+use serde_json::{json, Value};
+
+/// `ram dap` running as a child process.
+struct Adapter {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    seq: u64,
+}
+
+impl Adapter {
+    fn start() -> Self {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_ram"))
+            .arg("dap")
+            .env("LC_ALL", "C")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        Adapter {
+            child,
+            stdin,
+            stdout,
+            seq: 0,
+        }
+    }
+
+    fn write_raw(&mut self, bytes: &[u8]) {
+        self.stdin.write_all(bytes).unwrap();
+        self.stdin.flush().unwrap();
+    }
+
+    fn send(&mut self, command: &str, arguments: Value) {
+        self.seq += 1;
+        let body = json!({
+            "seq": self.seq,
+            "type": "request",
+            "command": command,
+            "arguments": arguments,
+        })
+        .to_string();
+        self.write_raw(format!("Content-Length: {}\r\n\r\n{body}", body.len()).as_bytes());
+    }
+
+    /// The next message, checking that its header gives the length of the body in bytes.
+    fn receive(&mut self) -> Value {
+        let mut header = String::new();
+        self.stdout.read_line(&mut header).unwrap();
+        let length: usize = header
+            .strip_prefix("Content-Length: ")
+            .and_then(|length| length.trim_end().parse().ok())
+            .unwrap_or_else(|| panic!("unexpected header {header:?}"));
+        let mut separator = String::new();
+        self.stdout.read_line(&mut separator).unwrap();
+        assert_eq!(separator, "\r\n");
+        let mut body = vec![0; length];
+        self.stdout.read_exact(&mut body).unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    /// Sends the request, returns its response followed by `events` events.
+    fn request(&mut self, command: &str, arguments: Value, events: usize) -> Vec<Value> {
+        self.send(command, arguments);
+        let messages: Vec<Value> = (0..=events).map(|_| self.receive()).collect();
+        assert_eq!(messages[0]["type"], "response");
+        assert_eq!(messages[0]["command"], command);
+        assert_eq!(messages[0]["request_seq"], self.seq);
+        messages
+    }
+
+    fn launch(&mut self, arguments: Value) {
+        self.request("initialize", json!({ "adapterID": "ram" }), 1);
+        let response = &self.request("launch", arguments, 0)[0];
+        assert_eq!(response["success"], true, "{response}");
+    }
+
+    fn disconnect(mut self) {
+        self.request("disconnect", Value::Null, 0);
+        assert!(self.child.wait().unwrap().success());
+    }
+}
+
+fn event(message: &Value) -> &str {
+    message["event"].as_str().unwrap_or_default()
+}
+
+#[test]
+fn framing() {
+    let mut adapter = Adapter::start();
+    // Header names are case-insensitive and other headers are ignored
+    let body = json!({ "seq": 1, "type": "request", "command": "initialize" }).to_string();
+    adapter.write_raw(
+        format!(
+            "content-length: {}\r\nContent-Type: application/vscode-jsonrpc\r\n\r\n{body}",
+            body.len()
+        )
+        .as_bytes(),
+    );
+    adapter.seq = 1;
+    let response = adapter.receive();
+    assert_eq!(response["success"], true);
+    assert_eq!(response["seq"], 1);
+    let initialized = adapter.receive();
+    assert_eq!(event(&initialized), "initialized");
+    assert_eq!(initialized["seq"], 2);
+
+    // The length counts bytes, not characters
+    let response = &adapter.request("launch", json!({ "program": "żółw.ram" }), 0)[0];
+    assert_eq!(response["success"], false);
+    assert!(response["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("Could not read ram machine code from file"));
+
+    let response = &adapter.request("evaluate", json!({}), 0)[0];
+    assert_eq!(response["success"], false);
+    assert_eq!(response["message"], "Unsupported request `evaluate`");
+    adapter.disconnect();
+}
+
+#[test]
+fn missing_content_length() {
+    let mut adapter = Adapter::start();
+    adapter.write_raw(b"Content-Type: application/json\r\n\r\n{}");
+    assert!(!adapter.child.wait().unwrap().success());
+}
+
+#[test]
+fn breakpoints() {
+    let mut adapter = Adapter::start();
+    adapter.launch(json!({
+        "program": "./examples/three_sum.ram",
+        "input": [1, 2, "3"],
+    }));
+    let breakpoints = &adapter.request(
+        "setBreakpoints",
+        json!({ "breakpoints": [{ "line": 5 }, { "line": 100 }] }),
+        0,
+    )[0]["body"]["breakpoints"];
+    assert_eq!(breakpoints[0], json!({ "verified": true, "line": 5 }));
+    assert_eq!(breakpoints[1]["verified"], false);
+
+    let messages = adapter.request("configurationDone", Value::Null, 1);
+    assert_eq!(event(&messages[1]), "stopped");
+    assert_eq!(messages[1]["body"]["reason"], "breakpoint");
+    let frames = &adapter.request("stackTrace", json!({ "threadId": 1 }), 0)[0];
+    assert_eq!(frames["body"]["stackFrames"][0]["line"], 5);
+
+    let messages = adapter.request("next", json!({ "threadId": 1 }), 1);
+    assert_eq!(messages[1]["body"]["reason"], "step");
+    let variables = &adapter.request("variables", json!({ "variablesReference": 1 }), 0)[0];
+    assert_eq!(
+        variables["body"]["variables"][0],
+        json!({ "name": "[0]", "value": "3", "variablesReference": 0 })
+    );
+
+    let messages = adapter.request("continue", json!({ "threadId": 1 }), 3);
+    assert_eq!(messages[0]["body"]["allThreadsContinued"], true);
+    assert_eq!(event(&messages[1]), "output");
+    assert!(messages[1]["body"]["output"]
+        .as_str()
+        .unwrap()
+        .contains('6'));
+    assert_eq!(event(&messages[2]), "exited");
+    assert_eq!(event(&messages[3]), "terminated");
+    adapter.disconnect();
+}
+
+#[test]
+fn breakpoint_after_last_instruction() {
+    let mut adapter = Adapter::start();
+    adapter.launch(json!({ "program": "./tests/fixtures/line_label.ram" }));
+    let breakpoints = &adapter.request(
+        "setBreakpoints",
+        json!({ "breakpoints": [{ "line": 2 }, { "line": 3 }] }),
+        0,
+    )[0]["body"]["breakpoints"];
+    assert_eq!(breakpoints[0], json!({ "verified": true, "line": 2 }));
+    assert_eq!(breakpoints[1]["verified"], false);
+    adapter.disconnect();
+}
+
+#[test]
+fn pause() {
+    let mut adapter = Adapter::start();
+    adapter.launch(json!({ "program": "./tests/fixtures/endless.ram" }));
+    // The program runs after the response, requests are still answered while it does
+    adapter.request("configurationDone", Value::Null, 0);
+    adapter.request("threads", Value::Null, 0);
+    let messages = adapter.request("pause", json!({ "threadId": 1 }), 1);
+    assert_eq!(messages[1]["body"]["reason"], "pause");
+
+    adapter.request("continue", json!({ "threadId": 1 }), 0);
+    let messages = adapter.request("pause", json!({ "threadId": 1 }), 1);
+    assert_eq!(messages[1]["body"]["reason"], "pause");
+    // Pausing a program which is not running does nothing
+    adapter.request("pause", json!({ "threadId": 1 }), 0);
+    adapter.disconnect();
+}
@@ -0,0 +1,58 @@
+use std::str::FromStr;
+
+use ram_machine::dataflow::{DefiniteAssignment, Initialization};
+use ram_machine::locale::Lang;
+use ram_machine::parser::RamCode;
+
+fn uses(source: &str) -> Vec<(usize, usize, Initialization)> {
+    let code = RamCode::from_str(source).unwrap();
+    DefiniteAssignment::new(&code)
+        .uses
+        .iter()
+        .map(|cell_use| (cell_use.instruction, cell_use.cell, cell_use.initialization))
+        .collect()
+}
+
+#[test]
+fn initialization() {
+    use Initialization::*;
+    assert_eq!(
+        uses("read 1\nload 1\nadd 2\nhalt\n"),
+        [(1, 1, Always), (2, 0, Always), (2, 2, Never)]
+    );
+    assert_eq!(
+        uses("read 1\nload 1\njzero A\nstore 2\nA: write 2\nhalt\n"),
+        [
+            (1, 1, Always),
+            (2, 0, Always),
+            (3, 0, Always),
+            (4, 2, Sometimes)
+        ]
+    );
+    // Any cell may be set through a computed address
+    assert_eq!(
+        uses("read 1\nread ^1\nwrite 5\nhalt\n"),
+        [(1, 1, Always), (2, 5, Sometimes)]
+    );
+    // Stores inside a loop reach its start on the second pass
+    assert_eq!(uses("A: write 1\nread 1\njump A\n"), [(0, 1, Sometimes)]);
+}
+
+#[test]
+fn unset_path() {
+    let source = "read 1\nload 1\njzero A\nstore 2\nA: write 2\nhalt\n";
+    let code = RamCode::from_str(source).unwrap();
+    let analysis = DefiniteAssignment::new(&code);
+    let unsafe_uses: Vec<_> = analysis.unsafe_uses().collect();
+    assert_eq!(unsafe_uses.len(), 1);
+    assert_eq!(analysis.unset_path(unsafe_uses[0]), Some(vec![0, 1, 2, 4]));
+
+    let diagnostics = analysis.diagnostics(&code, source, Lang::En);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "E0014");
+    assert_eq!(diagnostics[0].span.line, 5);
+    assert_eq!(
+        diagnostics[0].help.as_deref(),
+        Some("it is unset when the program goes through lines 1 → 2 → 3 → 5")
+    );
+}
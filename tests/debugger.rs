@@ -0,0 +1,177 @@
+use ram_machine::debugger::{
+    Breakpoint, Comparison, Condition, ConditionOperand, Debugger, DebuggerError, StopReason,
+};
+use ram_machine::interpreter::RamMachine;
+use ram_machine::operand::CellValue;
+use ram_machine::parser::RamCode;
+use std::str::FromStr;
+
+const COUNTDOWN: &str = "
+load =3
+store 1
+LOOP: load 1
+      sub =1
+      store 1
+      jgtz LOOP
+write 1
+halt
+";
+
+fn debugger() -> Debugger {
+    Debugger::new(RamMachine::new(
+        RamCode::from_str(COUNTDOWN).unwrap(),
+        vec![],
+    ))
+}
+
+#[test]
+fn parse_condition() {
+    assert_eq!(
+        Condition::from_str("[2] == 0"),
+        Ok(Condition {
+            left: ConditionOperand::Cell(2),
+            comparison: Comparison::Equal,
+            right: ConditionOperand::Number(CellValue::from(0)),
+        })
+    );
+    assert_eq!(
+        Condition::from_str("-1>=[10]"),
+        Ok(Condition {
+            left: ConditionOperand::Number(CellValue::from(-1)),
+            comparison: Comparison::GreaterOrEqual,
+            right: ConditionOperand::Cell(10),
+        })
+    );
+    assert_eq!(
+        Condition::from_str("[2] = 0"),
+        Err(DebuggerError::InvalidCondition("[2] = 0".to_owned()))
+    );
+}
+
+#[test]
+fn resolve_location() {
+    let debugger = debugger();
+    assert_eq!(debugger.resolve_location("LOOP"), Ok(2));
+    assert_eq!(debugger.resolve_location("5"), Ok(3));
+    assert_eq!(debugger.resolve_location("1"), Ok(0));
+    assert_eq!(
+        debugger.resolve_location("END"),
+        Err(DebuggerError::LocationNotFound("END".to_owned()))
+    );
+}
+
+#[test]
+fn resolve_line() {
+    let debugger = debugger();
+    assert_eq!(debugger.resolve_line(5), Ok(3));
+    assert_eq!(
+        debugger.resolve_line(100),
+        Err(DebuggerError::LocationNotFound("100".to_owned()))
+    );
+    // A label named like a line number doesn't take over the line
+    let code = RamCode::from_str("LOAD =1\nJUMP 3\n3:\n").unwrap();
+    let debugger = Debugger::new(RamMachine::new(code, vec![]));
+    assert_eq!(debugger.resolve_location("3"), Ok(2));
+    assert_eq!(
+        debugger.resolve_line(3),
+        Err(DebuggerError::LocationNotFound("3".to_owned()))
+    );
+}
+
+#[test]
+fn step() {
+    let mut debugger = debugger();
+    assert_eq!(debugger.step(3), StopReason::Step);
+    assert_eq!(debugger.machine().pointer(), 3);
+    assert_eq!(debugger.step(100), StopReason::Halted);
+    assert!(debugger.is_halted());
+    assert_eq!(debugger.machine().output(), [CellValue::from(0)]);
+}
+
+#[test]
+fn conditional_breakpoint() {
+    let mut debugger = debugger();
+    let location = debugger.resolve_location("LOOP").unwrap();
+    debugger.add_breakpoint(Breakpoint {
+        location,
+        condition: Some(Condition::from_str("[1] == 1").unwrap()),
+    });
+    assert_eq!(debugger.resume(), StopReason::Breakpoint(0));
+    assert_eq!(debugger.machine().pointer(), 2);
+    assert_eq!(debugger.machine().cell(1), Some(CellValue::from(1)));
+    assert_eq!(debugger.resume(), StopReason::Halted);
+}
+
+#[test]
+fn clear_breakpoints() {
+    let mut debugger = debugger();
+    let location = debugger.resolve_location("4").unwrap();
+    debugger.add_breakpoint(Breakpoint {
+        location,
+        condition: None,
+    });
+    assert_eq!(debugger.resume(), StopReason::Breakpoint(0));
+    debugger.clear_breakpoints();
+    assert!(debugger.breakpoints().is_empty());
+    assert_eq!(debugger.resume(), StopReason::Halted);
+}
+
+#[test]
+fn watchpoint() {
+    let mut debugger = debugger();
+    debugger.add_watchpoint(1);
+    assert_eq!(
+        debugger.resume(),
+        StopReason::Watchpoint(1, None, Some(CellValue::from(3)))
+    );
+    assert_eq!(
+        debugger.resume(),
+        StopReason::Watchpoint(1, Some(CellValue::from(3)), Some(CellValue::from(2)))
+    );
+    assert!(debugger.remove_watchpoint(1));
+    debugger.add_breakpoint(Breakpoint {
+        location: 0,
+        condition: None,
+    });
+    assert_eq!(debugger.finish(), StopReason::Halted);
+}
+
+#[test]
+fn bounded_runs() {
+    let code = RamCode::from_str("LOOP: jump LOOP").unwrap();
+    let mut endless = Debugger::new(RamMachine::new(code, vec![]));
+    assert_eq!(endless.resume_for(100), None);
+    assert_eq!(endless.finish_for(50), None);
+    assert_eq!(endless.machine().steps(), 150);
+
+    let mut debugger = debugger();
+    assert_eq!(debugger.finish_for(1000), Some(StopReason::Halted));
+    assert_eq!(debugger.resume_for(1), Some(StopReason::Halted));
+}
+
+#[test]
+fn reverse_execution() {
+    let machine = RamMachine::new(RamCode::from_str(COUNTDOWN).unwrap(), vec![]).with_history(100);
+    let mut debugger = Debugger::new(machine);
+    debugger.add_breakpoint(Breakpoint {
+        location: 2,
+        condition: None,
+    });
+    assert_eq!(debugger.finish(), StopReason::Halted);
+    assert_eq!(debugger.machine().steps(), 16);
+
+    assert_eq!(debugger.step_back(2), StopReason::Step);
+    assert!(!debugger.is_halted());
+    assert!(debugger.machine().output().is_empty());
+    assert_eq!(debugger.reverse_resume(), StopReason::Breakpoint(0));
+    assert_eq!(debugger.machine().cell(1), Some(CellValue::from(1)));
+    assert_eq!(debugger.reverse_resume(), StopReason::Breakpoint(0));
+    assert_eq!(debugger.machine().cell(1), Some(CellValue::from(2)));
+
+    assert_eq!(debugger.go_to(1), StopReason::Step);
+    assert_eq!(debugger.machine().cell(1), None);
+    assert_eq!(debugger.go_to(15), StopReason::Step);
+    assert_eq!(debugger.machine().output(), [CellValue::from(0)]);
+    assert_eq!(debugger.step_back(100), StopReason::StartOfHistory);
+    assert_eq!(debugger.machine().steps(), 0);
+}
@@ -0,0 +1,95 @@
+use ram_machine::diagnostic::{Diagnostic, Severity};
+use ram_machine::parser::{ParseOptions, RamCode};
+
+fn diagnostics(code: &str) -> Vec<Diagnostic> {
+    RamCode::parse_all(code, ParseOptions::default())
+        .unwrap_err()
+        .diagnostics(code)
+}
+
+#[test]
+fn misspelled_keyword() {
+    let diagnostics = diagnostics("read 1\nLOOP: jzeor END\n");
+    assert_eq!(diagnostics.len(), 1);
+    let diagnostic = &diagnostics[0];
+    assert_eq!(diagnostic.severity, Severity::Error);
+    assert_eq!(diagnostic.code, "E0001");
+    assert_eq!(diagnostic.span.line, 2);
+    assert_eq!(diagnostic.span.text, "jzeor");
+    assert_eq!(diagnostic.column(), 7);
+    assert_eq!(diagnostic.help.as_deref(), Some("did you mean `jzero`?"));
+}
+
+#[test]
+fn no_suggestion_for_unrelated_keyword() {
+    let diagnostics = diagnostics("banana 1");
+    assert_eq!(diagnostics[0].help, None);
+}
+
+#[test]
+fn underline_operand_and_trailing_argument() {
+    let diagnostics = diagnostics("store =3\nload 1 add 2 # comment\nhalt now");
+    let codes: Vec<(&str, &str)> = diagnostics
+        .iter()
+        .map(|d| (d.code, d.span.text.as_str()))
+        .collect();
+    assert_eq!(
+        codes,
+        vec![("E0003", "=3"), ("E0006", "add"), ("E0005", "now")]
+    );
+}
+
+#[test]
+fn render() {
+    let diagnostics = diagnostics("\tlaod 1");
+    assert_eq!(
+        diagnostics[0].render("file.ram"),
+        "error[E0001]: Keyword `laod` is not a valid keyword
+ --> file.ram:1:2
+  |
+1 | \tlaod 1
+  | \t^^^^
+  |
+  = help: did you mean `load`?
+"
+    );
+}
+
+#[test]
+fn json() {
+    let diagnostics = diagnostics("jump");
+    assert_eq!(
+        diagnostics[0].to_json("file.ram"),
+        serde_json::json!({
+            "file": "file.ram",
+            "severity": "error",
+            "code": "E0002",
+            "message": "Expected a label after keyword jump, got nothing",
+            "line": 1,
+            "column": 1,
+            "end_column": 5,
+            "text": "jump",
+            "help": "add the label to jump to, e.g. `JUMP LOOP`",
+        })
+    );
+}
+
+#[test]
+fn validation() {
+    let diagnostics = diagnostics("A: read 1\nA: jzero B\n");
+    let codes: Vec<(&str, usize, &str)> = diagnostics
+        .iter()
+        .map(|d| (d.code, d.span.line, d.span.text.as_str()))
+        .collect();
+    assert_eq!(codes, vec![("E0007", 2, "A"), ("E0008", 2, "B")]);
+}
+
+#[test]
+fn extended_keyword_in_strict_dialect() {
+    let diagnostics = diagnostics("read 0\nmod =2\n");
+    assert_eq!(diagnostics[0].code, "E0001");
+    assert_eq!(
+        diagnostics[0].help.as_deref(),
+        Some("`mod` is part of the extended dialect, enable it with `#!dialect extended`")
+    );
+}
@@ -0,0 +1,172 @@
+// `CellValue` is `i64` unless the `bigint` feature is enabled
+#![allow(clippy::useless_conversion)]
+
+use num_traits::ToPrimitive;
+use ram_machine::arithmetic::{ArithmeticMode, Division};
+use ram_machine::instruction::{Dialect, Instruction, InstructionParseError};
+use ram_machine::interpreter::RamMachine;
+use ram_machine::locale::Lang;
+use ram_machine::operand::{CellValue, Operand::*};
+use ram_machine::parser::{CodeParseError, ParseOptions, RamCode};
+use ram_machine::stream::WriterSink;
+use std::sync::{Arc, Mutex};
+
+const EXTENDED: ParseOptions = ParseOptions {
+    lenient: false,
+    dialect: Dialect::Extended,
+    keywords: Lang::En,
+};
+
+fn run(code: &str, input: Vec<i64>) -> Vec<i64> {
+    let code = RamCode::parse(code, EXTENDED).unwrap();
+    let input = input.into_iter().map(CellValue::from).collect();
+    let output = RamMachine::new(code, input).run().unwrap();
+    output.iter().map(|v| v.to_i64().unwrap()).collect()
+}
+
+#[test]
+fn strict_rejects_extended_keywords() {
+    for line in [
+        "mod =2",
+        "jneg end",
+        "JLTZ end",
+        "jgez end",
+        "jnzero end",
+        "inc",
+        "dec",
+    ] {
+        assert_eq!(
+            RamCode::parse(line, ParseOptions::default()).unwrap_err(),
+            CodeParseError::InstructionParseError(InstructionParseError::InvalidKeyword(
+                line.split_whitespace().next().unwrap().to_owned()
+            )),
+        );
+    }
+    assert!(RamCode::parse("write \"hi\"", ParseOptions::default()).is_err());
+}
+
+#[test]
+fn pragma_enables_extended_dialect() {
+    let code = "#!dialect extended\nload =7\nmod =3\nwrite 0\nhalt\n";
+    let code = RamCode::parse(code, ParseOptions::default()).unwrap();
+    assert_eq!(code.dialect, Dialect::Extended);
+    assert_eq!(
+        code.instructions[1],
+        Instruction::Mod(Number(CellValue::from(3)))
+    );
+    assert_eq!(
+        RamMachine::new(code, Vec::new()).run(),
+        Ok(vec![CellValue::from(1)])
+    );
+
+    assert_eq!(
+        RamCode::parse("#!dialect klingon", ParseOptions::default())
+            .unwrap_err()
+            .to_string(),
+        "`klingon` is not a dialect, expected `strict` or `extended`"
+    );
+}
+
+#[test]
+fn conditional_jumps() {
+    let code = "
+read 0
+jneg negative
+jnzero positive
+write =0
+halt
+negative: write =-1
+halt
+positive: jgez taken
+halt
+taken: write =1
+";
+    assert_eq!(run(code, vec![-5]), vec![-1]);
+    assert_eq!(run(code, vec![0]), vec![0]);
+    assert_eq!(run(code, vec![5]), vec![1]);
+}
+
+#[test]
+fn inc_dec_and_mod() {
+    let code = "
+read 0
+loop: write 0
+dec
+jgtz loop
+inc
+inc
+mod =2
+write 0
+";
+    assert_eq!(run(code, vec![3]), vec![3, 2, 1, 0]);
+
+    let code = RamCode::parse("load =-7\nmod =2\nwrite 0", EXTENDED).unwrap();
+    let floor = ArithmeticMode {
+        division: Division::Floor,
+        ..Default::default()
+    };
+    assert_eq!(
+        RamMachine::new(code.clone(), Vec::new()).run(),
+        Ok(vec![CellValue::from(-1)])
+    );
+    assert_eq!(
+        RamMachine::new(code, Vec::new())
+            .with_arithmetic_mode(floor)
+            .run(),
+        Ok(vec![CellValue::from(1)])
+    );
+}
+
+#[test]
+fn write_string() {
+    let source = "write \"sum # \\\"a\\\": \"\nwrite =3\nWRITE \"\\n\" # newline";
+    let code = RamCode::parse(source, EXTENDED).unwrap();
+    assert_eq!(
+        code.instructions[0],
+        Instruction::WriteString("sum # \"a\": ".to_owned())
+    );
+    assert_eq!(
+        code.instructions[0].to_string(),
+        "WRITE \"sum # \\\"a\\\": \""
+    );
+
+    let out = Arc::new(Mutex::new(Vec::new()));
+    let machine = RamMachine::new(code, Vec::new())
+        .with_output_sink(WriterSink::new(SharedBuffer(out.clone())));
+    assert_eq!(machine.run(), Ok(vec![CellValue::from(3)]));
+    assert_eq!(
+        String::from_utf8(out.lock().unwrap().clone()).unwrap(),
+        "sum # \"a\": 3\n\n"
+    );
+
+    assert_eq!(
+        RamCode::parse("write \"open", EXTENDED).unwrap_err(),
+        CodeParseError::InstructionParseError(InstructionParseError::UnterminatedString(
+            "\"open".to_owned()
+        ))
+    );
+}
+
+#[test]
+fn write_string_roundtrip() {
+    let text = "tab\t \"q\" \\ bell\u{7} é # not a comment\n";
+    let code = RamCode {
+        instructions: vec![Instruction::WriteString(text.to_owned()), Instruction::Halt],
+        ..RamCode::parse("halt", EXTENDED).unwrap()
+    };
+    let source = code.to_string();
+    let parsed = RamCode::parse(&source, EXTENDED).unwrap();
+    assert_eq!(parsed.instructions, code.instructions, "{}", source);
+}
+
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl std::io::Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,78 @@
+use std::fs;
+
+use ram_machine::format::{format, Case, FormatOptions};
+use ram_machine::parser::layout::LineLayout;
+
+#[test]
+fn line_layout() {
+    assert_eq!(
+        LineLayout::parse("  loop:  WRITE \"a # b\" # print"),
+        LineLayout {
+            label: Some("loop"),
+            keyword: Some("WRITE"),
+            argument: Some("\"a # b\""),
+            extra: None,
+            comment: Some("# print"),
+        }
+    );
+    assert_eq!(
+        LineLayout::parse("load 1 add 2 #x"),
+        LineLayout {
+            keyword: Some("load"),
+            argument: Some("1"),
+            extra: Some("add 2"),
+            comment: Some("#x"),
+            ..Default::default()
+        }
+    );
+    assert!(LineLayout::parse("   ").is_blank());
+    assert!(!LineLayout::parse("# only a comment").has_code());
+}
+
+#[test]
+fn canonical_form() {
+    let source = "
+
+# Header
+READ 1
+  loop: Jzero end   # done?
+SUB =1
+    jump loop
+
+
+end:
+  HALT
+\t# trailing
+
+";
+    let expected = "# Header
+      read  1
+loop: jzero end # done?
+      sub   =1
+      jump  loop
+
+end:
+      halt
+# trailing
+";
+    let formatted = format(source, FormatOptions::default());
+    assert_eq!(formatted, expected);
+    assert_eq!(format(&formatted, FormatOptions::default()), formatted);
+}
+
+#[test]
+fn upper_case() {
+    let options = FormatOptions { case: Case::Upper };
+    assert_eq!(
+        format("read 0\nwrite 0 # out\nhalt", options),
+        "READ  0\nWRITE 0 # out\nHALT\n"
+    );
+    assert_eq!("UPPER".parse(), Ok(Case::Upper));
+    assert!("title".parse::<Case>().is_err());
+}
+
+#[test]
+fn formatted_fixture() {
+    let source = fs::read_to_string("./tests/fixtures/formatted.ram").unwrap();
+    assert_eq!(format(&source, FormatOptions::default()), source);
+}
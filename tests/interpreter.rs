@@ -1,8 +1,20 @@
-use ram_machine::interpreter::RamMachine;
+// `CellValue` is `i64` unless the `bigint` feature is enabled
+#![allow(clippy::useless_conversion)]
+
+use ram_machine::instruction::Instruction;
+use ram_machine::interpreter::{ExecutionError, RamMachine, RamMachineError, RunState};
+use ram_machine::operand::{CellOperand, CellValue, ExpandError, Operand};
+use ram_machine::parser::source_map::Span;
 use ram_machine::parser::RamCode;
+use ram_machine::stats::RunStats;
 
 use std::fs::read_to_string;
 use std::str::FromStr;
+use std::time::Duration;
+
+fn values(values: &[i64]) -> Vec<CellValue> {
+    values.iter().map(|&v| v.into()).collect()
+}
 
 #[test]
 fn three_sum() {
@@ -15,24 +27,32 @@ fn three_sum() {
     let input4: Vec<i64> = vec![3209847, 16879823, 27034];
     let input5: Vec<i64> = vec![0, 0, 0];
     assert_eq!(
-        RamMachine::new(code.clone(), input1.clone()).run().unwrap(),
-        vec![input1.iter().sum()]
+        RamMachine::new(code.clone(), values(&input1))
+            .run()
+            .unwrap(),
+        values(&[input1.iter().sum::<i64>()])
     );
     assert_eq!(
-        RamMachine::new(code.clone(), input2.clone()).run().unwrap(),
-        vec![input2.iter().sum()]
+        RamMachine::new(code.clone(), values(&input2))
+            .run()
+            .unwrap(),
+        values(&[input2.iter().sum::<i64>()])
     );
     assert_eq!(
-        RamMachine::new(code.clone(), input3.clone()).run().unwrap(),
-        vec![input3.iter().sum()]
+        RamMachine::new(code.clone(), values(&input3))
+            .run()
+            .unwrap(),
+        values(&[input3.iter().sum::<i64>()])
     );
     assert_eq!(
-        RamMachine::new(code.clone(), input4.clone()).run().unwrap(),
-        vec![input4.iter().sum()]
+        RamMachine::new(code.clone(), values(&input4))
+            .run()
+            .unwrap(),
+        values(&[input4.iter().sum::<i64>()])
     );
     assert_eq!(
-        RamMachine::new(code, input5.clone()).run().unwrap(),
-        vec![input5.iter().sum()]
+        RamMachine::new(code, values(&input5)).run().unwrap(),
+        values(&[input5.iter().sum::<i64>()])
     );
 }
 
@@ -47,24 +67,34 @@ fn square() {
     let input4 = vec![978314014];
     let input5 = vec![32423];
     assert_eq!(
-        RamMachine::new(code.clone(), input1.clone()).run().unwrap(),
-        vec![input1[0] * input1[0]]
+        RamMachine::new(code.clone(), values(&input1))
+            .run()
+            .unwrap(),
+        values(&[input1[0] * input1[0]])
     );
     assert_eq!(
-        RamMachine::new(code.clone(), input2.clone()).run().unwrap(),
-        vec![input2[0] * input2[0]]
+        RamMachine::new(code.clone(), values(&input2))
+            .run()
+            .unwrap(),
+        values(&[input2[0] * input2[0]])
     );
     assert_eq!(
-        RamMachine::new(code.clone(), input3.clone()).run().unwrap(),
-        vec![input3[0] * input3[0]]
+        RamMachine::new(code.clone(), values(&input3))
+            .run()
+            .unwrap(),
+        values(&[input3[0] * input3[0]])
     );
     assert_eq!(
-        RamMachine::new(code.clone(), input4.clone()).run().unwrap(),
-        vec![input4[0] * input4[0]]
+        RamMachine::new(code.clone(), values(&input4))
+            .run()
+            .unwrap(),
+        values(&[input4[0] * input4[0]])
     );
     assert_eq!(
-        RamMachine::new(code.clone(), input5.clone()).run().unwrap(),
-        vec![input5[0] * input5[0]]
+        RamMachine::new(code.clone(), values(&input5))
+            .run()
+            .unwrap(),
+        values(&[input5[0] * input5[0]])
     );
 }
 
@@ -81,24 +111,34 @@ fn sequence_length() {
     let mut input5 = vec![40; 1_000_000];
     input5.push(0);
     assert_eq!(
-        RamMachine::new(code.clone(), input1.clone()).run().unwrap(),
-        vec![(input1.len() - 1).try_into().unwrap()]
+        RamMachine::new(code.clone(), values(&input1))
+            .run()
+            .unwrap(),
+        values(&[i64::try_from(input1.len() - 1).unwrap()])
     );
     assert_eq!(
-        RamMachine::new(code.clone(), input2.clone()).run().unwrap(),
-        vec![(input2.len() - 1).try_into().unwrap()]
+        RamMachine::new(code.clone(), values(&input2))
+            .run()
+            .unwrap(),
+        values(&[i64::try_from(input2.len() - 1).unwrap()])
     );
     assert_eq!(
-        RamMachine::new(code.clone(), input3.clone()).run().unwrap(),
-        vec![(input3.len() - 1).try_into().unwrap()]
+        RamMachine::new(code.clone(), values(&input3))
+            .run()
+            .unwrap(),
+        values(&[i64::try_from(input3.len() - 1).unwrap()])
     );
     assert_eq!(
-        RamMachine::new(code.clone(), input4.clone()).run().unwrap(),
-        vec![(input4.len() - 1).try_into().unwrap()]
+        RamMachine::new(code.clone(), values(&input4))
+            .run()
+            .unwrap(),
+        values(&[i64::try_from(input4.len() - 1).unwrap()])
     );
     assert_eq!(
-        RamMachine::new(code.clone(), input5.clone()).run().unwrap(),
-        vec![(input5.len() - 1).try_into().unwrap()]
+        RamMachine::new(code.clone(), values(&input5))
+            .run()
+            .unwrap(),
+        values(&[i64::try_from(input5.len() - 1).unwrap()])
     );
 }
 
@@ -113,24 +153,34 @@ fn log() {
     let input4 = vec![3, 999_999_999_999_999_999];
     let input5 = vec![40, 1_000_000_000];
     assert_eq!(
-        RamMachine::new(code.clone(), input1.clone()).run().unwrap(),
-        vec![32]
+        RamMachine::new(code.clone(), values(&input1))
+            .run()
+            .unwrap(),
+        values(&[32])
     );
     assert_eq!(
-        RamMachine::new(code.clone(), input2.clone()).run().unwrap(),
-        vec![0]
+        RamMachine::new(code.clone(), values(&input2))
+            .run()
+            .unwrap(),
+        values(&[0])
     );
     assert_eq!(
-        RamMachine::new(code.clone(), input3.clone()).run().unwrap(),
-        vec![3]
+        RamMachine::new(code.clone(), values(&input3))
+            .run()
+            .unwrap(),
+        values(&[3])
     );
     assert_eq!(
-        RamMachine::new(code.clone(), input4.clone()).run().unwrap(),
-        vec![37]
+        RamMachine::new(code.clone(), values(&input4))
+            .run()
+            .unwrap(),
+        values(&[37])
     );
     assert_eq!(
-        RamMachine::new(code.clone(), input5.clone()).run().unwrap(),
-        vec![5]
+        RamMachine::new(code.clone(), values(&input5))
+            .run()
+            .unwrap(),
+        values(&[5])
     );
 }
 
@@ -145,23 +195,171 @@ fn unit_digit() {
     let input4 = vec![0];
     let input5 = vec![576];
     assert_eq!(
-        RamMachine::new(code.clone(), input1.clone()).run().unwrap(),
-        vec![9]
+        RamMachine::new(code.clone(), values(&input1))
+            .run()
+            .unwrap(),
+        values(&[9])
+    );
+    assert_eq!(
+        RamMachine::new(code.clone(), values(&input2))
+            .run()
+            .unwrap(),
+        values(&[5])
     );
     assert_eq!(
-        RamMachine::new(code.clone(), input2.clone()).run().unwrap(),
-        vec![5]
+        RamMachine::new(code.clone(), values(&input3))
+            .run()
+            .unwrap(),
+        values(&[1])
     );
     assert_eq!(
-        RamMachine::new(code.clone(), input3.clone()).run().unwrap(),
-        vec![1]
+        RamMachine::new(code.clone(), values(&input4))
+            .run()
+            .unwrap(),
+        values(&[0])
     );
     assert_eq!(
-        RamMachine::new(code.clone(), input4.clone()).run().unwrap(),
-        vec![0]
+        RamMachine::new(code.clone(), values(&input5))
+            .run()
+            .unwrap(),
+        values(&[6])
     );
+}
+
+#[test]
+fn step_limit() {
+    let code = RamCode::from_str("loop: jump loop").unwrap();
+    let err = RamMachine::new(code, vec![])
+        .with_max_steps(1000)
+        .run()
+        .unwrap_err();
+    assert_eq!(err.error, RamMachineError::StepLimitExceeded(1000));
+    assert_eq!(err.pointer, 0);
+    assert_eq!(err.instruction, Instruction::Jump("loop".to_owned()));
+
+    let path = "./examples/three_sum.ram";
+    let code = RamCode::from_str(&read_to_string(path).unwrap()).unwrap();
+    assert_eq!(
+        RamMachine::new(code.clone(), values(&[1, 2, 3]))
+            .with_max_steps(6)
+            .run(),
+        Ok(values(&[6]))
+    );
+    let err = RamMachine::new(code, values(&[1, 2, 3]))
+        .with_max_steps(5)
+        .run()
+        .unwrap_err();
+    assert_eq!(err.error, RamMachineError::StepLimitExceeded(5));
+    assert_eq!(err.pointer, 5);
+    assert_eq!(err.instruction, Instruction::Write(Operand::ValueInCell(0)));
+}
+
+#[test]
+fn run_stats() {
+    let path = "./examples/three_sum.ram";
+    let code = RamCode::from_str(&read_to_string(path).unwrap()).unwrap();
+    let (output, stats) = RamMachine::new(code, values(&[1, 2, 3]))
+        .run_with_stats()
+        .unwrap();
+    assert_eq!(output, values(&[6]));
+    assert_eq!(
+        stats,
+        RunStats {
+            uniform_time: 6,
+            logarithmic_time: 23,
+            uniform_space: 3,
+            logarithmic_space: 6,
+        }
+    );
+
+    let code = RamCode::from_str("load =-8\nstore 1\nload =1\nwrite ^0\nhalt").unwrap();
+    let (_, stats) = RamMachine::new(code, vec![]).run_with_stats().unwrap();
     assert_eq!(
-        RamMachine::new(code.clone(), input5.clone()).run().unwrap(),
-        vec![6]
+        stats,
+        RunStats {
+            uniform_time: 5,
+            logarithmic_time: 4 + (4 + 1) + 1 + (1 + 1 + 4) + 1,
+            uniform_space: 2,
+            logarithmic_space: 4 + 4,
+        }
     );
 }
+
+#[test]
+fn error_location() {
+    let code = RamCode::from_str("read 1\nload =3\n\n  store ^3 # indirect").unwrap();
+    assert_eq!(
+        RamMachine::new(code, values(&[5])).run(),
+        Err(ExecutionError {
+            pointer: 2,
+            instruction: Instruction::Store(CellOperand::AddressOfCellInCell(3)),
+            span: Some(Span {
+                line: 4,
+                columns: 2..10,
+                text: "store ^3".to_owned(),
+            }),
+            error: RamMachineError::ExpandError(ExpandError::ValueNotSet(3)),
+        })
+    );
+}
+
+#[test]
+fn step_back() {
+    let path = "./examples/log.ram";
+    let code = RamCode::from_str(&read_to_string(path).unwrap()).unwrap();
+    // The clock of the time limit starts with the first step, but it isn't compared
+    let mut machine = RamMachine::new(code, values(&[2, 8]))
+        .with_history(100)
+        .with_stats()
+        .with_time_limit(Duration::from_secs(60));
+    let mut states = vec![machine.clone()];
+    while machine.run_line().unwrap() == RunState::Running {
+        states.push(machine.clone());
+    }
+    assert_eq!(machine.output(), values(&[3]));
+    assert_eq!(machine.history().unwrap().len(), 39);
+
+    while let Some(state) = states.pop() {
+        assert!(machine.step_back());
+        assert_eq!(machine, state);
+    }
+    assert!(!machine.step_back());
+    assert_eq!(machine.stats(), RunStats::default());
+}
+
+#[test]
+fn bounded_history() {
+    let code = RamCode::from_str("load =0\nloop: add =1\njump loop").unwrap();
+    let mut machine = RamMachine::new(code, vec![]).with_history(10);
+    for _ in 0..100 {
+        machine.run_line().unwrap();
+    }
+    assert_eq!(machine.history().unwrap().first_step(), Some(90));
+    for _ in 0..10 {
+        assert!(machine.step_back());
+    }
+    assert!(!machine.step_back());
+    assert_eq!(machine.steps(), 90);
+    assert_eq!(machine.cell(0), Some(CellValue::from(45)));
+}
+
+#[test]
+fn recent_writes() {
+    let path = "./examples/three_sum.ram";
+    let code = RamCode::from_str(&read_to_string(path).unwrap()).unwrap();
+    let mut machine = RamMachine::new(code, values(&[1, 2, 3])).with_history(100);
+    for _ in 0..2 {
+        machine.run_line().unwrap();
+    }
+    assert_eq!(machine.input(), values(&[1, 2, 3]));
+    assert_eq!(machine.input_pointer(), 2);
+
+    let written: Vec<usize> = machine
+        .history()
+        .unwrap()
+        .iter()
+        .rev()
+        .filter_map(|delta| delta.tape_write.as_ref().map(|write| write.address))
+        .collect();
+    assert_eq!(written, vec![0, 1]);
+}
@@ -0,0 +1,142 @@
+use std::process::Command;
+use std::str::FromStr;
+use std::{env, fs};
+
+use ram_machine::diagnostic::{Diagnostic, Severity};
+use ram_machine::lint::{lint, Level, LintConfig, Rule};
+use ram_machine::locale::Lang;
+use ram_machine::parser::RamCode;
+
+fn run(source: &str, config: &LintConfig) -> Vec<Diagnostic> {
+    let code = RamCode::from_str(source).unwrap();
+    lint(source, &code, config, Lang::En)
+}
+
+fn codes(source: &str) -> Vec<(usize, &'static str)> {
+    run(source, &LintConfig::default())
+        .iter()
+        .map(|d| (d.span.line, d.code))
+        .collect()
+}
+
+#[test]
+fn rules() {
+    assert_eq!(codes("read 1\nload 1\nhalt\n"), []);
+    assert_eq!(
+        codes("jump A\nwrite 1\nA: halt\n"),
+        [(2, "unreachable-code")]
+    );
+    assert_eq!(codes("A: read 0\nhalt\n"), [(1, "unused-label")]);
+    assert_eq!(
+        codes("read 1\nload 2\nstore ^3\nhalt\n"),
+        [(2, "uninitialized-read"), (3, "uninitialized-read")]
+    );
+    assert_eq!(
+        codes("A: jzero A\nhalt\n"),
+        [(1, "uninitialized-buffer"), (1, "infinite-loop")]
+    );
+    assert_eq!(codes("load =1\ndiv =0\nhalt\n"), [(2, "division-by-zero")]);
+    assert_eq!(codes("read 0\nwrite 0\n"), [(2, "missing-halt")]);
+    assert_eq!(
+        codes("read 0\njzero END\nhalt\nEND:\n"),
+        [(4, "trailing-label")]
+    );
+}
+
+#[test]
+fn stored_on_some_path() {
+    let source = "\
+read 0
+jzero SKIP
+store 1
+SKIP: load 1
+halt
+";
+    assert_eq!(codes(source), []);
+    // A computed address may store any cell
+    assert_eq!(codes("read 1\nread ^1\nload 7\nhalt\n"), []);
+}
+
+#[test]
+fn levels() {
+    let source = "A: read 0\nload =1\ndiv =0\nhalt\n";
+    let diagnostics = run(source, &LintConfig::default());
+    let severities: Vec<Severity> = diagnostics.iter().map(|d| d.severity).collect();
+    assert_eq!(severities, [Severity::Warning, Severity::Error]);
+
+    let mut config = LintConfig::default();
+    config.set(Rule::UnusedLabel, Level::Allow);
+    config.set(Rule::DivisionByZero, Level::Warn);
+    let diagnostics = run(source, &config);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+
+    // `JZERO` leaves the loop when the buffer is not zero
+    let diagnostics = run("load =1\nA: jzero A\nhalt\n", &LintConfig::default());
+    assert_eq!(diagnostics[0].code, "infinite-loop");
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+
+    assert_eq!(Rule::from_str("missing-halt"), Ok(Rule::MissingHalt));
+    assert!(Rule::from_str("missing_halt").is_err());
+}
+
+#[test]
+fn suppression() {
+    let source = "\
+#!lint allow(missing-halt) deny(unused-label)
+A: read 0  # lint: allow(uninitialized-read)
+# lint: allow(unused-label)
+B: load 7
+C: write 0
+";
+    let diagnostics = run(source, &LintConfig::default());
+    let found: Vec<(usize, &str, Severity)> = diagnostics
+        .iter()
+        .map(|d| (d.span.line, d.code, d.severity))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            (2, "unused-label", Severity::Error),
+            (4, "uninitialized-read", Severity::Warning),
+            (5, "unused-label", Severity::Error),
+        ]
+    );
+
+    // The pragma overrides the configured level
+    let mut config = LintConfig::default();
+    config.set(Rule::MissingHalt, Level::Deny);
+    config.set(Rule::UnusedLabel, Level::Allow);
+    assert_eq!(run(source, &config), diagnostics);
+}
+
+#[test]
+fn flag_order() {
+    let path = env::temp_dir().join(format!("ram-lint-{}.ram", std::process::id()));
+    fs::write(&path, "A: read 0\nhalt\n").unwrap();
+    let lint = |flags: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_ram"))
+            .arg("lint")
+            .args(flags)
+            .env("LC_ALL", "C")
+            .arg(&path)
+            .output()
+            .unwrap()
+    };
+    // The last flag given for a rule wins
+    let output = lint(&["-D", "unused-label", "-A", "unused-label"]);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    let output = lint(&[
+        "-A",
+        "unused-label",
+        "-W",
+        "unused-label",
+        "-D",
+        "unused-label",
+    ]);
+    assert!(!output.status.success());
+    let output = lint(&["-D", "unused-label", "-W", "unused-label"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("warning[unused-label]"));
+}
@@ -0,0 +1,176 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use ram_machine::instruction::{Dialect, Instruction, InstructionParseError};
+use ram_machine::interpreter::{RamMachine, RamMachineError};
+use ram_machine::locale::{instruction_description, Lang, Localize, Localized};
+use ram_machine::operand::{CellOperand::*, CellValue, Operand::*};
+use ram_machine::parser::{CodeParseError, ParseOptions, RamCode};
+
+const POLISH: ParseOptions = ParseOptions {
+    lenient: false,
+    dialect: Dialect::Strict,
+    keywords: Lang::Pl,
+};
+
+#[test]
+fn polish_keywords() {
+    let code = "CZYTAJ 1\nŁadUJ 1\nskocz_zero koniec\nladuj =1\npisz 0\nkoniec: STOP";
+    let code = RamCode::parse(code, POLISH).unwrap();
+    assert_eq!(
+        code.instructions[..3],
+        [
+            Instruction::Read(AddressOfCell(1)),
+            Instruction::Load(ValueInCell(1)),
+            Instruction::Jzero("koniec".to_owned()),
+        ]
+    );
+    assert_eq!(
+        RamMachine::new(code.clone(), vec![CellValue::from(0)]).run(),
+        Ok(vec![])
+    );
+    assert_eq!(
+        RamMachine::new(code, vec![CellValue::from(4)]).run(),
+        Ok(vec![CellValue::from(1)])
+    );
+
+    // English keywords keep working, Polish ones are only accepted when enabled
+    assert!(RamCode::parse("read 0\npisz 0", POLISH).is_ok());
+    assert_eq!(
+        RamCode::parse("pisz 0", ParseOptions::default()).unwrap_err(),
+        CodeParseError::InstructionParseError(InstructionParseError::InvalidKeyword(
+            "pisz".to_owned()
+        ))
+    );
+    assert!(RamCode::parse("#!keywords pl\npisz =1", ParseOptions::default()).is_ok());
+    // Extended keywords still need the extended dialect
+    assert!(RamCode::parse("zwiększ", POLISH).is_err());
+}
+
+#[test]
+fn locale_names() {
+    assert_eq!(Lang::from_locale("pl_PL.UTF-8"), Some(Lang::Pl));
+    assert_eq!(Lang::from_locale("en_US"), Some(Lang::En));
+    assert_eq!(Lang::from_locale("C"), Some(Lang::En));
+    assert_eq!(Lang::from_locale("de_DE.UTF-8"), None);
+    assert_eq!("PL".parse(), Ok(Lang::Pl));
+    assert!("klingon".parse::<Lang>().is_err());
+}
+
+#[test]
+fn polish_messages() {
+    let error = RamMachineError::DivisionFailed(CellValue::from(0), CellValue::from(7));
+    assert_eq!(error.localize(Lang::En), error.to_string());
+    assert_eq!(
+        Localized(error, Lang::Pl).to_string(),
+        "Dzielenie `7` przez `0` nie powiodło się."
+    );
+
+    let error = RamMachine::from_str("load 1", Vec::new())
+        .unwrap()
+        .run()
+        .unwrap_err();
+    assert_eq!(
+        error.localize(Lang::Pl),
+        "1: load 1 — Próba odczytu z komórki o adresie `1`, której wartość nigdy nie została ustawiona."
+    );
+
+    let source = "jump";
+    let errors = RamCode::parse_all(source, POLISH).unwrap_err();
+    assert_eq!(
+        errors.localize(Lang::Pl),
+        "W linii 1 znaleziono błąd: Oczekiwano etykiety po słowie kluczowym jump, nie znaleziono niczego\n"
+    );
+    let diagnostic = &errors.diagnostics_in(source, Lang::Pl)[0];
+    assert_eq!(
+        diagnostic.help.as_deref(),
+        Some("dodaj etykietę, do której ma nastąpić skok, np. `JUMP PETLA`")
+    );
+    assert!(diagnostic
+        .render_in("a.ram", Lang::Pl)
+        .starts_with("błąd[E0002]: Oczekiwano etykiety"));
+}
+
+#[test]
+fn keywords_independent_of_messages() {
+    let help = |source: &str, options: ParseOptions| {
+        let errors = RamCode::parse_all(source, options).unwrap_err();
+        errors.diagnostics_in(source, Lang::Pl)[0].help.clone()
+    };
+    // Polish messages don't make the Polish keywords valid or suggested
+    assert_eq!(help("czytja 1", ParseOptions::default()), None);
+    assert_eq!(
+        help("czytja 1", POLISH).as_deref(),
+        Some("czy chodziło o `czytaj`?")
+    );
+    assert_eq!(
+        help("#!keywords pl\nczytja 1", ParseOptions::default()).as_deref(),
+        Some("czy chodziło o `czytaj`?")
+    );
+}
+
+#[test]
+fn polish_run_stats() {
+    let (_, stats) = RamMachine::from_str("load =5\nwrite 0", Vec::new())
+        .unwrap()
+        .run_with_stats()
+        .unwrap();
+    assert_eq!(stats.to_string(), stats.localize(Lang::En));
+    assert!(stats
+        .localize(Lang::Pl)
+        .starts_with("Jednorodny koszt czasowy: 2\n"));
+}
+
+#[test]
+fn polish_interactive_tools() {
+    let ram = |args: &[&str], stdin: &str| {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_ram"))
+            .args(["--lang", "pl"])
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(stdin.as_bytes())
+            .unwrap();
+        String::from_utf8(child.wait_with_output().unwrap().stdout).unwrap()
+    };
+    let output = ram(&["run", "-s", "examples/three_sum.ram", "1", "2", "3"], "");
+    assert!(output.contains("Jednorodny koszt czasowy: "), "{output}");
+
+    let output = ram(
+        &["debug", "examples/three_sum.ram", "1", "2", "3"],
+        "f\nx\nq\n",
+    );
+    assert!(output.contains("Maszyna zatrzymała się po "), "{output}");
+    assert!(
+        output.contains("Nieznane polecenie `x`, zobacz help"),
+        "{output}"
+    );
+
+    let output = ram(&["repl"], ":undo\n:input 1 a\n:load /nonexistent.ram\n");
+    assert!(output.starts_with("REPL maszyny RAM"), "{output}");
+    assert!(output.contains("Nie ma czego cofnąć"), "{output}");
+    assert!(
+        output.contains("Nie można zamienić `a` na liczbę całkowitą"),
+        "{output}"
+    );
+    assert!(
+        output.contains("Nie można odczytać kodu maszyny RAM z pliku"),
+        "{output}"
+    );
+}
+
+#[test]
+fn instruction_descriptions() {
+    assert_eq!(
+        instruction_description("halt", Lang::Pl).as_deref(),
+        Some("Zatrzymuje maszynę.")
+    );
+    assert_eq!(instruction_description("HALT", Lang::En), None);
+    assert_eq!(instruction_description("nop", Lang::En), None);
+}
@@ -1,4 +1,22 @@
 #[cfg(test)]
+mod analysis;
+mod arithmetic;
+mod bigint;
+mod cfg;
+mod compiled;
+mod cst;
+mod dap;
+mod dataflow;
+mod debugger;
+mod diagnostic;
+mod dialect;
+mod format;
 mod interpreter;
+mod lint;
+mod locale;
+mod observer;
 mod operand;
+mod optimize;
 mod parser;
+mod stream;
+mod trace;
@@ -0,0 +1,104 @@
+use std::sync::{Arc, Mutex};
+
+use num_traits::ToPrimitive;
+
+use ram_machine::instruction::Instruction;
+use ram_machine::interpreter::RamMachine;
+use ram_machine::observer::ExecutionObserver;
+use ram_machine::operand::{CellAddress, CellValue};
+
+const ECHO_POSITIVE: &str = "
+LOOP: read 0
+      jzero END
+      write 0
+      jump LOOP
+END:  halt
+";
+
+#[derive(Debug, PartialEq, Eq)]
+enum Event {
+    Before(usize),
+    TapeWrite(CellAddress, Option<i64>, i64),
+    Read(usize, i64),
+    Write(i64),
+    Jump(usize, usize),
+    Halt(u64),
+}
+
+/// Values are recorded as `i64`, so that the expected events read the same with `bigint`.
+fn int(value: &CellValue) -> i64 {
+    value.to_i64().unwrap()
+}
+
+#[derive(Default)]
+struct Recorder(Vec<Event>);
+
+impl ExecutionObserver for Recorder {
+    fn before_instruction(&mut self, machine: &RamMachine, _: &Instruction) {
+        self.0.push(Event::Before(machine.pointer()))
+    }
+
+    fn on_tape_write(&mut self, address: CellAddress, old: Option<&CellValue>, new: &CellValue) {
+        self.0
+            .push(Event::TapeWrite(address, old.map(int), int(new)))
+    }
+
+    fn on_read(&mut self, index: usize, value: &CellValue) {
+        self.0.push(Event::Read(index, int(value)))
+    }
+
+    fn on_write(&mut self, value: &CellValue) {
+        self.0.push(Event::Write(int(value)))
+    }
+
+    fn on_jump(&mut self, from: usize, to: usize) {
+        self.0.push(Event::Jump(from, to))
+    }
+
+    fn on_halt(&mut self, machine: &RamMachine) {
+        self.0.push(Event::Halt(machine.steps()))
+    }
+}
+
+#[test]
+fn observer_events() {
+    let recorder = Arc::new(Mutex::new(Recorder::default()));
+    let machine = RamMachine::from_str(ECHO_POSITIVE, vec![CellValue::from(7), CellValue::from(0)])
+        .unwrap()
+        .with_observer(recorder.clone());
+    assert_eq!(machine.run().unwrap(), vec![CellValue::from(7)]);
+
+    use Event::*;
+    assert_eq!(
+        recorder.lock().unwrap().0,
+        vec![
+            Before(0),
+            Read(0, 7),
+            TapeWrite(0, None, 7),
+            Before(1),
+            Before(2),
+            Write(7),
+            Before(3),
+            Jump(3, 0),
+            Before(0),
+            Read(1, 0),
+            TapeWrite(0, Some(7), 0),
+            Before(1),
+            Jump(1, 4),
+            Before(4),
+            Halt(7),
+        ]
+    );
+}
+
+#[test]
+fn observers_are_not_cloned() {
+    let recorder = Arc::new(Mutex::new(Recorder::default()));
+    let machine = RamMachine::from_str(ECHO_POSITIVE, vec![CellValue::from(0)])
+        .unwrap()
+        .with_observer(recorder.clone());
+    let copy = machine.clone();
+    assert_eq!(machine, copy);
+    copy.run().unwrap();
+    assert!(recorder.lock().unwrap().0.is_empty());
+}
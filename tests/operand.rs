@@ -1,19 +1,26 @@
+// `CellValue` is `i64` unless the `bigint` feature is enabled
+#![allow(clippy::useless_vec, clippy::useless_conversion)]
+
 use ram_machine::operand::*;
 use CellOperand::*;
 use Operand::*;
 
+fn cell(value: i64) -> Option<CellValue> {
+    Some(value.into())
+}
+
 #[test]
 fn expand_address_of_cell() {
     let o = AddressOfCell(2);
-    assert_eq!(o.expand(&vec![Some(0), None, Some(5)]), Ok(2));
+    assert_eq!(o.expand(&vec![cell(0), None, cell(5)]), Ok(2));
 }
 
 #[test]
 fn expand_address_of_cell_in_cell() {
     let o = AddressOfCellInCell(6);
     let mut tape = vec![None; 6];
-    tape.push(Some(7));
-    tape.push(Some(2));
+    tape.push(cell(7));
+    tape.push(cell(2));
     assert_eq!(o.expand(&tape), Ok(7));
 }
 
@@ -25,14 +32,17 @@ fn expand_address_of_cell_in_cell_fail() {
     tape.push(None);
     assert_eq!(o.expand(&tape), Err(ExpandError::ValueNotSet(10)));
     tape.pop();
-    tape.push(Some(-5));
-    assert_eq!(o.expand(&tape), Err(ExpandError::ConvertError(-5, 10)));
+    tape.push(cell(-5));
+    assert_eq!(
+        o.expand(&tape),
+        Err(ExpandError::ConvertError(CellValue::from(-5), 10))
+    );
 }
 
 #[test]
 fn expand_number() {
-    let o = Number(10000000);
-    assert_eq!(o.expand(&vec![]), Ok(&10000000));
+    let o = Number(CellValue::from(10000000));
+    assert_eq!(o.expand(&vec![]), Ok(&CellValue::from(10000000)));
 }
 
 #[test]
@@ -40,8 +50,8 @@ fn expand_value_in_cell() {
     let o = ValueInCell(8);
     let mut tape = vec![None; 8];
     assert_eq!(o.expand(&tape), Err(ExpandError::ValueNotSet(8)));
-    tape.push(Some(20));
-    assert_eq!(o.expand(&tape), Ok(&20));
+    tape.push(cell(20));
+    assert_eq!(o.expand(&tape), Ok(&CellValue::from(20)));
 }
 
 #[test]
@@ -51,10 +61,13 @@ fn expand_value_of_value_in_cell() {
     assert_eq!(o.expand(&tape), Err(ExpandError::ValueNotSet(20)));
     tape.push(None);
     assert_eq!(o.expand(&tape), Err(ExpandError::ValueNotSet(20)));
-    tape[20] = Some(-500);
-    assert_eq!(o.expand(&tape), Err(ExpandError::ConvertError(-500, 20)));
-    tape[20] = Some(16);
+    tape[20] = cell(-500);
+    assert_eq!(
+        o.expand(&tape),
+        Err(ExpandError::ConvertError(CellValue::from(-500), 20))
+    );
+    tape[20] = cell(16);
     assert_eq!(o.expand(&tape), Err(ExpandError::ValueNotSet(16)));
-    tape[16] = Some(8);
-    assert_eq!(o.expand(&tape), Ok(&8));
+    tape[16] = cell(8);
+    assert_eq!(o.expand(&tape), Ok(&CellValue::from(8)));
 }
@@ -0,0 +1,151 @@
+// `CellValue` is `i64` unless the `bigint` feature is enabled
+#![allow(clippy::useless_conversion)]
+
+use std::fs;
+use std::str::FromStr;
+
+use ram_machine::interpreter::RamMachine;
+use ram_machine::operand::CellValue;
+use ram_machine::optimize::{optimize, Pass};
+use ram_machine::parser::{ParseOptions, RamCode};
+
+fn optimized(source: &str) -> String {
+    let code = RamCode::from_str(source).unwrap();
+    optimize(&code, &Pass::ALL).to_string()
+}
+
+#[test]
+fn constant_folding() {
+    assert_eq!(
+        optimized("load =2\nadd =3\nmult =4\nwrite 0\n"),
+        "LOAD =20\nWRITE 0\n"
+    );
+    // The result depends on the arithmetic mode
+    assert_eq!(
+        optimized("load =-7\ndiv =2\nwrite 0\n"),
+        "LOAD =-7\nDIV =2\nWRITE 0\n"
+    );
+    assert_eq!(
+        optimized("load =1\ndiv =0\nwrite 0\n"),
+        "LOAD =1\nDIV =0\nWRITE 0\n"
+    );
+    // Jumping to `ADD` skips `LOAD`
+    assert_eq!(
+        optimized("read 0\njzero A\nload =1\nA: add =1\nwrite 0\n"),
+        "READ 0\nJZERO A\nLOAD =1\nA: ADD =1\nWRITE 0\n"
+    );
+}
+
+#[test]
+fn redundant_loads() {
+    assert_eq!(
+        optimized("read 0\nstore 1\nload 1\nwrite 0\n"),
+        "READ 0\nSTORE 1\nWRITE 0\n"
+    );
+    assert_eq!(
+        optimized("read 1\nread 0\nstore ^1\nload ^1\nwrite 0\n"),
+        "READ 1\nREAD 0\nSTORE ^1\nLOAD ^1\nWRITE 0\n"
+    );
+}
+
+#[test]
+fn jumps_and_labels() {
+    assert_eq!(
+        optimized("read 0\njzero A\nwrite 0\nA: jump B\nwrite 1\nB:\nC: halt\n"),
+        "READ 0\nJZERO B\nWRITE 0\nB: HALT\n"
+    );
+    assert_eq!(
+        optimized("read 0\njump A\nA: write 0\n"),
+        "READ 0\nWRITE 0\n"
+    );
+    assert_eq!(
+        optimized("read 0\nA: jzero END\njump A\nEND:\n"),
+        "READ 0\nA: JZERO END\nJUMP A\nEND:\n"
+    );
+}
+
+/// Checks that optimizing `source` keeps its results on every input
+fn assert_same_output(source: &str, options: ParseOptions, name: &str) {
+    let inputs: [&[i64]; 5] = [&[], &[0], &[7, 3, 5], &[-12, 4, 1], &[1024, 2, 3]];
+    let code = RamCode::parse(source, options).unwrap();
+    let optimized = optimize(&code, &Pass::ALL);
+    assert_eq!(
+        RamCode::parse(&optimized.to_string(), options).unwrap(),
+        optimized,
+        "{name}"
+    );
+    for input in inputs {
+        let input: Vec<CellValue> = input.iter().map(|&v| v.into()).collect();
+        let expected = RamMachine::new(code.clone(), input.clone())
+            .with_max_steps(10_000)
+            .run();
+        let actual = RamMachine::new(optimized.clone(), input.clone())
+            .with_max_steps(10_000)
+            .run();
+        assert_eq!(
+            actual.map_err(|e| e.error),
+            expected.map_err(|e| e.error),
+            "{name} {input:?}"
+        );
+    }
+}
+
+#[test]
+fn same_output() {
+    for entry in fs::read_dir("examples").unwrap() {
+        let path = entry.unwrap().path();
+        let source = fs::read_to_string(&path).unwrap();
+        assert_same_output(&source, ParseOptions::default(), &path.to_string_lossy());
+    }
+}
+
+#[test]
+fn threading_into_loops() {
+    // A chain of jumps ending in a loop
+    assert_same_output(
+        "read 0\nLOOP: jzero END\nsub =1\njump NEXT\nwrite 0\nNEXT: jump LOOP\nEND: write 0\n",
+        ParseOptions::default(),
+        "chain",
+    );
+    // Jumps forming a cycle which is only entered on zero
+    assert_same_output(
+        "read 0\njzero A\nwrite 0\nhalt\nA: jump B\nB: jump A\n",
+        ParseOptions::default(),
+        "cycle",
+    );
+    assert_same_output(
+        "read 0\nA: jump B\nB: jzero A\nwrite 0\n",
+        ParseOptions::default(),
+        "conditional cycle",
+    );
+}
+
+#[test]
+fn folding_next_to_labels() {
+    // Jumping back to a folded `LOAD`
+    assert_same_output(
+        "read 1\nA: load =2\nadd =3\nwrite 0\nload 1\nsub =1\nstore 1\njgtz A\n",
+        ParseOptions::default(),
+        "label on load",
+    );
+    // Jumping back to the `ADD` after a constant `LOAD`
+    assert_same_output(
+        "read 1\nload =2\nA: add =3\nstore 2\nwrite 0\nload 1\nsub =1\nstore 1\nload 2\njgtz A\n",
+        ParseOptions::default(),
+        "label on add",
+    );
+}
+
+#[test]
+fn lenient_undefined_labels() {
+    let lenient = ParseOptions {
+        lenient: true,
+        ..Default::default()
+    };
+    assert_same_output("read 0\njzero MISSING\nwrite 0\nhalt\n", lenient, "missing");
+    assert_same_output(
+        "read 0\njump A\nA: jgtz MISSING\nload =1\nadd =1\nwrite 0\n",
+        lenient,
+        "threaded missing",
+    );
+}
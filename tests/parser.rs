@@ -1,6 +1,8 @@
 use ram_machine::instruction::{Instruction, InstructionParseError};
-use ram_machine::operand::{CellOperand, Operand};
-use ram_machine::parser::{CodeParseError, RamCode};
+use ram_machine::operand::{CellOperand, CellValue, Operand};
+use ram_machine::parser::source_map::{LabelSpan, Span};
+use ram_machine::parser::validate::ValidationError;
+use ram_machine::parser::{CodeParseError, ParseOptions, RamCode};
 use std::collections::HashMap;
 use std::str::FromStr;
 use CellOperand::AddressOfCell as AC;
@@ -34,15 +36,16 @@ halt: halt
 
     let expected_code = RamCode {
         instructions: vec![
-            L(Num(10)),
+            L(Num(CellValue::from(10))),
             W(VC(0)),
-            A(Num(-1)),
+            A(Num(CellValue::from(-1))),
             W(VC(0)),
             Jz(String::from("halt")),
             Jmp(String::from("loop")),
             H,
         ],
         jump_table: HashMap::from([("loop".to_owned(), 2), ("halt".to_owned(), 6)]),
+        ..Default::default()
     };
     assert_eq!(RamCode::from_str(code), Ok(expected_code));
 }
@@ -67,15 +70,16 @@ halt
         instructions: vec![
             R(AC(1)),
             L(VC(1)),
-            A(Num(-1)),
+            A(Num(CellValue::from(-1))),
             S(AC(1)),
-            A(Num(3)),
+            A(Num(CellValue::from(3))),
             R(AOC(0)),
             L(VC(1)),
             Jgtz(String::from("label")),
             H,
         ],
         jump_table: HashMap::from([("label".to_owned(), 1)]),
+        ..Default::default()
     };
     assert_eq!(RamCode::from_str(code), Ok(expected_code));
 }
@@ -91,8 +95,15 @@ div =-5
 ";
 
     let expected_code = RamCode {
-        instructions: vec![L(Num(-3)), R(AC(1)), A(VC(1)), M(Num(-2)), D(Num(-5))],
+        instructions: vec![
+            L(Num(CellValue::from(-3))),
+            R(AC(1)),
+            A(VC(1)),
+            M(Num(CellValue::from(-2))),
+            D(Num(CellValue::from(-5))),
+        ],
         jump_table: HashMap::new(),
+        ..Default::default()
     };
     assert_eq!(RamCode::from_str(code), Ok(expected_code));
 }
@@ -115,11 +126,12 @@ halt
             L(VC(3)),
             S(AOC(4)),
             W(VC(3)),
-            M(Num(2)),
+            M(Num(CellValue::from(2))),
             D(VOC(4)),
             H,
         ],
         jump_table: HashMap::new(),
+        ..Default::default()
     };
     assert_eq!(RamCode::from_str(code), Ok(expected_code));
 }
@@ -135,8 +147,14 @@ add =5
 ";
 
     let expected_code = RamCode {
-        instructions: vec![R(AC(1)), L(VC(1)), M(Num(2)), A(Num(5))],
+        instructions: vec![
+            R(AC(1)),
+            L(VC(1)),
+            M(Num(CellValue::from(2))),
+            A(Num(CellValue::from(5))),
+        ],
         jump_table: HashMap::new(),
+        ..Default::default()
     };
     assert_eq!(RamCode::from_str(code), Ok(expected_code));
 }
@@ -150,8 +168,9 @@ add =5
 ";
 
     let expected_code = RamCode {
-        instructions: vec![R(AC(3)), L(VC(3)), A(Num(5))],
+        instructions: vec![R(AC(3)), L(VC(3)), A(Num(CellValue::from(5)))],
         jump_table: HashMap::new(),
+        ..Default::default()
     };
     assert_eq!(RamCode::from_str(code), Ok(expected_code));
 }
@@ -164,6 +183,7 @@ fn parse_label_and_comment() {
     let expected_code = RamCode {
         instructions: vec![],
         jump_table: HashMap::from([(label.to_owned(), 0)]),
+        ..Default::default()
     };
     assert_eq!(RamCode::from_str(&code), Ok(expected_code));
 }
@@ -193,8 +213,9 @@ Load =4
     );
 
     let expected_code = RamCode {
-        instructions: vec![L(Num(4)), S(AC(3))],
+        instructions: vec![L(Num(CellValue::from(4))), S(AC(3))],
         jump_table: HashMap::from([(label.to_owned(), 1)]),
+        ..Default::default()
     };
     assert_eq!(RamCode::from_str(&code), Ok(expected_code));
 }
@@ -208,6 +229,7 @@ lOaD 1
     let expected_code = RamCode {
         instructions: vec![L(VC(1))],
         jump_table: HashMap::new(),
+        ..Default::default()
     };
     assert_eq!(RamCode::from_str(code), Ok(expected_code));
 }
@@ -236,14 +258,16 @@ store 1
 ";
 
     let expected_code = RamCode {
-        instructions: vec![R(AC(1)), L(VC(1)), A(Num(3)), S(AC(1))],
+        instructions: vec![R(AC(1)), L(VC(1)), A(Num(CellValue::from(3))), S(AC(1))],
         jump_table: HashMap::new(),
+        ..Default::default()
     };
 
     assert_eq!(RamCode::from_str(code), Ok(expected_code));
 }
 
 #[test]
+#[allow(clippy::redundant_pattern_matching)]
 fn parse_duplicate_label() {
     let code = "
 label: read 3
@@ -252,3 +276,130 @@ label: add =5
 
     assert!(matches!(RamCode::from_str(code), Err(_)));
 }
+
+#[test]
+fn parse_source_map() {
+    let code = "
+# comment
+read 1
+LOOP:  load 1 # comment
+       halt
+";
+
+    let source_map = RamCode::from_str(code).unwrap().source_map;
+    assert_eq!(source_map.lines, 5);
+    assert_eq!(
+        source_map.instructions,
+        vec![
+            Span {
+                line: 3,
+                columns: 0..6,
+                text: "read 1".to_owned()
+            },
+            Span {
+                line: 4,
+                columns: 7..13,
+                text: "load 1".to_owned()
+            },
+            Span {
+                line: 5,
+                columns: 7..11,
+                text: "halt".to_owned()
+            },
+        ]
+    );
+    assert_eq!(
+        source_map.labels,
+        vec![LabelSpan {
+            label: "LOOP".to_owned(),
+            span: Span {
+                line: 4,
+                columns: 0..4,
+                text: "LOOP".to_owned()
+            },
+            target: 1,
+        }]
+    );
+}
+
+#[test]
+fn add_instruction_source_map() {
+    let mut code = RamCode::from_str("read 1\n").unwrap();
+    code.add_instruction(W(VC(1)));
+    assert_eq!(code.source_map.instructions.len(), code.instructions.len());
+    assert!(code.source_map.instruction(0).is_some());
+    assert_eq!(code.source_map.instruction(1), None);
+}
+
+#[test]
+fn parse_undefined_label() {
+    let code = "
+read 0
+jzero end
+halt
+";
+
+    assert_eq!(
+        RamCode::from_str(code),
+        Err(CodeParseError::ValidationErrors(vec![
+            ValidationError::UndefinedLabel("end".to_owned(), 1)
+        ]))
+    );
+}
+
+#[test]
+fn parse_every_validation_error() {
+    let code = "
+jump start
+jzero end
+";
+
+    assert_eq!(
+        RamCode::from_str(code),
+        Err(CodeParseError::ValidationErrors(vec![
+            ValidationError::UndefinedLabel("start".to_owned(), 0),
+            ValidationError::UndefinedLabel("end".to_owned(), 1),
+        ]))
+    );
+}
+
+#[test]
+fn parse_lenient() {
+    let code = "
+label: read 0
+label: jzero end
+";
+
+    let expected_code = RamCode {
+        instructions: vec![R(AC(0)), Jz(String::from("end"))],
+        jump_table: HashMap::from([("label".to_owned(), 1)]),
+        ..Default::default()
+    };
+    assert_eq!(
+        RamCode::parse(
+            code,
+            ParseOptions {
+                lenient: true,
+                ..Default::default()
+            }
+        ),
+        Ok(expected_code)
+    );
+}
+
+#[test]
+fn validate_built_code() {
+    let code = RamCode {
+        instructions: vec![Jmp(String::from("end")), Jmp(String::from("missing"))],
+        jump_table: HashMap::from([("end".to_owned(), 2)]),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        code.validate(),
+        Err(vec![ValidationError::UndefinedLabel(
+            "missing".to_owned(),
+            1
+        )])
+    );
+}
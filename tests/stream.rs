@@ -0,0 +1,86 @@
+// `CellValue` is `i64` unless the `bigint` feature is enabled
+#![allow(clippy::useless_conversion)]
+
+use std::collections::VecDeque;
+use std::io::Cursor;
+use std::sync::mpsc;
+use std::thread;
+
+use ram_machine::interpreter::{RamMachine, RamMachineError};
+use ram_machine::operand::CellValue;
+use ram_machine::stream::{InputSource, OutputSink, ReaderSource, StreamError, WriterSink};
+
+const ECHO: &str = "
+LOOP: read 0
+      jzero END
+      write 0
+      jump LOOP
+END:  halt
+";
+
+fn values(values: &[i64]) -> Vec<CellValue> {
+    values.iter().map(|&v| v.into()).collect()
+}
+
+#[test]
+fn buffered_input_comes_first() {
+    let machine = RamMachine::from_str(ECHO, values(&[1, 2]))
+        .unwrap()
+        .with_input_source(VecDeque::from(values(&[3, 0])));
+    assert_eq!(machine.run().unwrap(), values(&[1, 2, 3]));
+}
+
+#[test]
+fn reader_source() {
+    let mut source = ReaderSource::new(Cursor::new("1 -2\n\n  3\nx"));
+    assert_eq!(source.read(), Ok(Some(CellValue::from(1))));
+    assert_eq!(source.read(), Ok(Some(CellValue::from(-2))));
+    assert_eq!(source.read(), Ok(Some(CellValue::from(3))));
+    assert_eq!(
+        source.read(),
+        Err(StreamError::InvalidInput("x".to_owned()))
+    );
+    assert_eq!(source.read(), Ok(None));
+}
+
+#[test]
+fn invalid_stream_input() {
+    let error = RamMachine::from_str(ECHO, Vec::new())
+        .unwrap()
+        .with_input_source(ReaderSource::new(Cursor::new("5 five")))
+        .run()
+        .unwrap_err();
+    assert_eq!(
+        error.error,
+        RamMachineError::StreamError(StreamError::InvalidInput("five".to_owned()))
+    );
+}
+
+#[test]
+fn writer_sink() {
+    let mut sink = WriterSink::new(Vec::new());
+    sink.write(&CellValue::from(4)).unwrap();
+    sink.write(&CellValue::from(-7)).unwrap();
+    assert_eq!(sink.into_inner(), b"4\n-7\n");
+    let mut machine_output = Vec::new();
+    machine_output.write(&CellValue::from(1)).unwrap();
+    assert_eq!(machine_output, values(&[1]));
+}
+
+#[test]
+fn channels() {
+    let (input, source) = mpsc::channel();
+    let (sink, output) = mpsc::channel();
+    let machine = RamMachine::from_str(ECHO, Vec::new())
+        .unwrap()
+        .with_input_source(source)
+        .with_output_sink(sink);
+    let runner = thread::spawn(move || machine.run().unwrap());
+
+    input.send(CellValue::from(8)).unwrap();
+    assert_eq!(output.recv(), Ok(CellValue::from(8)));
+    input.send(CellValue::from(9)).unwrap();
+    assert_eq!(output.recv(), Ok(CellValue::from(9)));
+    input.send(CellValue::from(0)).unwrap();
+    assert_eq!(runner.join().unwrap(), values(&[8, 9]));
+}
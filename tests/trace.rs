@@ -0,0 +1,63 @@
+// `CellValue` is `i64` unless the `bigint` feature is enabled
+#![allow(clippy::useless_conversion)]
+
+use ram_machine::interpreter::{RamMachine, RunState};
+use ram_machine::operand::CellValue;
+use ram_machine::trace::{TraceRecord, CSV_HEADER};
+
+const DOUBLE: &str = "
+read 0
+mult =2
+store 1
+write 1
+halt
+";
+
+fn trace(input: Vec<i64>) -> Vec<TraceRecord> {
+    let input = input.into_iter().map(CellValue::from).collect();
+    let mut machine = RamMachine::from_str(DOUBLE, input).unwrap().with_trace();
+    while let Ok(RunState::Running) = machine.run_line() {}
+    machine.take_trace()
+}
+
+#[test]
+fn trace_records() {
+    let records = trace(vec![21]);
+    assert_eq!(records.len(), 5);
+    assert_eq!(
+        records[0],
+        TraceRecord {
+            step: 0,
+            pointer: 0,
+            instruction: "READ 0".to_owned(),
+            line: Some(2),
+            buffer_before: None,
+            buffer_after: Some(CellValue::from(21)),
+            tape_write: Some((0, CellValue::from(21))),
+            input: Some(CellValue::from(21)),
+            output: None,
+        }
+    );
+    assert_eq!(records[1].buffer_before, Some(CellValue::from(21)));
+    assert_eq!(records[1].buffer_after, Some(CellValue::from(42)));
+    assert_eq!(records[2].tape_write, Some((1, CellValue::from(42))));
+    assert_eq!(records[3].output, Some(CellValue::from(42)));
+    assert_eq!(records[3].input, None);
+}
+
+#[test]
+fn trace_stops_at_error() {
+    assert!(trace(Vec::new()).is_empty());
+}
+
+#[test]
+fn trace_formats() {
+    let records = trace(vec![21]);
+    assert_eq!(
+        records[3].to_json().to_string(),
+        r#"{"buffer_after":42,"buffer_before":42,"input":null,"instruction":"WRITE 1","line":5,"output":42,"pointer":3,"step":3,"tape_write":null}"#
+    );
+    assert_eq!(CSV_HEADER.split(',').count(), 10);
+    assert_eq!(records[0].to_csv(), "0,0,READ 0,2,,21,0,21,21,");
+    assert_eq!(records[3].to_csv(), "3,3,WRITE 1,5,42,42,,,,42");
+}